@@ -83,7 +83,7 @@ fn main() {
 			.map(|c| *c as char)
 			.collect();
 
-    main_chars.append(&mut vec!['π', '"']);
+    main_chars.append(&mut vec!['π', '"', '×', '÷', '·', '−']);
 
     {
         let filtered_chars: Vec<char> = main_chars
@@ -111,7 +111,7 @@ fn main() {
                     font_stripper(
                         "Ubuntu-Light.ttf",
                         "ubuntu-light.ttf",
-                        [main_chars, vec!['∫']].concat(),
+                        [main_chars, vec!['∫', '√', 'Σ']].concat(),
                     )
                     .unwrap(),
                 )),
@@ -4,9 +4,16 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 
 /// REMEMBER TO UPDATE THIS IF EXMEX ADDS NEW FUNCTIONS
-const SUPPORTED_FUNCTIONS: [&str; 22] = [
+const SUPPORTED_FUNCTIONS: [&str; 56] = [
 	"abs", "signum", "sin", "cos", "tan", "asin", "acos", "atan", "sinh", "cosh", "tanh", "floor",
 	"round", "ceil", "trunc", "fract", "exp", "sqrt", "cbrt", "ln", "log2", "log10",
+	// Operators evaluated numerically by the parsing crate (see `src/operators.rs`)
+	"integral", "deriv", "sum",
+	// Functions evaluated numerically by the parsing crate (see `src/functions.rs`)
+	"gamma", "factorial", "erf", "erfc", "beta", "besselj0", "besselj1", "sec", "csc", "cot", "asec",
+	"acsc", "acot", "atan2", "min", "max", "mod", "nCr", "nPr", "log", "sigmoid", "normalpdf",
+	"normalcdf", "tpdf", "tcdf", "chisqpdf", "chisqcdf", "binompdf", "binomcdf", "poissonpdf",
+	"poissoncdf",
 ];
 
 fn main() {
@@ -0,0 +1,827 @@
+use crate::ast::{BinaryOp, Constant, Expr};
+use std::cmp::Ordering;
+
+/// Largest power of a sum that is multiplied out when expanding
+const MAX_EXPANDED_POWER: f64 = 12.0;
+
+/// Largest denominator coefficients are written as fractions with (such as `x/3`)
+const MAX_DENOMINATOR: i64 = 1000;
+
+/// Coefficients closer to zero than this are dropped, so rounding error doesn't leave terms such
+/// as `0.00000000000000002·x` behind
+const EPSILON: f64 = 1e-12;
+
+/// Largest constant term or leading coefficient of a polynomial whose divisors are tried as roots
+const MAX_ROOT_SEARCH: i128 = 1_000_000;
+
+/// Functions `f` with `f(-x) = -f(x)`
+const ODD_FUNCTIONS: [&str; 7] = ["sin", "tan", "asin", "atan", "sinh", "tanh", "cbrt"];
+
+/// Functions `f` with `f(-x) = f(x)`
+const EVEN_FUNCTIONS: [&str; 3] = ["cos", "cosh", "abs"];
+
+/// Ways an expression can be rewritten
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rewrite {
+	/// Combines like terms and applies identities, such as `sin(x)^2 + cos(x)^2` to `1`
+	Simplify,
+
+	/// Multiplies out products and powers of sums, such as `(x + 1)^2` to `x^2 + 2·x + 1`
+	Expand,
+
+	/// Pulls out common factors and the roots of polynomials, such as `x^2 - 1` to
+	/// `(x + 1)·(x - 1)`
+	Factor,
+}
+
+impl Rewrite {
+	pub const ALL: [Rewrite; 3] = [Self::Simplify, Self::Expand, Self::Factor];
+
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Simplify => "Simplify",
+			Self::Expand => "Expand",
+			Self::Factor => "Factor",
+		}
+	}
+
+	pub const fn description(self) -> &'static str {
+		match self {
+			Self::Simplify => "Combine like terms and apply identities such as sin²+cos²=1",
+			Self::Expand => "Multiply out products and powers of sums",
+			Self::Factor => "Pull out common factors and factor polynomials by their rational roots",
+		}
+	}
+
+	/// Rewrites `expr`, treating it as a function of `variable`
+	pub fn apply(self, expr: &Expr, variable: &str) -> Expr {
+		match self {
+			Self::Simplify => expr.simplified(),
+			Self::Expand => expr.expanded(),
+			Self::Factor => expr.factored(variable),
+		}
+	}
+}
+
+/// A coefficient multiplied by bases raised to powers, such as `3·x^2·sin(x)`
+#[derive(Clone, PartialEq, Debug)]
+struct Term {
+	coefficient: f64,
+
+	/// Bases along with the powers they're raised to. No base appears twice, and no power is zero.
+	factors: Vec<(Expr, f64)>,
+}
+
+impl Term {
+	const fn constant(coefficient: f64) -> Self {
+		Self {
+			coefficient,
+			factors: Vec::new(),
+		}
+	}
+
+	fn factor(base: Expr, power: f64) -> Self {
+		Self {
+			coefficient: 1.0,
+			factors: vec![(base, power)],
+		}
+	}
+
+	/// Multiplies the term by `base^power`
+	fn multiply_factor(&mut self, base: Expr, power: f64) {
+		match self.factors.iter().position(|(existing, _)| *existing == base) {
+			Some(i) => {
+				let power = round_near_integer(self.factors[i].1 + power);
+				match power == 0.0 {
+					true => {
+						self.factors.remove(i);
+					}
+					false => self.factors[i].1 = power,
+				}
+			}
+			None if power != 0.0 => {
+				self.factors.push((base, power));
+				self.factors.sort_by(factor_order);
+			}
+			None => {}
+		}
+	}
+
+	fn times(&self, other: &Term) -> Term {
+		let mut product = Term {
+			coefficient: self.coefficient * other.coefficient,
+			factors: self.factors.clone(),
+		};
+		for (base, power) in &other.factors {
+			product.multiply_factor(base.clone(), *power);
+		}
+		product
+	}
+
+	/// Sum of the powers of variables, which orders terms (`x^2` before `x·sin(x)` before numbers)
+	fn degree(&self) -> f64 {
+		self.factors
+			.iter()
+			.filter(|(base, _)| matches!(base, Expr::Variable(_)))
+			.map(|(_, power)| power)
+			.sum()
+	}
+
+	/// Power `variable` is raised to, if the term is a plain multiple of a power of it (or a number)
+	fn polynomial_degree(&self, variable: &str) -> Option<usize> {
+		match self.factors.as_slice() {
+			[] => Some(0),
+			[(Expr::Variable(name), power)] if (name == variable) && is_natural(*power) => {
+				Some(*power as usize)
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Sum of terms, none of which have the same factors
+type Sum = Vec<Term>;
+
+/// Returns true if `x` is an integer (allowing for rounding error)
+fn is_integer(x: f64) -> bool { x.is_finite() && ((x - x.round()).abs() < 1e-9) }
+
+/// Returns true if `x` is a non-negative integer (allowing for rounding error)
+fn is_natural(x: f64) -> bool { is_integer(x) && (x > -0.5) }
+
+/// Rounds `x` if it's an integer up to rounding error, such as the `0.9999999999999999` from adding
+/// `1/3` three times
+fn round_near_integer(x: f64) -> f64 {
+	match is_integer(x) {
+		true => x.round(),
+		false => x,
+	}
+}
+
+/// `x` as a fraction `p/q` with a small positive denominator, if it is one
+pub(crate) fn as_fraction(x: f64) -> Option<(i64, i64)> {
+	if !x.is_finite() || (x.abs() > 1e15) {
+		return None;
+	}
+
+	(1..=MAX_DENOMINATOR).find_map(|q| {
+		let scaled = x * q as f64;
+		let p = scaled.round();
+		((scaled - p).abs() <= 1e-12 * scaled.abs().max(1.0)).then_some((p as i64, q))
+	})
+}
+
+pub(crate) const fn gcd(a: i128, b: i128) -> i128 {
+	let (mut a, mut b) = (a.abs(), b.abs());
+	while b != 0 {
+		(a, b) = (b, a % b);
+	}
+	a
+}
+
+/// Positive divisors of `n`, which is no larger than [`MAX_ROOT_SEARCH`]
+fn divisors(n: i128) -> Vec<i128> {
+	let n = n.abs();
+	let mut divisors: Vec<i128> = (1..)
+		.take_while(|d| d * d <= n)
+		.filter(|d| n % d == 0)
+		.flat_map(|d| [d, n / d])
+		.collect();
+	divisors.sort_unstable();
+	divisors.dedup();
+	divisors
+}
+
+/// Order of factors within a term: constants, then variables, then everything else
+fn factor_order(a: &(Expr, f64), b: &(Expr, f64)) -> Ordering {
+	let rank = |expr: &Expr| match expr {
+		Expr::Number(_) => 0,
+		Expr::Constant(_) => 1,
+		Expr::Variable(_) => 2,
+		_ => 3,
+	};
+	(rank(&a.0).cmp(&rank(&b.0))).then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+}
+
+/// Order of terms within a sum: highest degree first, with numbers last
+fn term_order(a: &Term, b: &Term) -> Ordering {
+	let key = |term: &Term| {
+		let factors: Vec<String> = (term.factors.iter())
+			.map(|(base, power)| format!("{}^{}", base, power))
+			.collect();
+		factors.join("·")
+	};
+	(b.degree().partial_cmp(&a.degree()).unwrap_or(Ordering::Equal))
+		.then(a.factors.is_empty().cmp(&b.factors.is_empty()))
+		.then_with(|| key(a).cmp(&key(b)))
+}
+
+/// Adds `term` to `sum`, combining it with a like term if there is one
+fn add_term(sum: &mut Sum, term: Term) {
+	match sum.iter().position(|existing| existing.factors == term.factors) {
+		Some(i) => {
+			sum[i].coefficient += term.coefficient;
+			if sum[i].coefficient.abs() < EPSILON {
+				sum.remove(i);
+			}
+		}
+		None if term.coefficient.abs() >= EPSILON => sum.push(term),
+		None => {}
+	}
+}
+
+fn add(mut a: Sum, b: Sum) -> Sum {
+	for term in b {
+		add_term(&mut a, term);
+	}
+	a
+}
+
+fn negate(sum: Sum) -> Sum {
+	(sum.into_iter())
+		.map(|term| Term {
+			coefficient: -term.coefficient,
+			..term
+		})
+		.collect()
+}
+
+/// Product of `a` and `b`, multiplying each term of one by each term of the other
+fn multiply(a: &[Term], b: &[Term]) -> Sum {
+	let mut product = Vec::new();
+	for a in a {
+		for b in b {
+			add_term(&mut product, a.times(b));
+		}
+	}
+	product
+}
+
+/// `base` raised to the integer power `power`, multiplied out
+fn multiply_out(base: &[Term], power: usize) -> Sum {
+	(0..power).fold(vec![Term::constant(1.0)], |product, _| multiply(&product, base))
+}
+
+/// `base^power`, leaving out powers of one
+fn power_expr(base: Expr, power: f64) -> Expr {
+	match power == 1.0 {
+		true => base,
+		false => Expr::binary(BinaryOp::Pow, base, Expr::Number(power)),
+	}
+}
+
+/// Product of `factors`, or `None` if there are none
+fn product_expr(factors: Vec<Expr>) -> Option<Expr> {
+	factors
+		.into_iter()
+		.reduce(|product, factor| Expr::binary(BinaryOp::Mul, product, factor))
+}
+
+/// `term` without its sign, such as `2·x/3` for `-2/3·x`. Factors raised to negative powers are
+/// written as a denominator.
+fn unsigned_term_expr(term: &Term) -> Expr {
+	let magnitude = term.coefficient.abs();
+	let (numerator, denominator) = match as_fraction(magnitude) {
+		Some((p, q)) => (p as f64, q as f64),
+		None => (magnitude, 1.0),
+	};
+
+	let mut numerator_factors = Vec::new();
+	let mut denominator_factors = Vec::new();
+	for (base, power) in &term.factors {
+		match *power > 0.0 {
+			true => numerator_factors.push(power_expr(base.clone(), *power)),
+			false => denominator_factors.push(power_expr(base.clone(), -power)),
+		}
+	}
+	if (numerator != 1.0) | numerator_factors.is_empty() {
+		numerator_factors.insert(0, Expr::Number(numerator));
+	}
+	if denominator != 1.0 {
+		denominator_factors.insert(0, Expr::Number(denominator));
+	}
+
+	let numerator = product_expr(numerator_factors).unwrap_or(Expr::Number(1.0));
+	match product_expr(denominator_factors) {
+		Some(denominator) => Expr::binary(BinaryOp::Div, numerator, denominator),
+		None => numerator,
+	}
+}
+
+/// Writes `sum` as an expression, ordering its terms (see [`term_order`])
+fn sum_expr(mut sum: Sum) -> Expr {
+	sum.sort_by(term_order);
+	let mut terms = sum.iter();
+	let Some(first) = terms.next() else {
+		return Expr::Number(0.0);
+	};
+
+	let first = match first.coefficient < 0.0 {
+		true => Expr::Negate(Box::new(unsigned_term_expr(first))),
+		false => unsigned_term_expr(first),
+	};
+	terms.fold(first, |expr, term| {
+		let op = match term.coefficient < 0.0 {
+			true => BinaryOp::Sub,
+			false => BinaryOp::Add,
+		};
+		Expr::binary(op, expr, unsigned_term_expr(term))
+	})
+}
+
+/// Evaluates the function `name` at `x`, if it's one that can be folded into a number
+fn evaluate(name: &str, x: f64) -> Option<f64> {
+	let f: fn(f64) -> f64 = match name {
+		"sin" => f64::sin,
+		"cos" => f64::cos,
+		"tan" => f64::tan,
+		"exp" => f64::exp,
+		"ln" => f64::ln,
+		"log2" => f64::log2,
+		"log10" => f64::log10,
+		"sqrt" => f64::sqrt,
+		"cbrt" => f64::cbrt,
+		"abs" => f64::abs,
+		"floor" => f64::floor,
+		"ceil" => f64::ceil,
+		_ => return None,
+	};
+	Some(f(x))
+}
+
+/// Rewrites expressions into sums of terms
+struct Rewriter {
+	/// Whether products and powers of sums are multiplied out
+	expand: bool,
+}
+
+impl Rewriter {
+	/// `expr` rewritten as a sum of terms
+	fn sum(&self, expr: &Expr) -> Sum {
+		match expr {
+			Expr::Number(n) => add(Vec::new(), vec![Term::constant(*n)]),
+			Expr::Constant(_) | Expr::Variable(_) => vec![Term::factor(expr.clone(), 1.0)],
+			Expr::Negate(inner) => negate(self.sum(inner)),
+			Expr::Binary { op, lhs, rhs } => match op {
+				BinaryOp::Add => add(self.sum(lhs), self.sum(rhs)),
+				BinaryOp::Sub => add(self.sum(lhs), negate(self.sum(rhs))),
+				BinaryOp::Mul => self.product(self.sum(lhs), self.sum(rhs)),
+				BinaryOp::Div => {
+					let denominator = self.power(self.sum(rhs), -1.0);
+					self.product(self.sum(lhs), denominator)
+				}
+				BinaryOp::Pow => {
+					let exponent = self.sum(rhs);
+					match as_number(&exponent) {
+						Some(power) => self.power(self.sum(lhs), power),
+						None => {
+							let base = self.expr(lhs);
+							let power = Expr::binary(BinaryOp::Pow, base, self.build(exponent));
+							vec![Term::factor(power, 1.0)]
+						}
+					}
+				}
+			},
+			Expr::Call { name, args } => {
+				let args: Vec<Expr> = args.iter().map(|arg| self.expr(arg)).collect();
+				self.call(name, args)
+			}
+		}
+	}
+
+	/// `expr` rewritten
+	fn expr(&self, expr: &Expr) -> Expr { self.build(self.sum(expr)) }
+
+	/// Writes `sum` as an expression, applying identities first when simplifying
+	fn build(&self, sum: Sum) -> Expr {
+		match self.expand {
+			true => sum_expr(sum),
+			false => sum_expr(self.identities(sum)),
+		}
+	}
+
+	/// `sum` as a single term, treating it as one factor if it has several terms (after pulling
+	/// out the number they're all multiples of, such as the `2` of `2·x + 2`)
+	fn as_term(&self, sum: Sum) -> Term {
+		match sum.len() {
+			0 => Term::constant(0.0),
+			1 => sum.into_iter().next().unwrap(),
+			_ => {
+				let content = content(&sum);
+				let sum = multiply(&sum, &[Term::constant(1.0 / content)]);
+				Term {
+					coefficient: content,
+					factors: vec![(self.build(sum), 1.0)],
+				}
+			}
+		}
+	}
+
+	fn product(&self, a: Sum, b: Sum) -> Sum {
+		let is_number = |sum: &Sum| (sum.len() == 1) && sum[0].factors.is_empty();
+		// Numbers are always multiplied into sums, as `2·x + 2` is simpler than `2·(x + 1)`
+		let distribute = self.expand | ((a.len() <= 1) && (b.len() <= 1));
+		match distribute | is_number(&a) | is_number(&b) {
+			true => multiply(&a, &b),
+			false => add(Vec::new(), vec![self.as_term(a).times(&self.as_term(b))]),
+		}
+	}
+
+	/// `base` raised to the number `power`
+	fn power(&self, base: Sum, power: f64) -> Sum {
+		if power == 0.0 {
+			return vec![Term::constant(1.0)];
+		} else if power == 1.0 {
+			return base;
+		}
+
+		match base.as_slice() {
+			// Powers of single terms are distributed over their factors, as long as that gives the
+			// same value for negative bases: `(x^2)^0.5` is `abs(x)`, not `x`
+			[term] if is_integer(power) => vec![Term {
+				coefficient: term.coefficient.powf(power),
+				factors: (term.factors.iter())
+					.map(|(base, p)| (base.clone(), round_near_integer(p * power)))
+					.collect(),
+			}],
+			[Term {
+				coefficient,
+				factors,
+			}] if (*coefficient == 1.0) && (factors.len() == 1) && (factors[0].1 == 1.0) => {
+				vec![Term::factor(factors[0].0.clone(), power)]
+			}
+			[_, _, ..] if self.expand && is_natural(power) && (power <= MAX_EXPANDED_POWER) => {
+				multiply_out(&base, power as usize)
+			}
+			[] if power > 0.0 => Vec::new(),
+			[] => vec![Term::factor(Expr::Number(0.0), power)],
+			_ => vec![Term::factor(self.build(base), power)],
+		}
+	}
+
+	/// Call of `name` with `args` (which have already been rewritten), applying symmetries and
+	/// inverses such as `cos(-x) = cos(x)` and `ln(exp(x)) = x`
+	fn call(&self, name: &str, args: Vec<Expr>) -> Sum {
+		let call = |name: &str, args: Vec<Expr>| Expr::Call {
+			name: name.to_owned(),
+			args,
+		};
+		match (name, args.as_slice()) {
+			(name, [Expr::Negate(inner)]) if ODD_FUNCTIONS.contains(&name) => {
+				negate(self.call(name, vec![*inner.clone()]))
+			}
+			(name, [Expr::Negate(inner)]) if EVEN_FUNCTIONS.contains(&name) => {
+				self.call(name, vec![*inner.clone()])
+			}
+			("ln", [Expr::Call { name, args }]) if (name == "exp") && (args.len() == 1) => {
+				self.sum(&args[0])
+			}
+			(
+				"ln",
+				[Expr::Binary {
+					op: BinaryOp::Pow,
+					lhs,
+					rhs,
+				}],
+			) if **lhs == Expr::Constant(Constant::E) => self.sum(rhs),
+			// Only numbers that come out as integers are folded, so `sqrt(2)` stays exact
+			(name, [Expr::Number(x)]) => match evaluate(name, *x) {
+				Some(value) if is_integer(value) => self.sum(&Expr::Number(value.round())),
+				_ => vec![Term::factor(call(name, args), 1.0)],
+			},
+			_ => vec![Term::factor(call(name, args), 1.0)],
+		}
+	}
+
+	/// Applies identities to `sum` until none apply
+	fn identities(&self, mut sum: Sum) -> Sum {
+		// Each identity makes the sum smaller, so this is only a safeguard
+		for _ in 0..16 {
+			match self.identity(&sum) {
+				Some(rewritten) => sum = rewritten,
+				None => break,
+			}
+		}
+		sum
+	}
+
+	/// `sum` with one identity applied, if any apply
+	fn identity(&self, sum: &Sum) -> Option<Sum> {
+		for (i, term) in sum.iter().enumerate() {
+			if let Some(rewritten) = self.term_identity(term) {
+				let mut result: Sum = sum.iter().take(i).cloned().collect();
+				result = add(result, rewritten);
+				return Some(add(result, sum[(i + 1)..].to_vec()));
+			}
+
+			// sin(u)^2 + cos(u)^2 = 1
+			for (base, power) in &term.factors {
+				let Some(arg) = single_arg(base, "sin") else {
+					continue;
+				};
+				if *power != 2.0 {
+					continue;
+				}
+
+				let mut rest = term.clone();
+				rest.factors.retain(|(other, _)| other != base);
+				let mut partner = rest.clone();
+				partner.multiply_factor(call_of("cos", arg.clone()), 2.0);
+				let j = sum.iter().position(|other| {
+					(other.factors == partner.factors)
+						&& ((other.coefficient - term.coefficient).abs() < EPSILON)
+				});
+				if let Some(j) = j {
+					let others = (sum.iter().enumerate())
+						.filter(|(k, _)| (*k != i) && (*k != j))
+						.map(|(_, term)| term.clone());
+					return Some(add(others.collect(), vec![rest]));
+				}
+			}
+		}
+		None
+	}
+
+	/// `term` with an identity applied to its factors, if any apply
+	fn term_identity(&self, term: &Term) -> Option<Sum> {
+		let without = |bases: &[&Expr]| Term {
+			coefficient: term.coefficient,
+			factors: (term.factors.iter())
+				.filter(|(base, _)| !bases.contains(&base))
+				.cloned()
+				.collect(),
+		};
+
+		for (base, power) in &term.factors {
+			// sqrt(u)^2 = u
+			if let Some(arg) = single_arg(base, "sqrt") {
+				if is_integer(power / 2.0) && (*power > 0.0) {
+					let rest = vec![without(&[base])];
+					return Some(self.product(rest, self.power(self.sum(arg), power / 2.0)));
+				}
+			}
+
+			let Some(arg) = single_arg(base, "sin") else {
+				continue;
+			};
+			let cos = call_of("cos", arg.clone());
+			let Some(cos_power) = (term.factors.iter())
+				.find(|(other, _)| *other == cos)
+				.map(|(_, power)| *power)
+			else {
+				continue;
+			};
+
+			// sin(u)/cos(u) = tan(u)
+			if cos_power == -power {
+				let mut rewritten = without(&[base, &cos]);
+				rewritten.multiply_factor(call_of("tan", arg.clone()), *power);
+				return Some(vec![rewritten]);
+			}
+
+			// 2·sin(u)·cos(u) = sin(2·u), when that doesn't leave a fraction behind
+			if (*power == 1.0) && (cos_power == 1.0) && is_integer(term.coefficient / 2.0) {
+				let mut rewritten = without(&[base, &cos]);
+				rewritten.coefficient /= 2.0;
+				let double = Expr::binary(BinaryOp::Mul, Expr::Number(2.0), arg.clone());
+				let double = self.expr(&double);
+				rewritten.multiply_factor(call_of("sin", double), 1.0);
+				return Some(vec![rewritten]);
+			}
+		}
+		None
+	}
+}
+
+/// Value of `sum` if it's a number
+fn as_number(sum: &Sum) -> Option<f64> {
+	match sum.as_slice() {
+		[] => Some(0.0),
+		[term] if term.factors.is_empty() => Some(term.coefficient),
+		_ => None,
+	}
+}
+
+/// Call of the function `name` with the single argument `arg`
+fn call_of(name: &str, arg: Expr) -> Expr {
+	Expr::Call {
+		name: name.to_owned(),
+		args: vec![arg],
+	}
+}
+
+/// Argument of `expr` if it's a call of the function `name` with one argument
+fn single_arg<'a>(expr: &'a Expr, name: &str) -> Option<&'a Expr> {
+	match expr {
+		Expr::Call { name: called, args } if called == name => match args.as_slice() {
+			[arg] => Some(arg),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Largest number every coefficient of `sum` is a multiple of, such as `2` for `4·x + 6` or `1/6`
+/// for `x/2 + x^2/3`, with the sign of the leading term
+fn content(sum: &Sum) -> f64 {
+	let fractions: Option<Vec<(i64, i64)>> =
+		sum.iter().map(|term| as_fraction(term.coefficient)).collect();
+	let magnitude = match fractions {
+		Some(fractions) => {
+			let numerators = fractions.iter().fold(0, |g, (p, _)| gcd(g, *p as i128));
+			let denominators = (fractions.iter())
+				.fold(1, |l, (_, q)| l * (*q as i128) / gcd(l, *q as i128));
+			numerators as f64 / denominators as f64
+		}
+		None => 1.0,
+	};
+
+	let leading = sum.iter().min_by(|a, b| term_order(a, b));
+	match leading.is_some_and(|term| term.coefficient < 0.0) {
+		true => -magnitude,
+		false => magnitude,
+	}
+}
+
+/// Factors pulled out of a polynomial by its rational roots
+pub(crate) struct Roots {
+	/// Linear factors `q·x - p` for each root `p/q`, along with how many times they divide the
+	/// polynomial
+	pub(crate) factors: Vec<((i128, i128), usize)>,
+
+	/// What's left of the polynomial, as coefficients from the constant term up
+	pub(crate) quotient: Vec<i128>,
+}
+
+/// `q^n·P(p/q)` for the polynomial `P` of degree `n` with `coefficients` (which is zero exactly
+/// when `p/q` is a root), or `None` if it overflows
+fn scaled_value(coefficients: &[i128], p: i128, q: i128) -> Option<i128> {
+	let degree = coefficients.len() as u32 - 1;
+	coefficients.iter().enumerate().try_fold(0i128, |total, (i, a)| {
+		let term = a
+			.checked_mul(p.checked_pow(i as u32)?)?
+			.checked_mul(q.checked_pow(degree - i as u32)?)?;
+		total.checked_add(term)
+	})
+}
+
+/// Divides the polynomial with `coefficients` by `q·x - p`, where `p/q` is one of its roots
+fn divide_root(coefficients: &[i128], p: i128, q: i128) -> Vec<i128> {
+	let degree = coefficients.len() - 1;
+	let mut quotient = vec![0; degree];
+	quotient[degree - 1] = coefficients[degree] / q;
+	for i in (1..degree).rev() {
+		quotient[i - 1] = (coefficients[i] + p * quotient[i]) / q;
+	}
+	quotient
+}
+
+/// Finds the rational roots of the polynomial with integer `coefficients` (from the constant term
+/// up) by the rational root theorem: each is `p/q` with `p` dividing the constant term and `q`
+/// dividing the leading coefficient
+pub(crate) fn rational_roots(mut coefficients: Vec<i128>) -> Roots {
+	let mut factors = Vec::new();
+	let constant = coefficients[0];
+	let leading = coefficients[coefficients.len() - 1];
+	if (constant == 0) | (constant.abs() > MAX_ROOT_SEARCH) | (leading.abs() > MAX_ROOT_SEARCH) {
+		return Roots {
+			factors,
+			quotient: coefficients,
+		};
+	}
+
+	let mut candidates: Vec<(i128, i128)> = divisors(constant)
+		.into_iter()
+		.flat_map(|p| divisors(leading).into_iter().map(move |q| (p, q)))
+		.filter(|(p, q)| gcd(*p, *q) == 1)
+		.flat_map(|(p, q)| [(-p, q), (p, q)])
+		.collect();
+	candidates.sort_by(|(p1, q1), (p2, q2)| (p1 * q2).cmp(&(p2 * q1)));
+
+	for (p, q) in candidates {
+		let mut multiplicity = 0;
+		while (coefficients.len() > 1) && (scaled_value(&coefficients, p, q) == Some(0)) {
+			coefficients = divide_root(&coefficients, p, q);
+			multiplicity += 1;
+		}
+		if multiplicity > 0 {
+			factors.push(((p, q), multiplicity));
+		}
+	}
+
+	Roots {
+		factors,
+		quotient: coefficients,
+	}
+}
+
+/// Polynomial in `variable` with `coefficients` (from the constant term up)
+fn polynomial(coefficients: &[i128], variable: &str) -> Sum {
+	(coefficients.iter().enumerate())
+		.filter(|(_, a)| **a != 0)
+		.map(|(i, a)| match i {
+			0 => Term::constant(*a as f64),
+			i => Term {
+				coefficient: *a as f64,
+				factors: vec![(Expr::Variable(variable.to_owned()), i as f64)],
+			},
+		})
+		.collect()
+}
+
+/// Coefficients of `expr` as a polynomial in `variable` (from the constant term up), if it is one
+/// once multiplied out. Constants such as `π` are multiplied into the coefficients.
+pub(crate) fn polynomial_coefficients(expr: &Expr, variable: &str) -> Option<Vec<f64>> {
+	let mut coefficients = Vec::new();
+	for term in (Rewriter { expand: true }).sum(expr) {
+		let mut coefficient = term.coefficient;
+		let mut degree = 0;
+		for (base, power) in &term.factors {
+			match base {
+				Expr::Constant(Constant::Pi) => coefficient *= std::f64::consts::PI.powf(*power),
+				Expr::Constant(Constant::E) => coefficient *= std::f64::consts::E.powf(*power),
+				Expr::Variable(name) if (name == variable) && is_natural(*power) => {
+					degree = *power as usize
+				}
+				_ => return None,
+			}
+		}
+
+		if degree >= coefficients.len() {
+			coefficients.resize(degree + 1, 0.0);
+		}
+		coefficients[degree] += coefficient;
+	}
+	Some(coefficients)
+}
+
+impl Expr {
+	/// Simplifies the expression: combines like terms (`2·x + 3·x` to `5·x`) and factors
+	/// (`x·x` to `x^2`), folds numbers, and applies common identities such as
+	/// `sin(x)^2 + cos(x)^2 = 1`, `sin(x)/cos(x) = tan(x)`, and `cos(-x) = cos(x)`
+	pub fn simplified(&self) -> Expr { Rewriter { expand: false }.expr(self) }
+
+	/// Multiplies out products and whole powers of sums, such as `(x + 1)^2` to `x^2 + 2·x + 1`
+	pub fn expanded(&self) -> Expr { Rewriter { expand: true }.expr(self) }
+
+	/// Factors the expression: pulls out numbers and factors common to every term, then factors
+	/// what's left by its rational roots if it's a polynomial in `variable`, such as
+	/// `2·x^3 - 2·x` to `2·x·(x + 1)·(x - 1)`
+	pub fn factored(&self, variable: &str) -> Expr {
+		let rewriter = Rewriter { expand: true };
+		let sum = rewriter.sum(self);
+		if sum.len() < 2 {
+			return sum_expr(sum);
+		}
+
+		// Factors every term has are pulled out with the lowest power they have, such as `x^2` from
+		// `x^3 + x^2` or `1/x^2` from `1/x + 2/x^2`
+		let content = content(&sum);
+		let mut common = Term::constant(content);
+		for (base, power) in &sum[0].factors {
+			let powers: Option<Vec<f64>> = (sum.iter())
+				.map(|term| {
+					(term.factors.iter())
+						.find(|(other, p)| (other == base) && (p.signum() == power.signum()))
+						.map(|(_, power)| *power)
+				})
+				.collect();
+			if let Some(lowest) = powers.map(|powers| powers.into_iter().fold(*power, f64::min)) {
+				common.factors.push((base.clone(), lowest));
+			}
+		}
+
+		let inverse = Term {
+			coefficient: 1.0 / content,
+			factors: (common.factors.iter())
+				.map(|(base, power)| (base.clone(), -power))
+				.collect(),
+		};
+		let mut rest: Sum = multiply(&sum, &[inverse]);
+
+		let degrees: Option<Vec<usize>> =
+			rest.iter().map(|term| term.polynomial_degree(variable)).collect();
+		let integers = (rest.iter())
+			.all(|term| is_integer(term.coefficient) && (term.coefficient.abs() < 1e15));
+		if let (Some(degrees), true) = (degrees, integers) {
+			let mut coefficients = vec![0; degrees.iter().max().map_or(0, |degree| degree + 1)];
+			for (term, degree) in rest.iter().zip(degrees) {
+				coefficients[degree] += term.coefficient.round() as i128;
+			}
+
+			if coefficients.len() > 2 {
+				let roots = rational_roots(coefficients);
+				for ((p, q), multiplicity) in roots.factors {
+					let linear = polynomial(&[-p, q], variable);
+					common.multiply_factor(sum_expr(linear), multiplicity as f64);
+				}
+				rest = polynomial(&roots.quotient, variable);
+			}
+		}
+
+		match rest.as_slice() {
+			[term] if term.factors.is_empty() => common.coefficient *= term.coefficient,
+			_ => common.multiply_factor(sum_expr(rest), 1.0),
+		}
+		sum_expr(vec![common])
+	}
+}
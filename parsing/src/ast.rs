@@ -0,0 +1,294 @@
+use crate::error::{ParseError, ParseErrorKind};
+use crate::functions::function_usage;
+use crate::lexer::{implicit_multiplication, tokenize, Token, TokenKind};
+use crate::SUPPORTED_FUNCTIONS;
+use std::ops::Range;
+
+/// Constants expressions can use by name
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Constant {
+	/// `pi` or `π`
+	Pi,
+
+	/// Euler's number, `e` or `E`
+	E,
+}
+
+/// Operators that combine two expressions
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOp {
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Pow,
+}
+
+impl BinaryOp {
+	/// Binding powers of the operator's left and right sides, where operators with higher binding
+	/// powers are applied first. Every operator binds tighter on its right, making them all left
+	/// associative like in exmex, which reads `2^3^x` as `(2^3)^x`.
+	const fn binding_power(self) -> (u8, u8) {
+		match self {
+			Self::Add | Self::Sub => (1, 2),
+			Self::Mul | Self::Div => (3, 4),
+			Self::Pow => (7, 8),
+		}
+	}
+}
+
+/// Expression parsed from a string by [`parse_expression`]
+#[derive(Clone, PartialEq, Debug)]
+pub enum Expr {
+	Number(f64),
+	Constant(Constant),
+
+	/// A variable or other name, such as `x` or a user defined constant
+	Variable(String),
+
+	/// Unary minus, such as `-x`
+	Negate(Box<Expr>),
+
+	Binary {
+		op: BinaryOp,
+		lhs: Box<Expr>,
+		rhs: Box<Expr>,
+	},
+
+	/// A function applied to its arguments, such as `sin(x)` or `atan2(y, x)`
+	Call { name: String, args: Vec<Expr> },
+}
+
+impl Expr {
+	pub(crate) fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Self {
+		Self::Binary {
+			op,
+			lhs: Box::new(lhs),
+			rhs: Box::new(rhs),
+		}
+	}
+
+	/// Returns true if the expression is a sum or difference, which needs parentheses when it's
+	/// multiplied or negated
+	pub const fn is_sum(&self) -> bool {
+		matches!(
+			self,
+			Self::Binary {
+				op: BinaryOp::Add | BinaryOp::Sub,
+				..
+			}
+		)
+	}
+
+	/// Returns true if the expression is a single number, name, or call, which never needs
+	/// parentheses
+	pub const fn is_atom(&self) -> bool {
+		matches!(
+			self,
+			Self::Number(_) | Self::Constant(_) | Self::Variable(_) | Self::Call { .. }
+		)
+	}
+
+	/// Returns true if the expression is written starting with a number (such as `2x` or `3^x`), so
+	/// multiplying something by it needs a multiplication sign
+	pub fn starts_with_number(&self) -> bool {
+		match self {
+			Self::Number(_) => true,
+			// Fractions are written stacked
+			Self::Binary {
+				op: BinaryOp::Div, ..
+			} => false,
+			Self::Binary { lhs, .. } => lhs.starts_with_number(),
+			_ => false,
+		}
+	}
+
+	/// Wraps the expression in `deriv` calls, making it the `order`th derivative with respect to
+	/// `variable`
+	pub fn differentiated(self, variable: &str, order: usize) -> Self {
+		(0..order).fold(self, |expr, _| Self::Call {
+			name: "deriv".to_owned(),
+			args: vec![expr, Self::Variable(variable.to_owned())],
+		})
+	}
+}
+
+/// Pratt parser over the tokens of `input`
+struct Parser<'a> {
+	input: &'a str,
+	tokens: Vec<Token>,
+
+	/// Index of the next token to be parsed
+	position: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<&Token> { self.tokens.get(self.position) }
+
+	fn peek_kind(&self) -> Option<TokenKind> { self.peek().map(|token| token.kind) }
+
+	/// Token that was parsed last
+	fn previous(&self) -> Option<&Token> {
+		self.position.checked_sub(1).and_then(|i| self.tokens.get(i))
+	}
+
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	/// Empty span at the end of the input
+	const fn end(&self) -> Range<usize> { self.input.len()..self.input.len() }
+
+	/// Error for `token` appearing where it can't
+	fn unexpected(&self, token: &Token) -> ParseError {
+		match token.kind {
+			// Such as the parenthesis in `x + 1)`
+			TokenKind::CloseParen => ParseError::new(ParseErrorKind::UnbalancedParens)
+				.at(token.span.clone())
+				.with_fix(token.span.clone(), ""),
+			_ => ParseError::new(ParseErrorKind::UnexpectedToken(
+				token.text(self.input).to_owned(),
+			))
+			.at(token.span.clone()),
+		}
+	}
+
+	/// Parses an expression whose operators all have a left binding power of at least `min_power`
+	fn expression(&mut self, min_power: u8) -> Result<Expr, ParseError> {
+		let mut lhs = self.prefix()?;
+
+		while let Some(token) = self.peek() {
+			// Whether the operator is written out rather than implied, such as in `2*x` versus `2x`
+			let (op, explicit) = match token.kind {
+				TokenKind::Plus => (BinaryOp::Add, true),
+				TokenKind::Minus => (BinaryOp::Sub, true),
+				TokenKind::Star => (BinaryOp::Mul, true),
+				TokenKind::Slash => (BinaryOp::Div, true),
+				TokenKind::Caret => (BinaryOp::Pow, true),
+				_ if self
+					.previous()
+					.is_some_and(|previous| implicit_multiplication(previous, token)) =>
+				{
+					(BinaryOp::Mul, false)
+				}
+				_ => break,
+			};
+
+			let (left_power, right_power) = op.binding_power();
+			if left_power < min_power {
+				break;
+			}
+
+			if explicit {
+				self.position += 1;
+			}
+			let rhs = self.expression(right_power)?;
+			lhs = Expr::binary(op, lhs, rhs);
+		}
+
+		Ok(lhs)
+	}
+
+	/// Parses a number, name, call, parenthesized group, or negation
+	fn prefix(&mut self) -> Result<Expr, ParseError> {
+		let Some(token) = self.next() else {
+			return Err(ParseError::new(ParseErrorKind::ExpectedExpression).at(self.end()));
+		};
+		let text = token.text(self.input);
+
+		match token.kind {
+			TokenKind::Number => text.parse().map(Expr::Number).map_err(|_| {
+				ParseError::new(ParseErrorKind::InvalidNumber(text.to_owned())).at(token.span)
+			}),
+			TokenKind::Pi => Ok(Expr::Constant(Constant::Pi)),
+			TokenKind::Variable if text.eq_ignore_ascii_case("e") => Ok(Expr::Constant(Constant::E)),
+			TokenKind::Variable => Ok(Expr::Variable(text.to_owned())),
+			TokenKind::Identifier if self.peek_kind() == Some(TokenKind::OpenParen) => {
+				self.position += 1;
+				self.call(text, token.span)
+			}
+			TokenKind::Identifier => match function_usage(text) {
+				Some(usage) => {
+					let error = ParseError::new(ParseErrorKind::NotCalled {
+						function: text.to_owned(),
+						usage: usage.to_owned(),
+					})
+					.at(token.span.clone());
+					Err(match usage == format!("{}(x)", text) {
+						true => error.with_fix(token.span.end..token.span.end, "(x)"),
+						false => error,
+					})
+				}
+				None => Ok(Expr::Variable(text.to_owned())),
+			},
+			TokenKind::OpenParen => {
+				let inner = self.expression(0)?;
+				match self.next() {
+					Some(close) if close.kind == TokenKind::CloseParen => Ok(inner),
+					Some(other) => Err(self.unexpected(&other)),
+					None => Err(ParseError::new(ParseErrorKind::UnbalancedParens)
+						.at(token.span)
+						.with_fix(self.end(), ")")),
+				}
+			}
+			// Like in exmex, unary minus only applies to what directly follows it, so `-x^2` is
+			// `(-x)^2`
+			TokenKind::Minus => Ok(Expr::Negate(Box::new(self.prefix()?))),
+			TokenKind::Plus => self.prefix(),
+			_ => Err(self.unexpected(&token)),
+		}
+	}
+
+	/// Parses the arguments of a call to `name` (at `span`) after its opening parenthesis
+	fn call(&mut self, name: &str, span: Range<usize>) -> Result<Expr, ParseError> {
+		let mut args = Vec::new();
+		loop {
+			args.push(self.expression(0)?);
+			match self.next() {
+				Some(token) if token.kind == TokenKind::Comma => {}
+				Some(token) if token.kind == TokenKind::CloseParen => break,
+				Some(token) => return Err(self.unexpected(&token)),
+				None => {
+					return Err(ParseError::new(ParseErrorKind::UnclosedCall(name.to_owned()))
+						.at(span)
+						.with_fix(self.end(), ")"))
+				}
+			}
+		}
+
+		Ok(Expr::Call {
+			name: name.to_owned(),
+			args,
+		})
+	}
+}
+
+/// Parses `input` into an [`Expr`]. Understands implicit multiplication (such as `2x` or
+/// `(x+1)(x-1)`), `**` for powers, `pi` and `π`, and unicode operators such as `×` and `÷`.
+pub fn parse_expression(input: &str) -> Result<Expr, ParseError> {
+	let mut parser = Parser {
+		input,
+		tokens: tokenize(input),
+		position: 0,
+	};
+
+	let expr = parser.expression(0)?;
+	match parser.next() {
+		Some(token) => Err(parser.unexpected(&token)),
+		None => Ok(expr),
+	}
+}
+
+/// Returns the name and span of the first call in `input` to a function that isn't supported
+pub(crate) fn unknown_call(input: &str) -> Option<(&str, Range<usize>)> {
+	let tokens = tokenize(input);
+	tokens.windows(2).find_map(|pair| {
+		let name = pair[0].text(input);
+		((pair[0].kind == TokenKind::Identifier)
+			&& (pair[1].kind == TokenKind::OpenParen)
+			&& !SUPPORTED_FUNCTIONS.contains(&name))
+		.then(|| (name, pair[0].span.clone()))
+	})
+}
@@ -1,6 +1,4 @@
-use std::hint::unreachable_unchecked;
-
-use crate::{generate_hint, Hint, HINT_EMPTY};
+use crate::{function_docs, generate_hint, get_last_term, Definition, Hint, HINT_EMPTY};
 
 #[derive(PartialEq, Debug)]
 pub enum Movement {
@@ -22,87 +20,320 @@ impl Default for Movement {
 	fn default() -> Self { Self::None }
 }
 
+/// A name the last term of the input can be completed to
+#[derive(Clone, PartialEq, Debug)]
+pub struct Completion {
+	/// Name being completed, such as `sin`
+	pub name: String,
+
+	/// Text appended to the input to complete it, such as `n(` when completing `si` to `sin(`
+	pub hint: String,
+
+	/// How the name is used, such as `atan2(y, x)` or `k`
+	pub signature: String,
+
+	/// One line description of what the name is
+	pub description: String,
+}
+
+/// A name the user has defined
+#[derive(Clone, PartialEq, Debug)]
+struct UserName {
+	name: String,
+	signature: String,
+	description: String,
+
+	/// If the name is a function (and so is completed with an opening parenthesis)
+	is_function: bool,
+}
+
+/// What autocomplete knows about beyond the built-in functions: names the user has defined, and how
+/// often each name is used
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CompletionContext {
+	names: Vec<UserName>,
+
+	/// Number of times each name has been used
+	usage: Vec<(String, usize)>,
+}
+
+impl CompletionContext {
+	pub const EMPTY: CompletionContext = Self {
+		names: Vec::new(),
+		usage: Vec::new(),
+	};
+
+	/// Adds the name `definition` defines (if it defines one)
+	pub fn define(&mut self, definition: &Definition) {
+		let (name, signature, is_function) = match definition {
+			Definition::Expression(_) => return,
+			Definition::Function { name, param, .. } => (name, format!("{}({})", name, param), true),
+			Definition::Constant { name, .. } => (name, name.clone(), false),
+		};
+
+		self.names.push(UserName {
+			name: name.clone(),
+			description: format!("{} = {}", signature, definition.body()),
+			signature,
+			is_function,
+		});
+	}
+
+	/// Counts a use of `name`
+	pub fn record_usage(&mut self, name: &str) {
+		match self.usage.iter_mut().find(|(used, _)| used == name) {
+			Some((_, count)) => *count += 1,
+			None => self.usage.push((name.to_owned(), 1)),
+		}
+	}
+
+	/// Number of times `name` has been used
+	pub fn usage(&self, name: &str) -> usize {
+		self.usage
+			.iter()
+			.find(|(used, _)| used == name)
+			.map_or(0, |(_, count)| *count)
+	}
+
+	/// Completions of the last term of `input` to built-in functions and the names in `self`.
+	/// Ranked by how often they're used, and otherwise by the length of their hints (like
+	/// [`generate_hint`]).
+	pub fn completions(&self, input: &str) -> Vec<Completion> {
+		let chars: Vec<char> = input.chars().collect();
+		let Some(term) = get_last_term(&chars) else {
+			return Vec::new();
+		};
+		let term = term.trim_start();
+		if !term.starts_with(|c: char| c.is_ascii_alphabetic())
+			| !term.chars().all(|c| c.is_ascii_alphanumeric() | (c == '_'))
+		{
+			return Vec::new();
+		}
+
+		let builtins = function_docs().into_iter().map(|doc| {
+			(
+				doc.name.to_owned(),
+				doc.usage.to_owned(),
+				doc.description.to_owned(),
+				true,
+			)
+		});
+		let names = self.names.iter().map(|name| {
+			(
+				name.name.clone(),
+				name.signature.clone(),
+				name.description.clone(),
+				name.is_function,
+			)
+		});
+
+		let mut completions: Vec<Completion> = builtins
+			.chain(names)
+			.filter_map(|(name, signature, description, is_function)| {
+				let rest = name.strip_prefix(term)?;
+				let hint = match is_function {
+					true => format!("{}(", rest),
+					// Constants that are already written out have nothing left to complete
+					false if rest.is_empty() => return None,
+					false => rest.to_owned(),
+				};
+
+				Some(Completion {
+					name,
+					hint,
+					signature,
+					description,
+				})
+			})
+			.collect();
+
+		completions.sort_unstable_by(|a, b| {
+			(self.usage(&b.name).cmp(&self.usage(&a.name)))
+				.then(a.hint.len().cmp(&b.hint.len()))
+				.then(b.hint.cmp(&a.hint))
+		});
+		completions
+	}
+}
+
 #[derive(Clone, PartialEq)]
 pub struct AutoComplete<'a> {
 	pub i: usize,
 	pub hint: &'a Hint<'a>,
 	pub string: String,
+
+	/// Position of the caret in `string`, in characters. Hints complete the term just before it.
+	pub cursor: usize,
+
+	/// Names the term before the caret can be completed to, best first. When there are none, `hint`
+	/// is used instead (such as to close parentheses).
+	pub completions: Vec<Completion>,
+
+	/// What `completions` are drawn from
+	context: CompletionContext,
 }
 
 impl<'a> Default for AutoComplete<'a> {
 	fn default() -> AutoComplete<'a> { AutoComplete::EMPTY }
 }
 
+/// Returns true if every parenthesis in `string` is closed
+fn parens_balanced(string: &str) -> bool {
+	string.matches('(').count() <= string.matches(')').count()
+}
+
 impl<'a> AutoComplete<'a> {
 	pub const EMPTY: AutoComplete<'a> = Self {
 		i: 0,
 		hint: &HINT_EMPTY,
 		string: String::new(),
+		cursor: 0,
+		completions: Vec::new(),
+		context: CompletionContext::EMPTY,
 	};
 
+	/// Autocomplete of `string` with the caret at its end, without any user defined names
+	pub fn new(string: String) -> Self {
+		let mut autocomplete = Self {
+			cursor: string.chars().count(),
+			string,
+			..Self::EMPTY
+		};
+		autocomplete.do_update_logic();
+		autocomplete
+	}
+
+	/// Updates the string, with the caret at its end
 	#[allow(dead_code)]
-	pub fn update_string(&mut self, string: &str) {
-		if self.string != string {
+	pub fn update_string(&mut self, string: &str) { self.update(string, string.chars().count()) }
+
+	/// Updates the string and the position of the caret in it (in characters)
+	pub fn update(&mut self, string: &str, cursor: usize) {
+		let cursor = cursor.min(string.chars().count());
+		if (self.string != string) | (self.cursor != cursor) {
 			// catch empty strings here to avoid call to `generate_hint` and unnecessary logic
 			if string.is_empty() {
-				*self = Self::EMPTY;
+				*self = Self {
+					context: std::mem::take(&mut self.context),
+					..Self::EMPTY
+				};
 			} else {
 				self.string = string.to_owned();
+				self.cursor = cursor;
 				self.do_update_logic();
 			}
 		}
 	}
 
+	/// Completes user defined names from `context` along with built-in functions, and ranks
+	/// completions by how often `context` says they're used
+	pub fn set_context(&mut self, context: &CompletionContext) {
+		if &self.context != context {
+			self.context = context.clone();
+			self.completions = self.find_completions();
+			self.i = self.i.min(self.completions.len().saturating_sub(1));
+		}
+	}
+
+	/// Byte index of the caret in `string`
+	fn cursor_index(&self) -> usize {
+		self.string
+			.char_indices()
+			.nth(self.cursor)
+			.map_or(self.string.len(), |(i, _)| i)
+	}
+
+	/// Returns true if the caret is at the end of `string`
+	pub fn at_end(&self) -> bool { self.cursor_index() == self.string.len() }
+
+	/// Returns true if the caret is in the middle of a name, which can't be completed
+	fn in_name(&self) -> bool {
+		self.string[self.cursor_index()..]
+			.starts_with(|c: char| c.is_ascii_alphanumeric() | (c == '_'))
+	}
+
+	fn find_completions(&self) -> Vec<Completion> {
+		match self.in_name() {
+			true => Vec::new(),
+			false => self.context.completions(&self.string[..self.cursor_index()]),
+		}
+	}
+
 	/// Runs update logic assuming that a change to `self.string` has been made
 	fn do_update_logic(&mut self) {
 		self.i = 0;
-		self.hint = generate_hint(&self.string);
+		self.completions = self.find_completions();
+
+		let (before, after) = self.string.split_at(self.cursor_index());
+		let hint = generate_hint(before);
+		self.hint = match after.is_empty() {
+			true => hint,
+			// Hints for an empty string, or for a parenthesis that's already closed later on, don't
+			// apply in the middle of the string
+			false => match hint {
+				_ if before.is_empty() | self.in_name() => &Hint::None,
+				Hint::Single(")") if parens_balanced(&self.string) => &Hint::None,
+				hint => hint,
+			},
+		};
+	}
+
+	/// Returns true if there is anything to complete `string` with
+	pub fn has_hint(&self) -> bool { !self.completions.is_empty() | self.hint.is_some() }
+
+	/// Returns true if there are several completions to choose between
+	pub fn has_many(&self) -> bool { self.completions.len() > 1 }
+
+	/// Text `string` would be completed with if there's only one option (empty otherwise)
+	pub fn hint_text(&self) -> &str {
+		match self.completions.as_slice() {
+			[completion] => &completion.hint,
+			[] => self.hint.single().unwrap_or(""),
+			_ => "",
+		}
 	}
 
 	#[allow(dead_code)]
 	pub fn register_movement(&mut self, movement: &Movement) {
-		if movement.is_none() | self.hint.is_none() {
+		if movement.is_none() | !self.has_hint() {
 			return;
 		}
 
-		match self.hint {
-			Hint::Many(hints) => {
-				// Impossible for plural hints to be singular or non-existant
-				debug_assert!(hints.len() > 1); // check on debug
-
-				match movement {
-					Movement::Up => {
-						// Wrap self.i to maximum `i` value if needed
-						if self.i == 0 {
-							self.i = hints.len() - 1;
-						} else {
-							self.i -= 1;
-						}
-					}
-					Movement::Down => {
-						// Add one, if resulting value is above maximum `i` value, set `i` to 0
-						self.i += 1;
-						if self.i > (hints.len() - 1) {
-							self.i = 0;
-						}
-					}
-					Movement::Complete => {
-						self.apply_hint(unsafe { hints.get_unchecked(self.i) });
-					}
-					_ => unsafe { unreachable_unchecked() },
+		let count = self.completions.len();
+		match movement {
+			// Cycle through completions, wrapping around at either end
+			Movement::Up if count > 1 => self.i = self.i.checked_sub(1).unwrap_or(count - 1),
+			Movement::Down if count > 1 => self.i = (self.i + 1) % count,
+			Movement::Complete => match self.completions.get(self.i) {
+				Some(completion) => {
+					let hint = completion.hint.clone();
+					self.apply_hint(&hint);
 				}
-			}
-			Hint::Single(hint) => {
-				if movement.is_complete() {
-					self.apply_hint(hint);
+				None => {
+					let hint = self.hint;
+					if let Some(hint) = hint.single() {
+						self.apply_hint(hint);
+					}
 				}
-			}
-			Hint::None => unsafe { unreachable_unchecked() },
+			},
+			_ => {}
 		}
 	}
 
+	/// Inserts `hint` at the caret, moving the caret past it. Function names are completed along
+	/// with a closing parenthesis (leaving the caret between the two), unless a parenthesis
+	/// already follows the caret.
 	pub fn apply_hint(&mut self, hint: &str) {
-		self.string.push_str(hint);
+		let index = self.cursor_index();
+		let after = &self.string[index..];
+		let (insertion, advance) = match hint.strip_suffix('(') {
+			Some(name) if after.starts_with('(') => (name.to_owned(), hint.chars().count()),
+			Some(_) => (format!("{})", hint), hint.chars().count()),
+			None => (hint.to_owned(), hint.chars().count()),
+		};
+
+		self.string.insert_str(index, &insertion);
+		self.cursor += advance;
 		self.do_update_logic();
 	}
 }
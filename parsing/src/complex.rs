@@ -0,0 +1,370 @@
+use crate::ast::{parse_expression, BinaryOp, Constant, Expr};
+use crate::error::{ParseError, ParseErrorKind};
+use std::f64::consts::{FRAC_PI_2, LN_10, LN_2, TAU};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Name of the imaginary unit in complex functions
+const IMAGINARY_UNIT: &str = "i";
+
+/// Functions (of one argument) that complex functions can call
+const COMPLEX_FUNCTIONS: [&str; 19] = [
+	"sqrt", "cbrt", "exp", "ln", "log2", "log10", "sin", "cos", "tan", "sec", "csc", "cot", "sinh",
+	"cosh", "tanh", "asin", "acos", "atan", "abs",
+];
+
+/// Points sampled along each edge of a cell when finding how many times a function winds around
+/// zero along its boundary
+const SAMPLES_PER_EDGE: usize = 8;
+
+/// Most steps of Newton's method taken to pin down a zero or pole
+const MAX_REFINE_STEPS: usize = 32;
+
+/// Complex number `re + im·i`
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Complex {
+	pub re: f64,
+	pub im: f64,
+}
+
+impl Complex {
+	pub const ZERO: Complex = Complex::new(0.0, 0.0);
+	pub const ONE: Complex = Complex::new(1.0, 0.0);
+	pub const I: Complex = Complex::new(0.0, 1.0);
+
+	pub const fn new(re: f64, im: f64) -> Self { Self { re, im } }
+
+	pub const fn real(re: f64) -> Self { Self::new(re, 0.0) }
+
+	/// Distance from zero, `|z|`
+	pub fn abs(self) -> f64 { self.re.hypot(self.im) }
+
+	/// Angle from the positive real axis, in `(-π, π]`
+	pub fn arg(self) -> f64 { self.im.atan2(self.re) }
+
+	pub fn is_finite(self) -> bool { self.re.is_finite() && self.im.is_finite() }
+
+	pub fn powi(self, n: usize) -> Self { (0..n).fold(Self::ONE, |power, _| power * self) }
+
+	pub fn exp(self) -> Self {
+		let magnitude = self.re.exp();
+		Self::new(magnitude * self.im.cos(), magnitude * self.im.sin())
+	}
+
+	/// Principal natural logarithm
+	pub fn ln(self) -> Self { Self::new(self.abs().ln(), self.arg()) }
+
+	/// Principal square root
+	pub fn sqrt(self) -> Self {
+		let r = self.abs();
+		let re = ((r + self.re) / 2.0).sqrt();
+		let im = ((r - self.re) / 2.0).sqrt();
+		match self.im < 0.0 {
+			true => Self::new(re, -im),
+			false => Self::new(re, im),
+		}
+	}
+
+	/// `self` raised to `power`. Whole powers are multiplied out, so negative numbers can be
+	/// raised to them exactly.
+	pub fn pow(self, power: Complex) -> Self {
+		let whole = (power.im == 0.0) && (power.re.fract() == 0.0) && (power.re.abs() <= 64.0);
+		match (whole, power.re < 0.0) {
+			(true, false) => self.powi(power.re as usize),
+			(true, true) => Self::ONE / self.powi(-power.re as usize),
+			_ if self == Self::ZERO => match power.re > 0.0 {
+				true => Self::ZERO,
+				false => Self::real(f64::NAN),
+			},
+			_ => (power * self.ln()).exp(),
+		}
+	}
+
+	pub fn sin(self) -> Self {
+		Self::new(
+			self.re.sin() * self.im.cosh(),
+			self.re.cos() * self.im.sinh(),
+		)
+	}
+
+	pub fn cos(self) -> Self {
+		Self::new(
+			self.re.cos() * self.im.cosh(),
+			-self.re.sin() * self.im.sinh(),
+		)
+	}
+
+	pub fn sinh(self) -> Self {
+		Self::new(
+			self.re.sinh() * self.im.cos(),
+			self.re.cosh() * self.im.sin(),
+		)
+	}
+
+	pub fn cosh(self) -> Self {
+		Self::new(
+			self.re.cosh() * self.im.cos(),
+			self.re.sinh() * self.im.sin(),
+		)
+	}
+
+	/// Principal inverse sine, `-i·ln(i·z + sqrt(1 - z^2))`
+	pub fn asin(self) -> Self {
+		-Self::I * (Self::I * self + (Self::ONE - self * self).sqrt()).ln()
+	}
+
+	/// Principal inverse tangent, `i/2·(ln(1 - i·z) - ln(1 + i·z))`
+	pub fn atan(self) -> Self {
+		let iz = Self::I * self;
+		Self::new(0.0, 0.5) * ((Self::ONE - iz).ln() - (Self::ONE + iz).ln())
+	}
+}
+
+impl Add for Complex {
+	type Output = Self;
+	fn add(self, rhs: Self) -> Self { Self::new(self.re + rhs.re, self.im + rhs.im) }
+}
+
+impl Sub for Complex {
+	type Output = Self;
+	fn sub(self, rhs: Self) -> Self { Self::new(self.re - rhs.re, self.im - rhs.im) }
+}
+
+impl Mul for Complex {
+	type Output = Self;
+	fn mul(self, rhs: Self) -> Self {
+		Self::new(
+			self.re * rhs.re - self.im * rhs.im,
+			self.re * rhs.im + self.im * rhs.re,
+		)
+	}
+}
+
+impl Div for Complex {
+	type Output = Self;
+	fn div(self, rhs: Self) -> Self {
+		let denominator = rhs.re * rhs.re + rhs.im * rhs.im;
+		Self::new(
+			(self.re * rhs.re + self.im * rhs.im) / denominator,
+			(self.im * rhs.re - self.re * rhs.im) / denominator,
+		)
+	}
+}
+
+/// Negating leaves zero parts at `0` instead of `-0`, which keeps negative real numbers such as
+/// `-1` on the upper side of the branch cuts of `sqrt` and `ln`
+impl Neg for Complex {
+	type Output = Self;
+	fn neg(self) -> Self { Self::ZERO - self }
+}
+
+/// Applies the function `name` (one of [`COMPLEX_FUNCTIONS`]) to `z`
+fn call(name: &str, z: Complex) -> Complex {
+	match name {
+		"sqrt" => z.sqrt(),
+		// Real numbers have real cube roots, which aren't the principal ones for negative numbers
+		"cbrt" if z.im == 0.0 => Complex::real(z.re.cbrt()),
+		"cbrt" => z.pow(Complex::real(1.0 / 3.0)),
+		"exp" => z.exp(),
+		"ln" => z.ln(),
+		"log2" => z.ln() / Complex::real(LN_2),
+		"log10" => z.ln() / Complex::real(LN_10),
+		"sin" => z.sin(),
+		"cos" => z.cos(),
+		"tan" => z.sin() / z.cos(),
+		"sec" => Complex::ONE / z.cos(),
+		"csc" => Complex::ONE / z.sin(),
+		"cot" => z.cos() / z.sin(),
+		"sinh" => z.sinh(),
+		"cosh" => z.cosh(),
+		"tanh" => z.sinh() / z.cosh(),
+		"asin" => z.asin(),
+		"acos" => Complex::real(FRAC_PI_2) - z.asin(),
+		"atan" => z.atan(),
+		"abs" => Complex::real(z.abs()),
+		_ => Complex::real(f64::NAN),
+	}
+}
+
+/// Checks that `expr` only uses `variable`, the imaginary unit, and functions that are defined for
+/// complex numbers, collecting the names of other variables into `invalid`
+fn check(expr: &Expr, variable: &str, invalid: &mut Vec<String>) -> Result<(), ParseError> {
+	match expr {
+		Expr::Number(_) | Expr::Constant(_) => Ok(()),
+		Expr::Variable(name) => {
+			if (name != variable) && (name != IMAGINARY_UNIT) && !invalid.contains(name) {
+				invalid.push(name.clone());
+			}
+			Ok(())
+		}
+		Expr::Negate(inner) => check(inner, variable, invalid),
+		Expr::Binary { lhs, rhs, .. } => {
+			check(lhs, variable, invalid)?;
+			check(rhs, variable, invalid)
+		}
+		Expr::Call { name, args } => {
+			if !COMPLEX_FUNCTIONS.contains(&name.as_str()) || (args.len() != 1) {
+				return Err(ParseErrorKind::NotComplex(name.clone()).into());
+			}
+			check(&args[0], variable, invalid)
+		}
+	}
+}
+
+/// A zero or pole of a complex function
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ZeroOrPole {
+	pub z: Complex,
+
+	/// Order of the zero, such as 2 for the zero of `z^2`. Poles have negative orders, such as -1
+	/// for the pole of `1/z`.
+	pub order: i32,
+}
+
+impl ZeroOrPole {
+	pub const fn is_pole(&self) -> bool { self.order < 0 }
+
+	/// How many times the zero or pole is repeated
+	pub const fn multiplicity(&self) -> u32 { self.order.unsigned_abs() }
+}
+
+/// Function of a complex variable, such as `z^2 + 1` or `sqrt(z)`. Uses complex arithmetic
+/// throughout, so `sqrt(-1)` is `i` instead of being undefined.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComplexFunction {
+	expr: Expr,
+
+	/// Name of the function's variable
+	variable: String,
+}
+
+impl ComplexFunction {
+	/// Parses `input` as a function of `variable`, in which `i` is the imaginary unit
+	pub fn new(input: &str, variable: &str) -> Result<Self, ParseError> {
+		let expr = parse_expression(input)?;
+		let mut invalid = Vec::new();
+		check(&expr, variable, &mut invalid)?;
+		if !invalid.is_empty() {
+			return Err(ParseErrorKind::InvalidVariables(invalid).into());
+		}
+
+		Ok(Self {
+			expr,
+			variable: variable.to_owned(),
+		})
+	}
+
+	/// Value of the function at `z`
+	pub fn eval(&self, z: Complex) -> Complex { self.eval_expr(&self.expr, z) }
+
+	fn eval_expr(&self, expr: &Expr, z: Complex) -> Complex {
+		match expr {
+			Expr::Number(n) => Complex::real(*n),
+			Expr::Constant(Constant::Pi) => Complex::real(std::f64::consts::PI),
+			Expr::Constant(Constant::E) => Complex::real(std::f64::consts::E),
+			Expr::Variable(name) if *name == self.variable => z,
+			Expr::Variable(_) => Complex::I,
+			Expr::Negate(inner) => -self.eval_expr(inner, z),
+			Expr::Binary { op, lhs, rhs } => {
+				let (lhs, rhs) = (self.eval_expr(lhs, z), self.eval_expr(rhs, z));
+				match op {
+					BinaryOp::Add => lhs + rhs,
+					BinaryOp::Sub => lhs - rhs,
+					BinaryOp::Mul => lhs * rhs,
+					BinaryOp::Div => lhs / rhs,
+					BinaryOp::Pow => lhs.pow(rhs),
+				}
+			}
+			Expr::Call { name, args } => call(name, self.eval_expr(&args[0], z)),
+		}
+	}
+
+	/// Finds the zeros and poles of the function in the rectangle from `min` to `max`, which is
+	/// split into a grid of `cells` (columns and rows). Each cell's zeros and poles are counted by
+	/// how many times the function winds around zero along the cell's edge (the argument
+	/// principle), then pinned down with Newton's method.
+	pub fn zeros_and_poles(
+		&self, min: Complex, max: Complex, cells: [usize; 2],
+	) -> Vec<ZeroOrPole> {
+		let [columns, rows] = cells;
+		let size = Complex::new(
+			(max.re - min.re) / columns as f64,
+			(max.im - min.im) / rows as f64,
+		);
+
+		let mut found: Vec<ZeroOrPole> = Vec::new();
+		for row in 0..rows {
+			for column in 0..columns {
+				let corner = min + Complex::new(column as f64 * size.re, row as f64 * size.im);
+				let order = self.winding_number(corner, size);
+				if order == 0 {
+					continue;
+				}
+
+				let center = corner + Complex::new(size.re / 2.0, size.im / 2.0);
+				let z = self.refine(center, order, size).unwrap_or(center);
+
+				// Zeros and poles on the edge between cells can be found by both
+				let near = |other: &ZeroOrPole| (other.z - z).abs() < size.abs() / 2.0;
+				let duplicate = found.iter().any(|other| (other.order == order) && near(other));
+				if !duplicate {
+					found.push(ZeroOrPole { z, order });
+				}
+			}
+		}
+		found
+	}
+
+	/// Number of times the function winds around zero along the edge of the cell with the corner
+	/// `corner` and size `size`, which is the number of zeros minus the number of poles inside it
+	fn winding_number(&self, corner: Complex, size: Complex) -> i32 {
+		let edge = |start: Complex, step: Complex| {
+			let fraction = |i: usize| Complex::real(i as f64 / SAMPLES_PER_EDGE as f64);
+			(0..SAMPLES_PER_EDGE).map(move |i| start + step * fraction(i))
+		};
+		let (width, height) = (Complex::real(size.re), Complex::new(0.0, size.im));
+		let boundary: Vec<Complex> = edge(corner, width)
+			.chain(edge(corner + width, height))
+			.chain(edge(corner + width + height, -width))
+			.chain(edge(corner + height, -height))
+			.map(|z| self.eval(z))
+			.collect();
+		if boundary.iter().any(|w| !w.is_finite() || (*w == Complex::ZERO)) {
+			return 0;
+		}
+
+		// Angles between consecutive values, each of which is less than half a turn
+		let total: f64 = (0..boundary.len())
+			.map(|i| (boundary[(i + 1) % boundary.len()] / boundary[i]).arg())
+			.sum();
+		(total / TAU).round() as i32
+	}
+
+	/// Pins down the zero (or pole, for negative `order`) near `start` with Newton's method,
+	/// modified for zeros and poles repeated several times. `None` if it wanders away.
+	fn refine(&self, start: Complex, order: i32, size: Complex) -> Option<Complex> {
+		// Poles of the function are zeros of its reciprocal
+		let g = |z: Complex| match order < 0 {
+			true => Complex::ONE / self.eval(z),
+			false => self.eval(z),
+		};
+		let h = Complex::real(size.abs() * 1e-6);
+		let multiplicity = Complex::real(order.unsigned_abs() as f64);
+
+		let mut z = start;
+		for _ in 0..MAX_REFINE_STEPS {
+			let slope = (g(z + h) - g(z - h)) / (h + h);
+			let step = multiplicity * g(z) / slope;
+			if !step.is_finite() {
+				break;
+			}
+
+			z = z - step;
+			if step.abs() < 1e-12 * z.abs().max(1.0) {
+				break;
+			}
+		}
+
+		let near = ((z - start).re.abs() <= size.re) && ((z - start).im.abs() <= size.im);
+		(z.is_finite() && near).then_some(z)
+	}
+}
@@ -0,0 +1,241 @@
+use crate::functions::{erfc, is_natural, ln_gamma};
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+/// Most iterations the series and continued fractions below take to converge
+const MAX_ITERATIONS: usize = 1_000;
+
+/// Stand-in for zero in the continued fractions, which would otherwise divide by zero
+const TINY: f64 = 1e-300;
+
+/// Returns true if `x` is positive, which `NaN` isn't
+fn is_positive(x: f64) -> bool { x > 0.0 }
+
+/// Regularized lower incomplete gamma function `P(a, x)` for `a > 0` and `x >= 0`
+fn gamma_p(a: f64, x: f64) -> f64 {
+	if !is_positive(a) | !(0.0..).contains(&x) {
+		return f64::NAN;
+	}
+	if x == 0.0 {
+		return 0.0;
+	}
+
+	let scale = (a * x.ln() - x - ln_gamma(a)).exp();
+
+	// Power series, which converges quickly for small `x`
+	if x < a + 1.0 {
+		let mut term = 1.0 / a;
+		let mut sum = term;
+		for n in 1..MAX_ITERATIONS {
+			term *= x / (a + n as f64);
+			sum += term;
+			if term.abs() < sum.abs() * f64::EPSILON {
+				break;
+			}
+		}
+		return sum * scale;
+	}
+
+	// Continued fraction of `Q(a, x) = 1 - P(a, x)` using Lentz's method
+	let mut b = x + 1.0 - a;
+	let mut c = 1.0 / TINY;
+	let mut d = 1.0 / b;
+	let mut fraction = d;
+	for i in 1..MAX_ITERATIONS {
+		let an = -(i as f64) * (i as f64 - a);
+		b += 2.0;
+		d = an * d + b;
+		if d.abs() < TINY {
+			d = TINY;
+		}
+		c = b + an / c;
+		if c.abs() < TINY {
+			c = TINY;
+		}
+		d = 1.0 / d;
+		let delta = d * c;
+		fraction *= delta;
+		if (delta - 1.0).abs() < f64::EPSILON {
+			break;
+		}
+	}
+	1.0 - scale * fraction
+}
+
+/// Continued fraction used by [`beta_regularized`], evaluated with Lentz's method
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+	let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+	let mut c = 1.0;
+	let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+	let mut fraction = d;
+	for m in 1..MAX_ITERATIONS {
+		let m = m as f64;
+
+		let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+		d = 1.0 / clamp(1.0 + even * d);
+		c = clamp(1.0 + even / c);
+		fraction *= d * c;
+
+		let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+		d = 1.0 / clamp(1.0 + odd * d);
+		c = clamp(1.0 + odd / c);
+		let delta = d * c;
+		fraction *= delta;
+		if (delta - 1.0).abs() < f64::EPSILON {
+			break;
+		}
+	}
+	fraction
+}
+
+/// Regularized incomplete beta function `I_x(a, b)` for `a, b > 0` and `0 <= x <= 1`
+fn beta_regularized(x: f64, a: f64, b: f64) -> f64 {
+	if !is_positive(a) | !is_positive(b) | !(0.0..=1.0).contains(&x) {
+		return f64::NAN;
+	}
+	if (x == 0.0) | (x == 1.0) {
+		return x;
+	}
+
+	let ln_beta = ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b);
+	let scale = (a * x.ln() + b * (1.0 - x).ln() - ln_beta).exp();
+
+	// The continued fraction converges quickly on this side, so use symmetry on the other
+	if x < (a + 1.0) / (a + b + 2.0) {
+		scale * beta_fraction(a, b, x) / a
+	} else {
+		1.0 - scale * beta_fraction(b, a, 1.0 - x) / b
+	}
+}
+
+pub(crate) fn normal_pdf(x: f64, mean: f64, sd: f64) -> f64 {
+	if !is_positive(sd) {
+		return f64::NAN;
+	}
+
+	let z = (x - mean) / sd;
+	(-z * z / 2.0).exp() / (sd * (2.0 * PI).sqrt())
+}
+
+pub(crate) fn normal_cdf(x: f64, mean: f64, sd: f64) -> f64 {
+	if !is_positive(sd) {
+		return f64::NAN;
+	}
+
+	erfc(-(x - mean) / sd * FRAC_1_SQRT_2) / 2.0
+}
+
+/// Density of Student's t distribution with `v` degrees of freedom
+pub(crate) fn t_pdf(x: f64, v: f64) -> f64 {
+	if !is_positive(v) {
+		return f64::NAN;
+	}
+
+	let ln_scale = ln_gamma((v + 1.0) / 2.0) - ln_gamma(v / 2.0) - (v * PI).ln() / 2.0;
+	(ln_scale - (v + 1.0) / 2.0 * (x * x / v).ln_1p()).exp()
+}
+
+pub(crate) fn t_cdf(x: f64, v: f64) -> f64 {
+	if !is_positive(v) | x.is_nan() {
+		return f64::NAN;
+	}
+
+	let tail = beta_regularized(v / (v + x * x), v / 2.0, 0.5) / 2.0;
+	if x > 0.0 {
+		1.0 - tail
+	} else {
+		tail
+	}
+}
+
+/// Density of the chi-squared distribution with `k` degrees of freedom
+pub(crate) fn chi_squared_pdf(x: f64, k: f64) -> f64 {
+	if !is_positive(k) | x.is_nan() {
+		return f64::NAN;
+	}
+	if x < 0.0 {
+		return 0.0;
+	}
+
+	let half = k / 2.0;
+	((half - 1.0) * x.ln() - x / 2.0 - half * 2.0_f64.ln() - ln_gamma(half)).exp()
+}
+
+pub(crate) fn chi_squared_cdf(x: f64, k: f64) -> f64 {
+	if !is_positive(k) | x.is_nan() {
+		return f64::NAN;
+	}
+
+	gamma_p(k / 2.0, x.max(0.0) / 2.0)
+}
+
+/// Probability of `k` successes in `n` trials that each succeed with probability `p`. `k` is
+/// rounded down so the graph is a step function, whose area between integers is the probability.
+pub(crate) fn binomial_pdf(k: f64, n: f64, p: f64) -> f64 {
+	if !is_natural(n) | !(0.0..=1.0).contains(&p) | k.is_nan() {
+		return f64::NAN;
+	}
+
+	let k = k.floor();
+	if (k < 0.0) | (k > n) {
+		return 0.0;
+	}
+
+	// `0^0` is 1 here
+	let ln_successes = if k == 0.0 { 0.0 } else { k * p.ln() };
+	let ln_failures = if k == n { 0.0 } else { (n - k) * (-p).ln_1p() };
+	(ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0) + ln_successes + ln_failures)
+		.exp()
+}
+
+/// Probability of at most `k` successes in `n` trials, see [`binomial_pdf`]
+pub(crate) fn binomial_cdf(k: f64, n: f64, p: f64) -> f64 {
+	if !is_natural(n) | !(0.0..=1.0).contains(&p) | k.is_nan() {
+		return f64::NAN;
+	}
+
+	let k = k.floor();
+	if k < 0.0 {
+		0.0
+	} else if (k >= n) | (p == 0.0) {
+		1.0
+	} else if p == 1.0 {
+		0.0
+	} else {
+		beta_regularized(1.0 - p, n - k, k + 1.0)
+	}
+}
+
+/// Probability of `k` events happening when `lambda` are expected. `k` is rounded down like in
+/// [`binomial_pdf`].
+pub(crate) fn poisson_pdf(k: f64, lambda: f64) -> f64 {
+	if !(0.0..).contains(&lambda) | k.is_nan() {
+		return f64::NAN;
+	}
+
+	let k = k.floor();
+	if k < 0.0 {
+		return 0.0;
+	}
+	if lambda == 0.0 {
+		return if k == 0.0 { 1.0 } else { 0.0 };
+	}
+
+	(k * lambda.ln() - lambda - ln_gamma(k + 1.0)).exp()
+}
+
+/// Probability of at most `k` events happening when `lambda` are expected
+pub(crate) fn poisson_cdf(k: f64, lambda: f64) -> f64 {
+	if !(0.0..).contains(&lambda) | k.is_nan() {
+		return f64::NAN;
+	}
+
+	let k = k.floor();
+	if k < 0.0 {
+		0.0
+	} else if lambda == 0.0 {
+		1.0
+	} else {
+		1.0 - gamma_p(k + 1.0, lambda)
+	}
+}
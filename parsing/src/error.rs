@@ -0,0 +1,349 @@
+use crate::SUPPORTED_FUNCTIONS;
+use std::fmt;
+use std::ops::Range;
+
+/// What went wrong while parsing an expression or definition
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseErrorKind {
+	/// A call to a function that doesn't exist, such as `foo(x)`
+	UnknownFunction(String),
+
+	/// A parenthesis that is never closed, or one that closes nothing
+	UnbalancedParens,
+
+	/// Variables that aren't defined where they are used, such as `y` in `x + y`
+	InvalidVariables(Vec<String>),
+
+	/// The variable of an operator isn't a valid name, such as the `2` in `integral(t, 2, 0, 1)`
+	InvalidOperatorVariable { operator: String, variable: String },
+
+	/// A function called with the wrong number of arguments
+	WrongArgumentCount { function: String, usage: String },
+
+	/// A function called with an argument that is empty, such as `atan2(1, )`
+	EmptyArgument { function: String, usage: String },
+
+	/// A function used without calling it, such as `nCr` on its own
+	NotCalled { function: String, usage: String },
+
+	/// A call to a function whose parenthesis is never closed
+	UnclosedCall(String),
+
+	/// A character or operator where it can't be, such as the `*` in `2+*3`
+	UnexpectedToken(String),
+
+	/// An expression that ends where a value is still needed, such as `2+`
+	ExpectedExpression,
+
+	/// A number that isn't valid, such as `1.2.3`
+	InvalidNumber(String),
+
+	/// An expression that exmex couldn't parse, along with its description of the problem
+	Syntax(String),
+
+	/// A constant expression that isn't defined, such as `1/0`
+	Undefined,
+
+	/// A value that is empty
+	ExpectedValue,
+
+	/// A value that depends on `x` when it may only use numbers and constants
+	DependsOnX,
+
+	/// A constant (such as `k = 2x`) that depends on `x`
+	ConstantDependsOnX,
+
+	/// A definition with more than one `=`
+	MultipleEquals,
+
+	/// A definition whose name isn't a valid identifier
+	InvalidName(String),
+
+	/// A definition whose name is already used by a built-in function or constant
+	Reserved(String),
+
+	/// A function definition whose parameter isn't a valid identifier
+	InvalidParameter(String),
+
+	/// A function definition that uses `x` when its parameter is something else, such as
+	/// `f(t) = t·x`
+	XNotParameter(String),
+
+	/// A definition whose left hand side isn't a name or a function of one parameter
+	InvalidDefinition(String),
+
+	/// A name defined by more than one function
+	AlreadyDefined(String),
+
+	/// A definition that uses another definition which has an error
+	BrokenDependency(String),
+
+	/// Definitions that end up depending on themselves
+	CircularDefinition,
+
+	/// A function that isn't defined for complex numbers, such as `floor`
+	NotComplex(String),
+}
+
+impl fmt::Display for ParseErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnknownFunction(name) => write!(f, "Error: unknown function: {}", name),
+			Self::UnbalancedParens => write!(f, "Error: unbalanced parentheses"),
+			Self::InvalidVariables(names) => match names.as_slice() {
+				[name] => write!(f, "Error: invalid variable: {}", name),
+				_ => write!(f, "Error: invalid variables: {:?}", names),
+			},
+			Self::InvalidOperatorVariable { operator, variable } => {
+				write!(f, "Error: invalid variable for {}: {}", operator, variable)
+			}
+			Self::WrongArgumentCount { function, usage } => write!(
+				f,
+				"Error: wrong number of arguments to {}, expected {}",
+				function, usage
+			),
+			Self::EmptyArgument { function, usage } => {
+				write!(f, "Error: empty argument to {}, expected {}", function, usage)
+			}
+			Self::NotCalled { function, usage } => {
+				write!(f, "Error: {} must be called like {}", function, usage)
+			}
+			Self::UnclosedCall(name) => write!(f, "Error: unclosed call to {}", name),
+			Self::UnexpectedToken(token) => write!(f, "Error: unexpected '{}'", token),
+			Self::ExpectedExpression => write!(f, "Error: expected an expression"),
+			Self::InvalidNumber(number) => write!(f, "Error: invalid number: {}", number),
+			Self::Syntax(message) => write!(f, "{}", message),
+			Self::Undefined => write!(f, "Error: expression is undefined"),
+			Self::ExpectedValue => write!(f, "Error: expected a value"),
+			Self::DependsOnX => write!(f, "Error: value can't depend on x"),
+			Self::ConstantDependsOnX => write!(f, "Error: constants can't depend on x"),
+			Self::MultipleEquals => write!(f, "Error: more than one '='"),
+			Self::InvalidName(name) => write!(f, "Error: invalid name: {}", name),
+			Self::Reserved(name) => write!(f, "Error: {} is reserved", name),
+			Self::InvalidParameter(param) => write!(f, "Error: invalid parameter: {}", param),
+			Self::XNotParameter(param) => {
+				write!(f, "Error: x can't be used when the parameter is {}", param)
+			}
+			Self::InvalidDefinition(lhs) => write!(f, "Error: invalid definition: {}", lhs),
+			Self::AlreadyDefined(name) => write!(f, "Error: {} is already defined", name),
+			Self::BrokenDependency(name) => write!(f, "Error: {} has an error", name),
+			Self::CircularDefinition => write!(f, "Error: circular definition"),
+			Self::NotComplex(name) => {
+				write!(f, "Error: {} isn't supported for complex numbers", name)
+			}
+		}
+	}
+}
+
+/// A suggested change that fixes a [`ParseError`], replacing the bytes in `span` with `replacement`
+#[derive(Clone, PartialEq, Debug)]
+pub struct FixIt {
+	pub span: Range<usize>,
+	pub replacement: String,
+}
+
+impl FixIt {
+	/// Applies the fix to `source`, the string its span is into. `None` if the span doesn't fit
+	/// `source`, such as after it has been edited.
+	pub fn apply(&self, source: &str) -> Option<String> {
+		let before = source.get(..self.span.start)?;
+		let after = source.get(self.span.end..)?;
+		Some(format!("{}{}{}", before, self.replacement, after))
+	}
+}
+
+/// Error from parsing an expression or definition, along with where in the string it happened
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseError {
+	pub kind: ParseErrorKind,
+
+	/// Bytes of the string being parsed that caused the error, if they are known
+	pub span: Option<Range<usize>>,
+
+	/// Change that would fix the error
+	pub fix: Option<FixIt>,
+}
+
+impl From<ParseErrorKind> for ParseError {
+	fn from(kind: ParseErrorKind) -> Self { Self::new(kind) }
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.kind.fmt(f) }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+	pub const fn new(kind: ParseErrorKind) -> Self {
+		Self {
+			kind,
+			span: None,
+			fix: None,
+		}
+	}
+
+	/// Marks `span` as the bytes that caused the error
+	pub(crate) fn at(self, span: Range<usize>) -> Self {
+		Self {
+			span: Some(span),
+			..self
+		}
+	}
+
+	/// Suggests replacing the bytes in `span` with `replacement` to fix the error
+	pub(crate) fn with_fix(self, span: Range<usize>, replacement: &str) -> Self {
+		Self {
+			fix: Some(FixIt {
+				span,
+				replacement: replacement.to_owned(),
+			}),
+			..self
+		}
+	}
+
+	/// Error for a call to the unknown function `name`, suggesting the closest supported function
+	pub(crate) fn unknown_function(name: &str, span: Range<usize>) -> Self {
+		let error = Self::new(ParseErrorKind::UnknownFunction(name.to_owned())).at(span.clone());
+		match closest_function(name) {
+			Some(suggestion) => error.with_fix(span, suggestion),
+			None => error,
+		}
+	}
+
+	/// Converts the error's spans with `map`
+	pub(crate) fn map_spans(self, map: impl Fn(Range<usize>) -> Range<usize>) -> Self {
+		Self {
+			span: self.span.map(&map),
+			fix: self.fix.map(|fix| FixIt {
+				span: map(fix.span),
+				..fix
+			}),
+			kind: self.kind,
+		}
+	}
+
+	/// Moves the error's spans by `offset` bytes, for errors found in a substring of the string being
+	/// parsed
+	pub(crate) fn offset(self, offset: usize) -> Self {
+		self.map_spans(|span| (span.start + offset)..(span.end + offset))
+	}
+
+	/// Converts the error's spans from bytes of `parsed` (a rewritten form of `source`, such as
+	/// after being processed via [`crate::process_func_str`]) into bytes of `source`
+	pub fn in_source(self, parsed: &str, source: &str) -> Self {
+		let alignment = align(parsed, source);
+		let locate = |span: Range<usize>| locate_span(&alignment, parsed, source, span);
+		Self {
+			span: self.span.and_then(locate),
+			fix: self
+				.fix
+				.and_then(|fix| locate(fix.span.clone()).map(|span| FixIt { span, ..fix })),
+			kind: self.kind,
+		}
+	}
+
+	/// Like [`ParseError::in_source`], where `raw` is a whole definition (such as `f(x) = x^2`) and
+	/// `parsed` is a rewritten form of its body
+	pub fn in_definition(self, parsed: &str, raw: &str) -> Self {
+		let offset = raw.find('=').map_or(0, |i| i + 1);
+		self.in_source(parsed, &raw[offset..]).offset(offset)
+	}
+}
+
+/// Byte range of `inner` within `outer`, where `inner` is a substring slice of `outer`
+pub(crate) fn span_within(outer: &str, inner: &str) -> Range<usize> {
+	let start = (inner.as_ptr() as usize).saturating_sub(outer.as_ptr() as usize);
+	start..(start + inner.len())
+}
+
+/// Number of single character insertions, deletions, or substitutions that turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+	for (i, ca) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+		for (j, cb) in b.iter().enumerate() {
+			let substitution = diagonal + (ca != *cb) as usize;
+			diagonal = row[j + 1];
+			row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+		}
+	}
+	row[b.len()]
+}
+
+/// Supported function whose name is closest to `name`, if any are close enough to be a typo of it
+fn closest_function(name: &str) -> Option<&'static str> {
+	let lowercase = name.to_lowercase();
+	let max_distance = (name.len() / 3).max(1);
+	SUPPORTED_FUNCTIONS
+		.iter()
+		.map(|&function| (function, edit_distance(&lowercase, &function.to_lowercase())))
+		.filter(|&(_, distance)| distance <= max_distance)
+		.min_by_key(|&(_, distance)| distance)
+		.map(|(function, _)| function)
+}
+
+/// Aligns the characters of `parsed` with the characters of `source` they came from, returning the
+/// byte range of `source` each character of `parsed` corresponds to. Characters that were inserted
+/// (such as implicit multiplication) correspond to an empty range where they were inserted.
+fn align(parsed: &str, source: &str) -> Vec<Range<usize>> {
+	let a: Vec<char> = parsed.chars().collect();
+	let (offsets, b): (Vec<usize>, Vec<char>) = source.char_indices().unzip();
+	let byte = |j: usize| offsets.get(j).copied().unwrap_or(source.len());
+
+	// `common[i][j]` is the length of the longest common subsequence of `a[i..]` and `b[j..]`
+	let mut common = vec![vec![0_usize; b.len() + 1]; a.len() + 1];
+	for i in (0..a.len()).rev() {
+		for j in (0..b.len()).rev() {
+			common[i][j] = if a[i] == b[j] {
+				common[i + 1][j + 1] + 1
+			} else {
+				common[i + 1][j].max(common[i][j + 1])
+			};
+		}
+	}
+
+	let mut result = Vec::with_capacity(a.len());
+
+	// Start of the characters of `parsed` and `source` since the last matching pair
+	let (mut gap_i, mut gap_j) = (0, 0);
+	let (mut i, mut j) = (0, 0);
+	while i < a.len() {
+		if (j < b.len()) && (a[i] == b[j]) && (common[i][j] == common[i + 1][j + 1] + 1) {
+			// Characters in between matches were rewritten from the characters between them in `source`
+			result.extend((gap_i..i).map(|_| byte(gap_j)..byte(j)));
+			result.push(byte(j)..byte(j + 1));
+			i += 1;
+			j += 1;
+			(gap_i, gap_j) = (i, j);
+		} else if (j >= b.len()) || (common[i + 1][j] >= common[i][j + 1]) {
+			i += 1;
+		} else {
+			j += 1;
+		}
+	}
+	result.extend((gap_i..a.len()).map(|_| byte(gap_j)..source.len()));
+	result
+}
+
+/// Converts `span` of bytes in `parsed` into bytes of `source` using `alignment` from [`align`]
+fn locate_span(
+	alignment: &[Range<usize>], parsed: &str, source: &str, span: Range<usize>,
+) -> Option<Range<usize>> {
+	let start = parsed.get(..span.start)?.chars().count();
+	let end = parsed.get(..span.end)?.chars().count();
+
+	if start == end {
+		let position = alignment
+			.get(start)
+			.map_or(source.len(), |range| range.start);
+		return Some(position..position);
+	}
+
+	let ranges = alignment.get(start..end)?;
+	let first = ranges.iter().map(|range| range.start).min()?;
+	let last = ranges.iter().map(|range| range.end).max()?;
+	Some(first..last)
+}
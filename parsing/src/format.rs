@@ -0,0 +1,81 @@
+use crate::ast::{parse_expression, BinaryOp, Constant, Expr};
+use crate::error::ParseError;
+use std::fmt;
+
+/// Returns true if `expr` is written starting with a minus sign, which needs parentheses after
+/// another operator (such as in `2·(-x)`)
+fn is_negative(expr: &Expr) -> bool {
+	match expr {
+		Expr::Negate(_) => true,
+		Expr::Number(n) => n.is_sign_negative(),
+		_ => false,
+	}
+}
+
+/// Returns true if `expr` is a power, such as `x^2`
+const fn is_power(expr: &Expr) -> bool {
+	matches!(
+		expr,
+		Expr::Binary {
+			op: BinaryOp::Pow,
+			..
+		}
+	)
+}
+
+/// Writes `expr`, wrapped in parentheses if `parens` is true
+fn write_grouped(f: &mut fmt::Formatter, expr: &Expr, parens: bool) -> fmt::Result {
+	match parens {
+		true => write!(f, "({})", expr),
+		false => write!(f, "{}", expr),
+	}
+}
+
+/// Writes expressions in a canonical form: multiplication is written out as `·`, `+` and `-` are
+/// surrounded by single spaces, constants are written as `π` and `e`, and only the parentheses
+/// that are needed are kept. Parsing and writing the result again gives the same string.
+impl fmt::Display for Expr {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Expr::Number(n) => write!(f, "{}", n),
+			Expr::Constant(Constant::Pi) => write!(f, "π"),
+			Expr::Constant(Constant::E) => write!(f, "e"),
+			Expr::Variable(name) => write!(f, "{}", name),
+			Expr::Negate(inner) => {
+				write!(f, "-")?;
+				// `-x^2` is read as `(-x)^2`, so negated powers keep their parentheses
+				write_grouped(f, inner, inner.is_sum() | is_negative(inner) | is_power(inner))
+			}
+			Expr::Binary { op, lhs, rhs } => {
+				let (lhs_parens, separator, rhs_parens) = match op {
+					BinaryOp::Add => (false, " + ", is_negative(rhs)),
+					BinaryOp::Sub => (false, " - ", rhs.is_sum() | is_negative(rhs)),
+					BinaryOp::Mul => (lhs.is_sum(), "·", rhs.is_sum() | is_negative(rhs)),
+					BinaryOp::Div => (lhs.is_sum(), "/", !rhs.is_atom() && !is_power(rhs)),
+					// Powers of powers keep their parentheses, as exmex reads `2^3^x` as `(2^3)^x`
+					BinaryOp::Pow => (!lhs.is_atom() | is_negative(lhs), "^", !rhs.is_atom()),
+				};
+
+				write_grouped(f, lhs, lhs_parens)?;
+				write!(f, "{}", separator)?;
+				write_grouped(f, rhs, rhs_parens)
+			}
+			Expr::Call { name, args } => {
+				write!(f, "{}(", name)?;
+				for (i, arg) in args.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{}", arg)?;
+				}
+				write!(f, ")")
+			}
+		}
+	}
+}
+
+/// Writes `input` in canonical form (see [`Expr`]'s `Display` implementation), such as `2·sin(x)·x`
+/// for `2sin(x)x` or `x^2 + 1` for `x**2+1`
+pub fn format_expression(input: &str) -> Result<String, ParseError> {
+	parse_expression(input).map(|expr| expr.to_string())
+}
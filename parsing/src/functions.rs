@@ -0,0 +1,546 @@
+use crate::distributions::{
+	binomial_cdf, binomial_pdf, chi_squared_cdf, chi_squared_pdf, normal_cdf, normal_pdf, poisson_cdf,
+	poisson_pdf, t_cdf, t_pdf,
+};
+use crate::operators::OperatorKind;
+use std::f64::consts::{FRAC_2_SQRT_PI, PI};
+
+/// `sqrt(2π)`
+const SQRT_TAU: f64 = 2.5066282746310002;
+
+/// `g` parameter of the Lanczos approximation of the gamma function
+const LANCZOS_G: f64 = 7.0;
+
+/// Coefficients of the Lanczos approximation for `LANCZOS_G`
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+	0.999_999_999_999_809_9,
+	676.520_368_121_885_1,
+	-1_259.139_216_722_402_8,
+	771.323_428_777_653_1,
+	-176.615_029_162_140_6,
+	12.507_343_278_686_905,
+	-0.138_571_095_265_720_12,
+	9.984_369_578_019_572e-6,
+	1.505_632_735_149_311_6e-7,
+];
+
+/// Largest integer whose factorial fits in an `f64`
+const MAX_FACTORIAL: f64 = 170.0;
+
+/// Past this distance from zero `erf` and `erfc` are calculated with a continued fraction instead of
+/// a power series, which loses precision to cancellation as `|x|` grows
+const ERF_SERIES_LIMIT: f64 = 2.0;
+
+/// Number of terms of the continued fraction `erfc` is calculated with
+const ERFC_FRACTION_TERMS: usize = 80;
+
+/// Past this distance from zero the bessel functions are calculated with their asymptotic expansion
+/// instead of a power series
+const BESSEL_SERIES_LIMIT: f64 = 12.0;
+
+/// Functions exmex evaluates itself along with how they're called and what they do
+const EXMEX_FUNCTIONS: [(&str, &str, &str); 22] = [
+	("abs", "abs(x)", "Absolute value of x"),
+	("signum", "signum(x)", "Sign of x"),
+	("sin", "sin(x)", "Sine of x"),
+	("cos", "cos(x)", "Cosine of x"),
+	("tan", "tan(x)", "Tangent of x"),
+	("asin", "asin(x)", "Inverse sine of x"),
+	("acos", "acos(x)", "Inverse cosine of x"),
+	("atan", "atan(x)", "Inverse tangent of x"),
+	("sinh", "sinh(x)", "Hyperbolic sine of x"),
+	("cosh", "cosh(x)", "Hyperbolic cosine of x"),
+	("tanh", "tanh(x)", "Hyperbolic tangent of x"),
+	("floor", "floor(x)", "Largest integer less than or equal to x"),
+	("round", "round(x)", "Integer nearest to x"),
+	("ceil", "ceil(x)", "Smallest integer greater than or equal to x"),
+	("trunc", "trunc(x)", "Integer part of x"),
+	("fract", "fract(x)", "Fractional part of x"),
+	("exp", "exp(x)", "e raised to the power of x"),
+	("sqrt", "sqrt(x)", "Square root of x"),
+	("cbrt", "cbrt(x)", "Cube root of x"),
+	("ln", "ln(x)", "Natural logarithm of x"),
+	("log2", "log2(x)", "Base 2 logarithm of x"),
+	("log10", "log10(x)", "Base 10 logarithm of x"),
+];
+
+/// Documentation of a function that expressions can use
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FunctionDoc {
+	pub name: &'static str,
+
+	/// How the function is called, such as `atan2(y, x)`
+	pub usage: &'static str,
+
+	/// One line description of what the function does
+	pub description: &'static str,
+}
+
+/// Documents every function expressions can use, in the order they should be listed
+pub fn function_docs() -> Vec<FunctionDoc> {
+	let exmex = EXMEX_FUNCTIONS
+		.iter()
+		.map(|&(name, usage, description)| FunctionDoc {
+			name,
+			usage,
+			description,
+		});
+
+	let operators = OperatorKind::ALL.iter().map(|kind| FunctionDoc {
+		name: kind.name(),
+		usage: kind.usage(),
+		description: kind.description(),
+	});
+
+	let functions = Function::ALL.iter().map(|function| FunctionDoc {
+		name: function.name(),
+		usage: function.usage(),
+		description: function.description(),
+	});
+
+	exmex.chain(operators).chain(functions).collect()
+}
+
+/// How the supported function `name` is called, such as `atan2(y, x)`
+pub(crate) fn function_usage(name: &str) -> Option<&'static str> {
+	function_docs()
+		.into_iter()
+		.find(|doc| doc.name == name)
+		.map(|doc| doc.usage)
+}
+
+/// Built-in functions that exmex doesn't support, which are evaluated numerically
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Function {
+	Gamma,
+	Factorial,
+	Erf,
+	Erfc,
+	Beta,
+	BesselJ0,
+	BesselJ1,
+	Sec,
+	Csc,
+	Cot,
+	Asec,
+	Acsc,
+	Acot,
+	Atan2,
+	Min,
+	Max,
+	Mod,
+	Combinations,
+	Permutations,
+	Log,
+	Sigmoid,
+	NormalPdf,
+	NormalCdf,
+	TPdf,
+	TCdf,
+	ChiSquaredPdf,
+	ChiSquaredCdf,
+	BinomialPdf,
+	BinomialCdf,
+	PoissonPdf,
+	PoissonCdf,
+}
+
+impl Function {
+	const ALL: [Function; 31] = [
+		Self::Gamma,
+		Self::Factorial,
+		Self::Erf,
+		Self::Erfc,
+		Self::Beta,
+		Self::BesselJ0,
+		Self::BesselJ1,
+		Self::Sec,
+		Self::Csc,
+		Self::Cot,
+		Self::Asec,
+		Self::Acsc,
+		Self::Acot,
+		Self::Atan2,
+		Self::Min,
+		Self::Max,
+		Self::Mod,
+		Self::Combinations,
+		Self::Permutations,
+		Self::Log,
+		Self::Sigmoid,
+		Self::NormalPdf,
+		Self::NormalCdf,
+		Self::TPdf,
+		Self::TCdf,
+		Self::ChiSquaredPdf,
+		Self::ChiSquaredCdf,
+		Self::BinomialPdf,
+		Self::BinomialCdf,
+		Self::PoissonPdf,
+		Self::PoissonCdf,
+	];
+
+	pub(crate) fn from_name(name: &str) -> Option<Self> {
+		Self::ALL
+			.iter()
+			.find(|function| function.name() == name)
+			.copied()
+	}
+
+	pub(crate) const fn name(self) -> &'static str {
+		match self {
+			Self::Gamma => "gamma",
+			Self::Factorial => "factorial",
+			Self::Erf => "erf",
+			Self::Erfc => "erfc",
+			Self::Beta => "beta",
+			Self::BesselJ0 => "besselj0",
+			Self::BesselJ1 => "besselj1",
+			Self::Sec => "sec",
+			Self::Csc => "csc",
+			Self::Cot => "cot",
+			Self::Asec => "asec",
+			Self::Acsc => "acsc",
+			Self::Acot => "acot",
+			Self::Atan2 => "atan2",
+			Self::Min => "min",
+			Self::Max => "max",
+			Self::Mod => "mod",
+			Self::Combinations => "nCr",
+			Self::Permutations => "nPr",
+			Self::Log => "log",
+			Self::Sigmoid => "sigmoid",
+			Self::NormalPdf => "normalpdf",
+			Self::NormalCdf => "normalcdf",
+			Self::TPdf => "tpdf",
+			Self::TCdf => "tcdf",
+			Self::ChiSquaredPdf => "chisqpdf",
+			Self::ChiSquaredCdf => "chisqcdf",
+			Self::BinomialPdf => "binompdf",
+			Self::BinomialCdf => "binomcdf",
+			Self::PoissonPdf => "poissonpdf",
+			Self::PoissonCdf => "poissoncdf",
+		}
+	}
+
+	/// How the function is called, used in error messages
+	pub(crate) const fn usage(self) -> &'static str {
+		match self {
+			Self::Gamma => "gamma(x)",
+			Self::Factorial => "factorial(x)",
+			Self::Erf => "erf(x)",
+			Self::Erfc => "erfc(x)",
+			Self::Beta => "beta(a, b)",
+			Self::BesselJ0 => "besselj0(x)",
+			Self::BesselJ1 => "besselj1(x)",
+			Self::Sec => "sec(x)",
+			Self::Csc => "csc(x)",
+			Self::Cot => "cot(x)",
+			Self::Asec => "asec(x)",
+			Self::Acsc => "acsc(x)",
+			Self::Acot => "acot(x)",
+			Self::Atan2 => "atan2(y, x)",
+			Self::Min => "min(a, b, ...)",
+			Self::Max => "max(a, b, ...)",
+			Self::Mod => "mod(a, b)",
+			Self::Combinations => "nCr(n, r)",
+			Self::Permutations => "nPr(n, r)",
+			Self::Log => "log(b, x)",
+			Self::Sigmoid => "sigmoid(x)",
+			Self::NormalPdf => "normalpdf(x) or normalpdf(x, m, s)",
+			Self::NormalCdf => "normalcdf(x) or normalcdf(x, m, s)",
+			Self::TPdf => "tpdf(x, v)",
+			Self::TCdf => "tcdf(x, v)",
+			Self::ChiSquaredPdf => "chisqpdf(x, k)",
+			Self::ChiSquaredCdf => "chisqcdf(x, k)",
+			Self::BinomialPdf => "binompdf(k, n, p)",
+			Self::BinomialCdf => "binomcdf(k, n, p)",
+			Self::PoissonPdf => "poissonpdf(k, l)",
+			Self::PoissonCdf => "poissoncdf(k, l)",
+		}
+	}
+
+	const fn description(self) -> &'static str {
+		match self {
+			Self::Gamma => "Gamma function",
+			Self::Factorial => "x!, extended to non-integers by the gamma function",
+			Self::Erf => "Error function",
+			Self::Erfc => "Complementary error function, 1 - erf(x)",
+			Self::Beta => "Beta function",
+			Self::BesselJ0 => "Bessel function of the first kind of order 0",
+			Self::BesselJ1 => "Bessel function of the first kind of order 1",
+			Self::Sec => "Secant of x, 1/cos(x)",
+			Self::Csc => "Cosecant of x, 1/sin(x)",
+			Self::Cot => "Cotangent of x, 1/tan(x)",
+			Self::Asec => "Inverse secant of x",
+			Self::Acsc => "Inverse cosecant of x",
+			Self::Acot => "Inverse cotangent of x",
+			Self::Atan2 => "Angle of the point (x, y) from the positive x axis",
+			Self::Min => "Smallest of the arguments",
+			Self::Max => "Largest of the arguments",
+			Self::Mod => "Remainder of a divided by b, which has the sign of b",
+			Self::Combinations => "Number of ways to choose r of n items",
+			Self::Permutations => "Number of ways to arrange r of n items",
+			Self::Log => "Logarithm of x in base b",
+			Self::Sigmoid => "Logistic function, 1/(1 + e^-x)",
+			Self::NormalPdf => "Density of the normal distribution with mean m and standard deviation s (0 and 1 if left out)",
+			Self::NormalCdf => "Probability of the normal distribution with mean m and standard deviation s (0 and 1 if left out) being at most x",
+			Self::TPdf => "Density of Student's t distribution with v degrees of freedom",
+			Self::TCdf => "Probability of Student's t distribution with v degrees of freedom being at most x",
+			Self::ChiSquaredPdf => "Density of the chi-squared distribution with k degrees of freedom",
+			Self::ChiSquaredCdf => "Probability of the chi-squared distribution with k degrees of freedom being at most x",
+			Self::BinomialPdf => "Probability of k successes in n trials that each succeed with probability p",
+			Self::BinomialCdf => "Probability of at most k successes in n trials that each succeed with probability p",
+			Self::PoissonPdf => "Probability of k events happening when l are expected",
+			Self::PoissonCdf => "Probability of at most k events happening when l are expected",
+		}
+	}
+
+	/// Whether the function can be called with `n` arguments
+	pub(crate) const fn takes_arguments(self, n: usize) -> bool {
+		match self {
+			Self::Beta
+			| Self::Atan2
+			| Self::Mod
+			| Self::Combinations
+			| Self::Permutations
+			| Self::Log
+			| Self::TPdf
+			| Self::TCdf
+			| Self::ChiSquaredPdf
+			| Self::ChiSquaredCdf
+			| Self::PoissonPdf
+			| Self::PoissonCdf => n == 2,
+			Self::BinomialPdf | Self::BinomialCdf => n == 3,
+			Self::NormalPdf | Self::NormalCdf => (n == 1) | (n == 3),
+			Self::Min | Self::Max => n >= 2,
+			_ => n == 1,
+		}
+	}
+
+	/// Evaluates the function with the arguments `args`, which has a valid length
+	pub(crate) fn eval(self, args: &[f64]) -> f64 {
+		let x = args[0];
+		match self {
+			Self::Gamma => gamma(x),
+			Self::Factorial => factorial(x),
+			Self::Erf => erf(x),
+			Self::Erfc => erfc(x),
+			Self::Beta => beta(x, args[1]),
+			Self::BesselJ0 => bessel_j(0, x),
+			Self::BesselJ1 => bessel_j(1, x),
+			Self::Sec => 1.0 / x.cos(),
+			Self::Csc => 1.0 / x.sin(),
+			Self::Cot => 1.0 / x.tan(),
+			Self::Asec => (1.0 / x).acos(),
+			Self::Acsc => (1.0 / x).asin(),
+			Self::Acot => (1.0 / x).atan(),
+			Self::Atan2 => x.atan2(args[1]),
+			Self::Min | Self::Max if args.iter().any(|arg| arg.is_nan()) => f64::NAN,
+			Self::Min => args.iter().copied().fold(f64::INFINITY, f64::min),
+			Self::Max => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+			Self::Mod => x - args[1] * (x / args[1]).floor(),
+			Self::Combinations => combinations(x, args[1]),
+			Self::Permutations => permutations(x, args[1]),
+			Self::Log => args[1].ln() / x.ln(),
+			Self::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+			Self::NormalPdf | Self::NormalCdf => {
+				// The standard normal distribution if the mean and standard deviation are left out
+				let (mean, sd) = match args {
+					[_, mean, sd] => (*mean, *sd),
+					_ => (0.0, 1.0),
+				};
+
+				match self {
+					Self::NormalPdf => normal_pdf(x, mean, sd),
+					_ => normal_cdf(x, mean, sd),
+				}
+			}
+			Self::TPdf => t_pdf(x, args[1]),
+			Self::TCdf => t_cdf(x, args[1]),
+			Self::ChiSquaredPdf => chi_squared_pdf(x, args[1]),
+			Self::ChiSquaredCdf => chi_squared_cdf(x, args[1]),
+			Self::BinomialPdf => binomial_pdf(x, args[1], args[2]),
+			Self::BinomialCdf => binomial_cdf(x, args[1], args[2]),
+			Self::PoissonPdf => poisson_pdf(x, args[1]),
+			Self::PoissonCdf => poisson_cdf(x, args[1]),
+		}
+	}
+}
+
+/// Returns true if `x` is a non-negative integer
+pub(crate) fn is_natural(x: f64) -> bool { (x >= 0.0) && (x.fract() == 0.0) }
+
+/// Sum of the Lanczos series and the `t` it is scaled by, for `x >= 0.5`
+fn lanczos(x: f64) -> (f64, f64) {
+	let x = x - 1.0;
+	let sum = LANCZOS_COEFFICIENTS[1..]
+		.iter()
+		.enumerate()
+		.fold(LANCZOS_COEFFICIENTS[0], |sum, (i, c)| {
+			sum + c / (x + (i as f64) + 1.0)
+		});
+	(sum, x + LANCZOS_G + 0.5)
+}
+
+/// The gamma function using the Lanczos approximation, undefined at non-positive integers
+fn gamma(x: f64) -> f64 {
+	if (x <= 0.0) && (x.fract() == 0.0) {
+		return f64::NAN;
+	}
+
+	// Reflection formula
+	if x < 0.5 {
+		return PI / ((PI * x).sin() * gamma(1.0 - x));
+	}
+
+	let (sum, t) = lanczos(x);
+	// Split `t^(x - 0.5)` so it doesn't overflow before being multiplied by `e^-t`
+	let power = t.powf((x - 0.5) / 2.0);
+	SQRT_TAU * power * (power * (-t).exp()) * sum
+}
+
+/// Natural logarithm of the gamma function for `x > 0`, which doesn't overflow like [`gamma`]
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+	if x < 0.5 {
+		return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+	}
+
+	let (sum, t) = lanczos(x);
+	(SQRT_TAU * sum).ln() + (x - 0.5) * t.ln() - t
+}
+
+/// `x!`, which is extended to non-integers with the gamma function
+fn factorial(x: f64) -> f64 {
+	if is_natural(x) && (x <= MAX_FACTORIAL) {
+		return (2..=(x as u32)).fold(1.0, |product, i| product * (i as f64));
+	}
+	gamma(x + 1.0)
+}
+
+fn beta(a: f64, b: f64) -> f64 {
+	if (a > 0.0) && (b > 0.0) {
+		(ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b)).exp()
+	} else {
+		gamma(a) * gamma(b) / gamma(a + b)
+	}
+}
+
+/// Power series of the error function, which is precise for small `|x|`
+fn erf_series(x: f64) -> f64 {
+	let mut sum = 0.0;
+	let mut term = x;
+	let mut n = 0.0;
+	loop {
+		let addend = term / (2.0 * n + 1.0);
+		sum += addend;
+		if addend.abs() <= f64::EPSILON * sum.abs() {
+			return FRAC_2_SQRT_PI * sum;
+		}
+		n += 1.0;
+		term *= -x * x / n;
+	}
+}
+
+/// Continued fraction of the complementary error function, which is precise for large `x > 0`
+fn erfc_fraction(x: f64) -> f64 {
+	let fraction = (1..=ERFC_FRACTION_TERMS)
+		.rev()
+		.fold(x, |fraction, k| x + (k as f64 / 2.0) / fraction);
+	(-x * x).exp() * FRAC_2_SQRT_PI / (2.0 * fraction)
+}
+
+fn erf(x: f64) -> f64 {
+	if x.abs() < ERF_SERIES_LIMIT {
+		erf_series(x)
+	} else {
+		x.signum() * (1.0 - erfc_fraction(x.abs()))
+	}
+}
+
+pub(crate) fn erfc(x: f64) -> f64 {
+	if x.abs() < ERF_SERIES_LIMIT {
+		1.0 - erf_series(x)
+	} else if x > 0.0 {
+		erfc_fraction(x)
+	} else {
+		2.0 - erfc_fraction(-x)
+	}
+}
+
+/// Bessel function of the first kind of order `n`
+fn bessel_j(n: i32, x: f64) -> f64 {
+	if x.is_nan() {
+		return f64::NAN;
+	}
+
+	// `J_n(-x) = (-1)^n J_n(x)`
+	if x < 0.0 {
+		return (-1.0_f64).powi(n) * bessel_j(n, -x);
+	}
+
+	if x < BESSEL_SERIES_LIMIT {
+		// Power series: the sum of `(-1)^k (x/2)^(2k + n) / (k! (k + n)!)`
+		let half = x / 2.0;
+		let mut term = half.powi(n) / factorial(n as f64);
+		let mut sum: f64 = 0.0;
+		let mut k = 0.0;
+		while term.abs() > f64::EPSILON * sum.abs() {
+			sum += term;
+			k += 1.0;
+			term *= -half * half / (k * (k + n as f64));
+		}
+		return sum;
+	}
+
+	// Hankel's asymptotic expansion, summed until its terms stop shrinking
+	let mu = 4.0 * (n * n) as f64;
+	let (mut p, mut q) = (0.0, 0.0);
+	let mut term: f64 = 1.0;
+	let mut k = 0;
+	loop {
+		match k % 4 {
+			0 => p += term,
+			1 => q += term,
+			2 => p -= term,
+			_ => q -= term,
+		}
+
+		k += 1;
+		let next = term * (mu - ((2 * k - 1) * (2 * k - 1)) as f64) / (k as f64 * 8.0 * x);
+		if (next.abs() >= term.abs()) | (next == 0.0) {
+			break;
+		}
+		term = next;
+	}
+
+	let chi = x - (n as f64 / 2.0 + 0.25) * PI;
+	(2.0 / (PI * x)).sqrt() * (p * chi.cos() - q * chi.sin())
+}
+
+/// Number of ways to choose `r` of `n` items, where both are non-negative integers
+fn combinations(n: f64, r: f64) -> f64 {
+	if !is_natural(n) | !is_natural(r) {
+		return f64::NAN;
+	}
+	if r > n {
+		return 0.0;
+	}
+
+	let r = r.min(n - r);
+	(1..=(r as u64))
+		.fold(1.0, |product, i| product * (n - r + i as f64) / i as f64)
+		.round()
+}
+
+/// Number of ways to arrange `r` of `n` items, where both are non-negative integers
+fn permutations(n: f64, r: f64) -> f64 {
+	if !is_natural(n) | !is_natural(r) {
+		return f64::NAN;
+	}
+	if r > n {
+		return 0.0;
+	}
+
+	(0..(r as u64)).fold(1.0, |product, i| product * (n - i as f64))
+}
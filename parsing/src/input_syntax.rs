@@ -0,0 +1,316 @@
+use crate::latex::{GREEK_LETTERS, LATEX_FUNCTIONS};
+use crate::SUPPORTED_FUNCTIONS;
+
+/// Inverse trigonometric functions as AsciiMath names them, along with the functions they are
+const ASCIIMATH_FUNCTIONS: [(&str, &str); 3] =
+	[("arcsin", "asin"), ("arccos", "acos"), ("arctan", "atan")];
+
+/// Returns true if `input` looks like LaTeX (such as `\frac{1}{2}x^{2}`) rather than the syntax
+/// expressions are written in
+pub fn is_latex(input: &str) -> bool { input.contains('\\') | input.contains('{') }
+
+/// Converts LaTeX into the syntax expressions are written in, such as `\frac{1}{2}x^{2}` into
+/// `((1)/(2))x^(2)`. Supports a practical subset of LaTeX: `\frac`, `\sqrt`, functions such as
+/// `\sin`, `\cdot`, `^{}`, `\pi`, and `\left( \right)`. Unsupported commands are left in, so they are
+/// reported as errors.
+pub fn latex_to_plain(input: &str) -> String { Reader::new(input).latex_until(None) }
+
+/// Converts AsciiMath into the syntax expressions are written in, such as `frac(1)(2) x^2 xx 3`
+/// into `((1)/(2)) x^2 * 3`. Supports `frac`, `root`, `xx`, `-:`, `log_b`, and functions called
+/// without parentheses (such as `sqrt x`). `log` on its own is base 10, as in AsciiMath.
+pub fn asciimath_to_plain(input: &str) -> String { Reader::new(input).asciimath_until(None) }
+
+/// Name of the logarithm with the base `base` (`None` for `log` on its own, which is base 10)
+fn log_name(base: Option<&str>) -> String {
+	match base.map(str::trim) {
+		None | Some("10") => "log10".to_owned(),
+		Some("2") => "log2".to_owned(),
+		Some("e") => "ln".to_owned(),
+		Some(base) => format!("(1/ln({}))ln", base),
+	}
+}
+
+/// Reads through an expression written in another syntax, converting it as it goes
+struct Reader<'a> {
+	input: &'a str,
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	const fn new(input: &'a str) -> Self { Self { input, position: 0 } }
+
+	fn rest(&self) -> &'a str { &self.input[self.position..] }
+
+	fn peek(&self) -> Option<char> { self.rest().chars().next() }
+
+	fn bump(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.position += c.len_utf8();
+		Some(c)
+	}
+
+	/// Reads `prefix` if the rest of the input starts with it
+	fn eat(&mut self, prefix: &str) -> bool {
+		let starts_with = self.rest().starts_with(prefix);
+		if starts_with {
+			self.position += prefix.len();
+		}
+		starts_with
+	}
+
+	fn skip_whitespace(&mut self) {
+		while self.peek().is_some_and(char::is_whitespace) {
+			self.bump();
+		}
+	}
+
+	/// Reads a run of letters, digits, and decimal points (such as `2x`), which is the argument of
+	/// a function called without parentheses
+	fn word(&mut self) -> &'a str {
+		let rest = self.rest();
+		let len = rest
+			.find(|c: char| !(c.is_ascii_alphanumeric() | (c == '.')))
+			.unwrap_or(rest.len());
+		self.position += len;
+		&rest[..len]
+	}
+
+	/// `name` written out, separated from a letter or digit following it so they aren't read as one
+	/// name
+	fn name(&self, name: &str) -> String {
+		match self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+			true => format!("{} ", name),
+			false => name.to_owned(),
+		}
+	}
+
+	/// Converts LaTeX until `end` (which is read) or the end of the input
+	fn latex_until(&mut self, end: Option<char>) -> String {
+		let mut result = String::new();
+		while let Some(c) = self.bump() {
+			match c {
+				c if Some(c) == end => break,
+				'\\' => {
+					let command = self.latex_command();
+					result.push_str(&command);
+				}
+				'{' => {
+					let group = self.latex_until(Some('}'));
+					result.push_str(&format!("({})", group));
+				}
+				'^' => {
+					let exponent = self.latex_argument();
+					result.push_str(&format!("^({})", exponent));
+				}
+				c => result.push(c),
+			}
+		}
+		result
+	}
+
+	/// Converts the argument of a LaTeX command: a `{}` group, or else a single character or command
+	/// (such as the `1` and `2` of `\frac12`)
+	fn latex_argument(&mut self) -> String {
+		self.skip_whitespace();
+		match self.bump() {
+			Some('{') => self.latex_until(Some('}')),
+			Some('\\') => self.latex_command(),
+			Some(c) => c.to_string(),
+			None => String::new(),
+		}
+	}
+
+	/// Converts the argument of a function called without parentheses (such as the `2x` of
+	/// `\sin 2x`) into a parenthesized one. Empty if parentheses follow, as they already are the
+	/// argument.
+	fn latex_function_argument(&mut self) -> String {
+		self.skip_whitespace();
+		let rest = self.rest();
+		if rest.starts_with('(') | rest.starts_with("\\left") {
+			return String::new();
+		}
+
+		if rest.starts_with('{') | rest.starts_with('\\') {
+			return format!("({})", self.latex_argument());
+		}
+
+		match self.word() {
+			"" => String::new(),
+			word => format!("({})", word),
+		}
+	}
+
+	/// Converts the delimiter following `\left` (or `\right`, if `left` is false)
+	fn latex_delimiter(&mut self, left: bool) -> &'static str {
+		self.skip_whitespace();
+		let delimiter = match self.bump() {
+			Some('\\') => self.command_name(),
+			Some(c) => &self.input[(self.position - c.len_utf8())..self.position],
+			None => "",
+		};
+
+		match (delimiter, left) {
+			("(" | "[" | "{" | "lbrace", _) => "(",
+			(")" | "]" | "}" | "rbrace", _) => ")",
+			// Absolute values, such as `\left| x \right|`
+			("|" | "vert", true) => "abs(",
+			("|" | "vert", false) => ")",
+			// `\left.` and `\right.` are invisible
+			_ => "",
+		}
+	}
+
+	/// Reads the name of a LaTeX command after its backslash: a run of letters, or a single other
+	/// character (such as the `,` of `\,`)
+	fn command_name(&mut self) -> &'a str {
+		let rest = self.rest();
+		let len = match rest.find(|c: char| !c.is_ascii_alphabetic()) {
+			Some(0) => rest.chars().next().map_or(0, char::len_utf8),
+			Some(len) => len,
+			None => rest.len(),
+		};
+		self.position += len;
+		&rest[..len]
+	}
+
+	/// Converts the LaTeX command whose backslash was just read
+	fn latex_command(&mut self) -> String {
+		let name = self.command_name();
+		match name {
+			"frac" | "dfrac" | "tfrac" => {
+				let numerator = self.latex_argument();
+				let denominator = self.latex_argument();
+				format!("(({})/({}))", numerator, denominator)
+			}
+			"sqrt" => {
+				self.skip_whitespace();
+				let index = self.eat("[").then(|| self.latex_until(Some(']')));
+				let radicand = self.latex_argument();
+				match index.as_deref().map(str::trim) {
+					None | Some("2") => format!("sqrt({})", radicand),
+					Some("3") => format!("cbrt({})", radicand),
+					Some(index) => format!("(({})^(1/({})))", radicand, index),
+				}
+			}
+			"left" => self.latex_delimiter(true).to_owned(),
+			"right" => self.latex_delimiter(false).to_owned(),
+			"cdot" | "times" | "ast" => "*".to_owned(),
+			"div" => "/".to_owned(),
+			"pi" => self.name("pi"),
+			"log" => {
+				let base = self.eat("_").then(|| self.latex_argument());
+				format!("{}{}", log_name(base.as_deref()), self.latex_function_argument())
+			}
+			"mathrm" | "operatorname" | "text" | "mathit" => {
+				let name = self.latex_argument();
+				match SUPPORTED_FUNCTIONS.contains(&name.as_str()) {
+					true => format!("{}{}", name, self.latex_function_argument()),
+					false => self.name(&name),
+				}
+			}
+			// Spacing
+			"," | ";" | ":" | "!" | " " | "quad" | "qquad" => " ".to_owned(),
+			name if GREEK_LETTERS.contains(&name) => self.name(name),
+			name => {
+				let function = LATEX_FUNCTIONS
+					.iter()
+					.find(|(_, command)| command.strip_prefix('\\') == Some(name));
+				match function {
+					Some((function, _)) => format!("{}{}", function, self.latex_function_argument()),
+					// Left in so it's reported as an error
+					None => format!("\\{}", name),
+				}
+			}
+		}
+	}
+
+	/// Converts AsciiMath until `end` (which is read) or the end of the input
+	fn asciimath_until(&mut self, end: Option<char>) -> String {
+		let mut result = String::new();
+		while let Some(c) = self.peek() {
+			if Some(c) == end {
+				self.bump();
+				break;
+			}
+
+			if self.eat("xx") | self.eat("**") | self.eat("cdot") {
+				result.push('*');
+			} else if self.eat("-:") {
+				result.push('/');
+			} else if self.eat("frac") {
+				let numerator = self.asciimath_group();
+				let denominator = self.asciimath_group();
+				result.push_str(&format!("(({})/({}))", numerator, denominator));
+			} else if self.eat("root") {
+				let index = self.asciimath_group();
+				let radicand = self.asciimath_group();
+				result.push_str(&format!("(({})^(1/({})))", radicand, index));
+			} else if let Some(function) = self.asciimath_function() {
+				match function {
+					"log" => {
+						let base = self.eat("_").then(|| self.asciimath_group());
+						result.push_str(&log_name(base.as_deref()));
+					}
+					function => result.push_str(function),
+				}
+				result.push_str(&self.asciimath_function_argument());
+			} else {
+				self.bump();
+				match c {
+					'(' | '[' | '{' => {
+						let close = match c {
+							'(' => ')',
+							'[' => ']',
+							_ => '}',
+						};
+						let group = self.asciimath_until(Some(close));
+						result.push_str(&format!("({})", group));
+					}
+					']' | '}' => result.push(')'),
+					c => result.push(c),
+				}
+			}
+		}
+		result
+	}
+
+	/// Reads the name of the function at the start of the rest of the input, returning the name of
+	/// the function it is
+	fn asciimath_function(&mut self) -> Option<&'static str> {
+		let rest = self.rest();
+		let (name, function) = ASCIIMATH_FUNCTIONS
+			.iter()
+			.copied()
+			.chain(SUPPORTED_FUNCTIONS.iter().map(|&function| (function, function)))
+			.filter(|(name, _)| rest.starts_with(name))
+			.max_by_key(|(name, _)| name.len())?;
+		self.position += name.len();
+		Some(function)
+	}
+
+	/// Converts the argument of an AsciiMath function like
+	/// [`Reader::latex_function_argument`], such as the `x` of `sqrt x`
+	fn asciimath_function_argument(&mut self) -> String {
+		self.skip_whitespace();
+		match self.peek() {
+			Some('(') | None => String::new(),
+			Some('[' | '{') => format!("({})", self.asciimath_group()),
+			Some(_) => match self.word() {
+				"" => String::new(),
+				word => format!("({})", word),
+			},
+		}
+	}
+
+	/// Converts an AsciiMath group such as `(x+1)`, or else a single character
+	fn asciimath_group(&mut self) -> String {
+		self.skip_whitespace();
+		match self.bump() {
+			Some('(') => self.asciimath_until(Some(')')),
+			Some('[') => self.asciimath_until(Some(']')),
+			Some('{') => self.asciimath_until(Some('}')),
+			Some(c) => c.to_string(),
+			None => String::new(),
+		}
+	}
+}
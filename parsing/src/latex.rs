@@ -0,0 +1,180 @@
+use crate::ast::{BinaryOp, Constant, Expr};
+
+/// Functions written as a LaTeX command instead of by name, such as `\arcsin` for `asin`
+pub(crate) const LATEX_FUNCTIONS: [(&str, &str); 19] = [
+	("sin", "\\sin"),
+	("cos", "\\cos"),
+	("tan", "\\tan"),
+	("asin", "\\arcsin"),
+	("acos", "\\arccos"),
+	("atan", "\\arctan"),
+	("sinh", "\\sinh"),
+	("cosh", "\\cosh"),
+	("tanh", "\\tanh"),
+	("sec", "\\sec"),
+	("csc", "\\csc"),
+	("cot", "\\cot"),
+	("exp", "\\exp"),
+	("ln", "\\ln"),
+	("log2", "\\log_{2}"),
+	("log10", "\\log_{10}"),
+	("min", "\\min"),
+	("max", "\\max"),
+	("gamma", "\\Gamma"),
+];
+
+/// Names of greek letters, which LaTeX writes as symbols
+pub(crate) const GREEK_LETTERS: [&str; 16] = [
+	"alpha", "beta", "gamma", "delta", "epsilon", "theta", "lambda", "mu", "rho", "sigma", "tau",
+	"phi", "psi", "omega", "Delta", "Omega",
+];
+
+/// LaTeX of a variable, writing multi-letter names upright
+fn variable_latex(name: &str) -> String {
+	if name.chars().count() == 1 {
+		name.to_owned()
+	} else if GREEK_LETTERS.contains(&name) {
+		format!("\\{}", name)
+	} else {
+		format!("\\mathrm{{{}}}", name)
+	}
+}
+
+/// LaTeX of `expr`, wrapped in parentheses if `parens` is true
+fn grouped(expr: &Expr, parens: bool) -> String {
+	match parens {
+		true => format!("\\left({}\\right)", expr.to_latex()),
+		false => expr.to_latex(),
+	}
+}
+
+/// Returns true if `expr` is negated, which needs parentheses after another operator (such as in
+/// `2 \cdot (-x)`)
+const fn is_negated(expr: &Expr) -> bool { matches!(expr, Expr::Negate(_)) }
+
+/// Returns true if `expr` is a power, such as `x^{2}`
+const fn is_power(expr: &Expr) -> bool {
+	matches!(
+		expr,
+		Expr::Binary {
+			op: BinaryOp::Pow,
+			..
+		}
+	)
+}
+
+/// Number of `deriv` calls with respect to `variable` nested around an expression (such as 2 in
+/// `deriv(deriv(f, x), x)`), along with that expression
+fn nested_derivatives<'a>(args: &'a [Expr], variable: &Expr) -> (usize, &'a Expr) {
+	match args {
+		[Expr::Call { name, args: inner }, v] if (name == "deriv") && (v == variable) => {
+			match inner.as_slice() {
+				[_, inner_variable] if inner_variable == variable => {
+					let (order, expr) = nested_derivatives(inner, variable);
+					(order + 1, expr)
+				}
+				_ => (1, &args[0]),
+			}
+		}
+		_ => (1, &args[0]),
+	}
+}
+
+/// LaTeX of a call to `name` with `args`
+fn call_latex(name: &str, args: &[Expr]) -> String {
+	let arg = |i: usize| args[i].to_latex();
+	match (name, args.len()) {
+		("sqrt", 1) => format!("\\sqrt{{{}}}", arg(0)),
+		("cbrt", 1) => format!("\\sqrt[3]{{{}}}", arg(0)),
+		("abs", 1) => format!("\\left|{}\\right|", arg(0)),
+		("floor", 1) => format!("\\left\\lfloor {}\\right\\rfloor", arg(0)),
+		("ceil", 1) => format!("\\left\\lceil {}\\right\\rceil", arg(0)),
+		("factorial", 1) => format!("{}!", grouped(&args[0], !args[0].is_atom())),
+		("nCr", 2) => format!("\\binom{{{}}}{{{}}}", arg(0), arg(1)),
+		("log", 2) => format!("\\log_{{{}}}\\left({}\\right)", arg(0), arg(1)),
+		("integral", 4) => format!(
+			"\\int_{{{}}}^{{{}}} {} \\, d{}",
+			arg(2),
+			arg(3),
+			grouped(&args[0], args[0].is_sum()),
+			arg(1)
+		),
+		("sum", 4) => format!(
+			"\\sum_{{{}={}}}^{{{}}} {}",
+			arg(1),
+			arg(2),
+			arg(3),
+			grouped(&args[0], args[0].is_sum())
+		),
+		("deriv", 2) => {
+			let (order, expr) = nested_derivatives(args, &args[1]);
+			let operator = match order {
+				1 => format!("\\frac{{d}}{{d{}}}", arg(1)),
+				_ => format!("\\frac{{d^{{{}}}}}{{d{}^{{{}}}}}", order, arg(1), order),
+			};
+			format!("{} {}", operator, grouped(expr, !expr.is_atom()))
+		}
+		("deriv", 3) => format!(
+			"\\left.\\frac{{d}}{{d{}}} {}\\right|_{{{}={}}}",
+			arg(1),
+			grouped(&args[0], !args[0].is_atom()),
+			arg(1),
+			arg(2)
+		),
+		_ => {
+			let name = match LATEX_FUNCTIONS.iter().find(|(function, _)| *function == name) {
+				Some((_, command)) => command.to_string(),
+				None if name.chars().count() == 1 => name.to_owned(),
+				None => format!("\\operatorname{{{}}}", name),
+			};
+			let args: Vec<String> = args.iter().map(Expr::to_latex).collect();
+			format!("{}\\left({}\\right)", name, args.join(", "))
+		}
+	}
+}
+
+impl Expr {
+	/// Writes the expression as LaTeX, such as `\frac{x^{2}}{2}` for `x^2/2`
+	pub fn to_latex(&self) -> String {
+		match self {
+			Expr::Number(n) if n.is_infinite() => match n.is_sign_positive() {
+				true => "\\infty".to_owned(),
+				false => "-\\infty".to_owned(),
+			},
+			Expr::Number(n) => n.to_string(),
+			Expr::Constant(Constant::Pi) => "\\pi".to_owned(),
+			Expr::Constant(Constant::E) => "e".to_owned(),
+			Expr::Variable(name) => variable_latex(name),
+			// Negated powers are grouped, as `-x^{2}` is read back as `(-x)^2`
+			Expr::Negate(inner) => {
+				let parens = inner.is_sum() | is_negated(inner) | is_power(inner);
+				format!("-{}", grouped(inner, parens))
+			}
+			Expr::Binary { op, lhs, rhs } => match op {
+				BinaryOp::Add => format!("{} + {}", lhs.to_latex(), grouped(rhs, is_negated(rhs))),
+				BinaryOp::Sub => format!(
+					"{} - {}",
+					lhs.to_latex(),
+					grouped(rhs, rhs.is_sum() | is_negated(rhs))
+				),
+				BinaryOp::Mul => {
+					let rhs_parens = rhs.is_sum() | is_negated(rhs);
+					// Numbers need a multiplication sign to not run into what they're multiplied by
+					let separator = match !rhs_parens && rhs.starts_with_number() {
+						true => " \\cdot ",
+						false => " ",
+					};
+					format!(
+						"{}{}{}",
+						grouped(lhs, lhs.is_sum()),
+						separator,
+						grouped(rhs, rhs_parens)
+					)
+				}
+				BinaryOp::Div => format!("\\frac{{{}}}{{{}}}", lhs.to_latex(), rhs.to_latex()),
+				BinaryOp::Pow => format!("{}^{{{}}}", grouped(lhs, !lhs.is_atom()), rhs.to_latex()),
+			},
+			Expr::Call { name, args } => call_latex(name, args),
+		}
+	}
+}
@@ -0,0 +1,212 @@
+use crate::SUPPORTED_FUNCTIONS;
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// What a [`Token`] is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TokenKind {
+	/// A number such as `2`, `0.5`, or `6.674e-11`
+	Number,
+
+	/// A name such as `sin`, `k`, or `tau`
+	Identifier,
+
+	/// `x` or `e` (in either case), which are split off of the letters around them so `xx` and `2ex`
+	/// are products
+	Variable,
+
+	/// `pi` or `π`
+	Pi,
+
+	/// `+`
+	Plus,
+
+	/// `-` or `−`
+	Minus,
+
+	/// `*`, `×`, `·`, `⋅`, or `∗`
+	Star,
+
+	/// `/`, `÷`, or `∕`
+	Slash,
+
+	/// `^` or `**`
+	Caret,
+
+	OpenParen,
+	CloseParen,
+	Comma,
+
+	/// A character that can't be part of an expression
+	Unknown,
+}
+
+impl TokenKind {
+	/// Returns true if a new term starts after this token, such as after `+` or `(`
+	pub(crate) const fn separates_terms(self) -> bool {
+		matches!(
+			self,
+			Self::Plus | Self::Minus | Self::Slash | Self::Caret | Self::OpenParen | Self::Comma
+		)
+	}
+}
+
+/// A piece of an expression along with the bytes of the expression it was read from
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Token {
+	pub(crate) kind: TokenKind,
+	pub(crate) span: Range<usize>,
+}
+
+impl Token {
+	/// Text of the token in `input`, the string it was read from
+	pub(crate) fn text<'a>(&self, input: &'a str) -> &'a str { &input[self.span.clone()] }
+
+	/// Text of the token in the form exmex expects: `π` for `pi`, `^` for `**`, ascii operators for
+	/// unicode ones, and numbers in scientific notation (such as `6.674e-11`) as `6.674*10^(-11)` so
+	/// the `e` isn't mistaken for euler's number
+	pub(crate) fn normalized<'a>(&self, input: &'a str) -> Cow<'a, str> {
+		let text = self.text(input);
+		match self.kind {
+			TokenKind::Pi => Cow::Borrowed("π"),
+			TokenKind::Minus => Cow::Borrowed("-"),
+			TokenKind::Star => Cow::Borrowed("*"),
+			TokenKind::Slash => Cow::Borrowed("/"),
+			TokenKind::Caret => Cow::Borrowed("^"),
+			TokenKind::Number => match text.split_once(['e', 'E']) {
+				Some((mantissa, exponent)) => Cow::Owned(format!("{}*10^({})", mantissa, exponent)),
+				None => Cow::Borrowed(text),
+			},
+			_ => Cow::Borrowed(text),
+		}
+	}
+
+	/// Returns true if the token can end an operand, such as `2` or `)` in `2x` and `(x+1)x`
+	const fn ends_operand(&self) -> bool {
+		matches!(
+			self.kind,
+			TokenKind::Number
+				| TokenKind::Identifier
+				| TokenKind::Variable
+				| TokenKind::Pi
+				| TokenKind::CloseParen
+		)
+	}
+}
+
+/// Returns true if the adjacent tokens `a` and `b` are implicitly multiplied, such as in `2x`,
+/// `x(2)`, and `)(`. Parentheses following an identifier are a call rather than a product.
+pub(crate) fn implicit_multiplication(a: &Token, b: &Token) -> bool {
+	let begins_operand = match b.kind {
+		TokenKind::Identifier | TokenKind::Variable | TokenKind::Pi => true,
+		// `2 3` is an error rather than `6`
+		TokenKind::Number => a.kind != TokenKind::Number,
+		TokenKind::OpenParen => a.kind != TokenKind::Identifier,
+		_ => false,
+	};
+	a.ends_operand() && begins_operand
+}
+
+/// Returns true if `c` can start a name
+#[inline]
+const fn is_name_start(c: char) -> bool { c.is_ascii_alphabetic() | (c == '_') }
+
+/// Returns true if `c` can be part of a name
+#[inline]
+const fn is_name_char(c: char) -> bool { c.is_ascii_alphanumeric() | (c == '_') }
+
+/// Length in bytes of the number at the start of `input`, including an exponent such as the `e-11`
+/// in `6.674e-11`
+fn number_len(input: &str) -> usize {
+	let mantissa = input
+		.find(|c: char| !(c.is_ascii_digit() | (c == '.')))
+		.unwrap_or(input.len());
+
+	let rest = &input[mantissa..];
+	let Some(exponent) = rest.strip_prefix(['e', 'E']) else {
+		return mantissa;
+	};
+	let unsigned = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+	let digits = unsigned
+		.find(|c: char| !c.is_ascii_digit())
+		.unwrap_or(unsigned.len());
+
+	// `2e` and `2ex` are `2` multiplied by euler's number
+	match digits {
+		0 => mantissa,
+		_ => input.len() - unsigned.len() + digits,
+	}
+}
+
+/// Splits the run of name characters `input[start..end]` into tokens. `pi`, `x`, and `e` are split
+/// off of the letters around them unless they begin a supported function (such as `exp`), so `xsin`
+/// is `x` followed by `sin` and `emax` is `e` followed by `max`.
+fn split_name(input: &str, start: usize, end: usize, tokens: &mut Vec<Token>) {
+	let mut i = start;
+	while i < end {
+		let rest = &input[i..end];
+		let (kind, len) = if rest.starts_with("pi") {
+			(TokenKind::Pi, 2)
+		} else if rest.starts_with(['x', 'X', 'e', 'E'])
+			&& !SUPPORTED_FUNCTIONS.iter().any(|function| rest.starts_with(function))
+		{
+			(TokenKind::Variable, 1)
+		} else if rest.starts_with(|c: char| c.is_ascii_digit()) && i != start {
+			// Digits following a variable, such as in `x2`
+			let digits = rest
+				.find(|c: char| !c.is_ascii_digit())
+				.unwrap_or(rest.len());
+			(TokenKind::Number, digits)
+		} else {
+			(TokenKind::Identifier, rest.len())
+		};
+
+		tokens.push(Token {
+			kind,
+			span: i..(i + len),
+		});
+		i += len;
+	}
+}
+
+/// Splits `input` into tokens, skipping whitespace
+pub(crate) fn tokenize(input: &str) -> Vec<Token> {
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	while let Some(c) = input[i..].chars().next() {
+		let rest = &input[i..];
+
+		if is_name_start(c) {
+			let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+			split_name(input, i, i + len, &mut tokens);
+			i += len;
+			continue;
+		}
+
+		let (kind, len) = match c {
+			c if c.is_whitespace() => {
+				i += c.len_utf8();
+				continue;
+			}
+			c if c.is_ascii_digit() | (c == '.') => (TokenKind::Number, number_len(rest)),
+			'*' if rest.starts_with("**") => (TokenKind::Caret, 2),
+			'π' => (TokenKind::Pi, c.len_utf8()),
+			'+' => (TokenKind::Plus, 1),
+			'-' | '−' => (TokenKind::Minus, c.len_utf8()),
+			'*' | '×' | '·' | '⋅' | '∗' => (TokenKind::Star, c.len_utf8()),
+			'/' | '÷' | '∕' => (TokenKind::Slash, c.len_utf8()),
+			'^' => (TokenKind::Caret, 1),
+			'(' => (TokenKind::OpenParen, 1),
+			')' => (TokenKind::CloseParen, 1),
+			',' => (TokenKind::Comma, 1),
+			c => (TokenKind::Unknown, c.len_utf8()),
+		};
+
+		tokens.push(Token {
+			kind,
+			span: i..(i + len),
+		});
+		i += len;
+	}
+	tokens
+}
@@ -1,13 +1,37 @@
+mod algebra;
+mod ast;
 mod autocomplete;
 mod autocomplete_hashmap;
+mod complex;
+mod distributions;
+mod error;
+mod format;
+mod functions;
+mod input_syntax;
+mod latex;
+mod lexer;
+mod operators;
 mod parsing;
+mod polynomial;
 mod splitting;
 mod suggestions;
+mod symbols;
 
 pub use crate::{
-	autocomplete::{AutoComplete, Movement},
+	algebra::Rewrite,
+	ast::{parse_expression, BinaryOp, Constant, Expr},
+	autocomplete::{AutoComplete, Completion, CompletionContext, Movement},
 	autocomplete_hashmap::compile_hashmap,
-	parsing::{process_func_str, BackingFunction, FlatExWrapper},
+	complex::{Complex, ComplexFunction, ZeroOrPole},
+	error::{FixIt, ParseError, ParseErrorKind},
+	format::format_expression,
+	functions::{function_docs, FunctionDoc},
+	input_syntax::{asciimath_to_plain, is_latex, latex_to_plain},
+	parsing::{
+		numeric_derivative, process_func_str, BackingFunction, FlatExWrapper, MAX_DERIVATIVE,
+	},
+	polynomial::{Polynomial, Root},
 	splitting::{split_function, split_function_chars, SplitType},
 	suggestions::{generate_hint, get_last_term, Hint, HINT_EMPTY, SUPPORTED_FUNCTIONS},
+	symbols::{parse_definition, process_definition, referenced_names, Definition, SymbolTable},
 };
@@ -0,0 +1,434 @@
+use std::ops::Range;
+
+use crate::error::{span_within, ParseError, ParseErrorKind};
+use crate::functions::Function;
+use crate::parsing::{numeric_derivative, FlatExWrapper};
+use crate::symbols::{identifiers, is_identifier, matching_paren};
+use crate::SUPPORTED_FUNCTIONS;
+
+/// Prefix of the variables operator calls are replaced with before the expression is handed to exmex
+pub(crate) const PLACEHOLDER: &str = "__op";
+
+/// Number of intervals Simpson's rule splits an integral into
+const INTEGRAL_INTERVALS: usize = 128;
+
+/// Most terms a sum can have before it is considered undefined, as every term is evaluated at every
+/// point the function is sampled at
+const MAX_SUM_TERMS: f64 = 10_000.0;
+
+/// Functions that take an expression along with the variable it's in terms of, which are evaluated
+/// numerically
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum OperatorKind {
+	/// `integral(f, t, a, b)`: definite integral of `f` with respect to `t` from `a` to `b`
+	Integral,
+
+	/// `deriv(f, t)` or `deriv(f, t, a)`: derivative of `f` with respect to `t` at `a` (or at `x`)
+	Derivative,
+
+	/// `sum(f, n, a, b)`: sum of `f` for every integer `n` from `a` to `b`
+	Sum,
+}
+
+impl OperatorKind {
+	pub(crate) const ALL: [OperatorKind; 3] = [Self::Integral, Self::Derivative, Self::Sum];
+
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"integral" => Some(Self::Integral),
+			"deriv" => Some(Self::Derivative),
+			"sum" => Some(Self::Sum),
+			_ => None,
+		}
+	}
+
+	pub(crate) const fn name(self) -> &'static str {
+		match self {
+			Self::Integral => "integral",
+			Self::Derivative => "deriv",
+			Self::Sum => "sum",
+		}
+	}
+
+	/// How the operator is called, used in error messages
+	pub(crate) const fn usage(self) -> &'static str {
+		match self {
+			Self::Integral => "integral(f, t, a, b)",
+			Self::Derivative => "deriv(f, t) or deriv(f, t, a)",
+			Self::Sum => "sum(f, n, a, b)",
+		}
+	}
+
+	pub(crate) const fn description(self) -> &'static str {
+		match self {
+			Self::Integral => "Definite integral of f with respect to t from a to b",
+			Self::Derivative => "Derivative of f with respect to t at a (or at x)",
+			Self::Sum => "Sum of f for every integer n from a to b",
+		}
+	}
+
+	/// Whether the operator can be called with `n` arguments
+	const fn takes_arguments(self, n: usize) -> bool {
+		match self {
+			Self::Integral | Self::Sum => n == 4,
+			Self::Derivative => (n == 2) | (n == 3),
+		}
+	}
+}
+
+/// Splits the arguments of a call on the commas that aren't nested inside of parentheses
+pub(crate) fn split_arguments(arguments: &str) -> Vec<&str> {
+	let mut result = Vec::new();
+	let mut depth: usize = 0;
+	let mut start = 0;
+	for (i, c) in arguments.char_indices() {
+		match c {
+			'(' => depth += 1,
+			')' => depth = depth.saturating_sub(1),
+			',' if depth == 0 => {
+				result.push(&arguments[start..i]);
+				start = i + 1;
+			}
+			_ => {}
+		}
+	}
+	result.push(&arguments[start..]);
+	result
+}
+
+/// If `expr[start..end]` is the name of an operator that is being called, returns the index of the
+/// parenthesis closing the call along with its arguments
+pub(crate) fn operator_call(expr: &str, start: usize, end: usize) -> Option<(usize, Vec<&str>)> {
+	OperatorKind::from_name(&expr[start..end])?;
+	if !expr[end..].starts_with('(') {
+		return None;
+	}
+
+	let close = matching_paren(expr, end + 1)?;
+	Some((close, split_arguments(&expr[(end + 1)..close])))
+}
+
+/// Integrates `f` from `a` to `b` using Simpson's rule
+fn simpson(mut f: impl FnMut(f64) -> f64, a: f64, b: f64) -> f64 {
+	if !a.is_finite() | !b.is_finite() {
+		return f64::NAN;
+	}
+
+	let h = (b - a) / (INTEGRAL_INTERVALS as f64);
+	let inner: f64 = (1..INTEGRAL_INTERVALS)
+		.map(|i| {
+			let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+			weight * f(a + (i as f64) * h)
+		})
+		.sum();
+
+	(f(a) + inner + f(b)) * h / 3.0
+}
+
+/// Sums `f` over every integer from `a` to `b` (both rounded)
+fn sum(mut f: impl FnMut(f64) -> f64, a: f64, b: f64) -> f64 {
+	let (a, b) = (a.round(), b.round());
+	if !a.is_finite() | !b.is_finite() | (b - a >= MAX_SUM_TERMS) {
+		return f64::NAN;
+	}
+
+	(a as i64..=b as i64).map(|n| f(n as f64)).sum()
+}
+
+/// Anything that is evaluated numerically instead of by exmex
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Callee {
+	Operator(OperatorKind),
+	Function(Function),
+}
+
+impl Callee {
+	fn from_name(name: &str) -> Option<Self> {
+		OperatorKind::from_name(name)
+			.map(Self::Operator)
+			.or_else(|| Function::from_name(name).map(Self::Function))
+	}
+
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Operator(kind) => kind.name(),
+			Self::Function(function) => function.name(),
+		}
+	}
+
+	const fn usage(self) -> &'static str {
+		match self {
+			Self::Operator(kind) => kind.usage(),
+			Self::Function(function) => function.usage(),
+		}
+	}
+
+	const fn takes_arguments(self, n: usize) -> bool {
+		match self {
+			Self::Operator(kind) => kind.takes_arguments(n),
+			Self::Function(function) => function.takes_arguments(n),
+		}
+	}
+}
+
+/// A call that exmex can't evaluate, such as `integral(t^2, t, 0, x)` or `gamma(x)`
+#[derive(Clone, PartialEq)]
+pub(crate) enum Operator {
+	/// An operator with a variable of its own
+	Bound {
+		kind: OperatorKind,
+
+		/// Expression the operator is applied to, boxed as parsed expressions are large
+		body: Box<FlatExWrapper>,
+
+		/// Variable `body` is in terms of
+		variable: String,
+
+		/// Bounds of integrals and sums, or the point derivatives are taken at
+		bounds: Vec<FlatExWrapper>,
+	},
+
+	/// A built-in function
+	Call {
+		function: Function,
+		arguments: Vec<FlatExWrapper>,
+	},
+}
+
+/// Parses `argument`, a substring of `expr`, moving the spans of errors so they are into `expr`
+fn parse_argument(
+	expr: &str, argument: &str, variables: &[&str],
+) -> Result<FlatExWrapper, ParseError> {
+	FlatExWrapper::parse(argument, variables)
+		.map_err(|error| error.offset(span_within(expr, argument).start))
+}
+
+impl Operator {
+	/// Parses the call `expr[call]` to `callee` with the raw `arguments` (substrings of `expr`), where
+	/// `variables` are the variables that are defined outside of the call
+	fn parse(
+		callee: Callee, expr: &str, call: Range<usize>, arguments: &[&str], variables: &[&str],
+	) -> Result<Self, ParseError> {
+		let name = callee.name();
+		if !callee.takes_arguments(arguments.len()) {
+			return Err(ParseError::new(ParseErrorKind::WrongArgumentCount {
+				function: name.to_owned(),
+				usage: callee.usage().to_owned(),
+			})
+			.at(call));
+		}
+
+		if arguments.iter().any(|argument| argument.trim().is_empty()) {
+			return Err(ParseError::new(ParseErrorKind::EmptyArgument {
+				function: name.to_owned(),
+				usage: callee.usage().to_owned(),
+			})
+			.at(call));
+		}
+
+		let kind = match callee {
+			Callee::Operator(kind) => kind,
+			Callee::Function(function) => {
+				return Ok(Self::Call {
+					function,
+					arguments: arguments
+						.iter()
+						.map(|argument| parse_argument(expr, argument, variables))
+						.collect::<Result<Vec<FlatExWrapper>, ParseError>>()?,
+				});
+			}
+		};
+
+		let variable = arguments[1].trim();
+		if !is_identifier(variable)
+			| matches!(variable, "e" | "E" | "pi")
+			| SUPPORTED_FUNCTIONS.contains(&variable)
+		{
+			return Err(ParseError::new(ParseErrorKind::InvalidOperatorVariable {
+				operator: name.to_owned(),
+				variable: variable.to_owned(),
+			})
+			.at(span_within(expr, variable)));
+		}
+
+		let mut body_variables = variables.to_vec();
+		body_variables.push(variable);
+		let body = parse_argument(expr, arguments[0], &body_variables)?;
+
+		let mut bounds = arguments[2..]
+			.iter()
+			.map(|bound| parse_argument(expr, bound, variables))
+			.collect::<Result<Vec<FlatExWrapper>, ParseError>>()?;
+
+		// `deriv(f, t)` is taken at `x`
+		if bounds.is_empty() {
+			bounds.push(FlatExWrapper::parse("x", variables).map_err(|error| error.at(call))?);
+		}
+
+		Ok(Self::Bound {
+			kind,
+			body: Box::new(body),
+			variable: variable.to_owned(),
+			bounds,
+		})
+	}
+
+	/// Returns true if the operator's value depends on the variable `name`
+	pub(crate) fn depends_on(&self, name: &str) -> bool {
+		match self {
+			Self::Bound {
+				body,
+				variable,
+				bounds,
+				..
+			} => {
+				bounds.iter().any(|bound| bound.depends_on(name))
+					| ((variable != name) && body.depends_on(name))
+			}
+			Self::Call { arguments, .. } => {
+				arguments.iter().any(|argument| argument.depends_on(name))
+			}
+		}
+	}
+
+	/// Evaluates the operator where the variables outside of it have the values in `env`
+	pub(crate) fn eval(&self, env: &[(&str, f64)]) -> f64 {
+		let (kind, body, variable, bounds) = match self {
+			Self::Bound {
+				kind,
+				body,
+				variable,
+				bounds,
+			} => (kind, body, variable, bounds),
+			Self::Call {
+				function,
+				arguments,
+			} => {
+				let values: Vec<f64> = arguments
+					.iter()
+					.map(|argument| argument.eval_in(env))
+					.collect();
+				return function.eval(&values);
+			}
+		};
+
+		let bounds: Vec<f64> = bounds.iter().map(|bound| bound.eval_in(env)).collect();
+
+		let mut env = env.to_vec();
+		env.push((variable, 0.0));
+		let last = env.len() - 1;
+		let f = |value: f64| {
+			env[last].1 = value;
+			body.eval_in(&env)
+		};
+
+		match kind {
+			OperatorKind::Integral => simpson(f, bounds[0], bounds[1]),
+			OperatorKind::Derivative => numeric_derivative(f, 1, bounds[0]),
+			OperatorKind::Sum => sum(f, bounds[0], bounds[1]),
+		}
+	}
+}
+
+/// A string whose operators have been replaced with placeholder variables by [`extract_operators`]
+pub(crate) struct Extracted {
+	pub(crate) func_str: String,
+
+	/// Operators in the order of their placeholders
+	pub(crate) operators: Vec<Operator>,
+
+	/// Byte range of the original string each operator was called in
+	pub(crate) calls: Vec<Range<usize>>,
+}
+
+/// Replaces every call that exmex can't evaluate in `func_str` with a placeholder variable
+pub(crate) fn extract_operators(
+	func_str: &str, variables: &[&str],
+) -> Result<Extracted, ParseError> {
+	let mut result = String::with_capacity(func_str.len());
+	let mut operators = Vec::new();
+	let mut calls = Vec::new();
+	let mut last = 0;
+	for (s, e) in identifiers(func_str) {
+		// Skip identifiers that are inside of an already extracted call
+		if s < last {
+			continue;
+		}
+
+		let Some(callee) = Callee::from_name(&func_str[s..e]) else {
+			continue;
+		};
+
+		if !func_str[e..].starts_with('(') {
+			let error = ParseError::new(ParseErrorKind::NotCalled {
+				function: callee.name().to_owned(),
+				usage: callee.usage().to_owned(),
+			})
+			.at(s..e);
+
+			// Suggest calling functions of one variable with `x`
+			let call = format!("{}(x)", callee.name());
+			return Err(match callee.usage() == call {
+				true => error.with_fix(e..e, "(x)"),
+				false => error,
+			});
+		}
+
+		let Some(close) = matching_paren(func_str, e + 1) else {
+			return Err(ParseError::new(ParseErrorKind::UnclosedCall(callee.name().to_owned()))
+				.at(s..e)
+				.with_fix(func_str.len()..func_str.len(), ")"));
+		};
+		let arguments = split_arguments(&func_str[(e + 1)..close]);
+
+		// exmex handles `log(x)` itself
+		if (callee == Callee::Function(Function::Log)) && (arguments.len() == 1) {
+			continue;
+		}
+
+		let call = s..(close + 1);
+		operators.push(Operator::parse(
+			callee,
+			func_str,
+			call.clone(),
+			&arguments,
+			variables,
+		)?);
+		calls.push(call);
+
+		result.push_str(&func_str[last..s]);
+		result.push_str(&format!("{}{}", PLACEHOLDER, operators.len() - 1));
+		last = close + 1;
+	}
+	result.push_str(&func_str[last..]);
+
+	Ok(Extracted {
+		func_str: result,
+		operators,
+		calls,
+	})
+}
+
+/// Converts `span` of bytes in the string returned by [`extract_operators`] into bytes of the
+/// string operators were extracted from, where `calls` are the byte ranges of the operators' calls
+pub(crate) fn original_span(span: Range<usize>, calls: &[Range<usize>]) -> Range<usize> {
+	let original = |position: usize, is_end: bool| {
+		// How much longer the calls so far are than the placeholders that replaced them
+		let mut delta: isize = 0;
+		for (i, call) in calls.iter().enumerate() {
+			let start = (call.start as isize - delta) as usize;
+			let end = start + PLACEHOLDER.len() + i.to_string().len();
+
+			// Positions inside of a placeholder cover the whole call
+			match is_end {
+				false if position < start => break,
+				false if position < end => return call.start,
+				true if position <= start => break,
+				true if position <= end => return call.end,
+				_ => delta += call.len() as isize - (end - start) as isize,
+			}
+		}
+		(position as isize + delta) as usize
+	};
+	original(span.start, false)..original(span.end, true)
+}
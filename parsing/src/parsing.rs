@@ -1,16 +1,63 @@
+use crate::ast::{parse_expression, unknown_call, Expr};
+use crate::error::{ParseError, ParseErrorKind};
+use crate::operators::{extract_operators, original_span, Extracted, Operator, PLACEHOLDER};
+use crate::symbols::identifiers;
 use exmex::prelude::*;
 use std::collections::HashMap;
+use std::ops::Range;
+
+/// Highest derivative that is calculated symbolically, higher ones are approximated numerically
+/// from it as symbolic derivatives grow very quickly
+const MAX_SYMBOLIC_DERIVATIVE: usize = 10;
+
+/// Highest derivative that can be displayed. Past [`MAX_SYMBOLIC_DERIVATIVE`] derivatives are
+/// finite differences, which take longer and are mostly rounding error at higher orders.
+pub const MAX_DERIVATIVE: usize = MAX_SYMBOLIC_DERIVATIVE + 4;
+
+/// Approximates the `order`th derivative of `f` at `x` with a central finite difference
+pub fn numeric_derivative(mut f: impl FnMut(f64) -> f64, order: usize, x: f64) -> f64 {
+	if order == 0 {
+		return f(x);
+	}
+
+	// Higher orders divide by higher powers of `h`, so they need a larger step to limit rounding error
+	let h = f64::EPSILON.powf(1.0 / (order as f64 + 2.0)) * x.abs().max(1.0);
+
+	let mut binomial = 1.0;
+	let mut sum = 0.0;
+	for k in 0..=order {
+		let term = binomial * f(x + ((order as f64 / 2.0) - k as f64) * h);
+		if k % 2 == 0 {
+			sum += term;
+		} else {
+			sum -= term;
+		}
+		binomial *= (order - k) as f64 / (k + 1) as f64;
+	}
+
+	sum / h.powi(order as i32)
+}
 
 #[derive(Clone, PartialEq)]
 pub struct FlatExWrapper {
 	func: Option<FlatEx<f64>>,
 	func_str: Option<String>,
+
+	/// Order of the derivative of `func` that is approximated numerically when evaluating, used
+	/// when `func` couldn't be differentiated symbolically
+	numeric_order: usize,
+
+	/// Operators (such as `integral(t^2, t, 0, x)`) that were replaced with placeholder variables in
+	/// `func`, which are evaluated numerically
+	operators: Vec<Operator>,
 }
 
 impl FlatExWrapper {
 	const EMPTY: FlatExWrapper = FlatExWrapper {
 		func: None,
 		func_str: None,
+		numeric_order: 0,
+		operators: Vec::new(),
 	};
 
 	#[inline]
@@ -18,25 +65,161 @@ impl FlatExWrapper {
 		Self {
 			func: Some(f),
 			func_str: None,
+			numeric_order: 0,
+			operators: Vec::new(),
+		}
+	}
+
+	/// Parses `func_str`, which may only use the variables in `variables`
+	pub(crate) fn parse(func_str: &str, variables: &[&str]) -> Result<Self, ParseError> {
+		let Extracted {
+			func_str: extracted,
+			operators,
+			calls,
+		} = extract_operators(func_str, variables)?;
+		let locate = |span: Range<usize>| original_span(span, &calls);
+
+		parse_expression(&extracted).map_err(|error| error.map_spans(locate))?;
+
+		// Calls to functions exmex doesn't have were all extracted, so any other call is unknown
+		if let Some((name, span)) = unknown_call(&extracted) {
+			return Err(ParseError::unknown_function(name, locate(span)));
+		}
+
+		let func = exmex::parse::<f64>(&extracted)
+			.map_err(|e| ParseError::new(ParseErrorKind::Syntax(e.to_string())))?;
+
+		let invalid: Vec<String> = func
+			.var_names()
+			.iter()
+			.filter(|name| !name.starts_with(PLACEHOLDER) && !variables.contains(&name.as_str()))
+			.cloned()
+			.collect();
+		if let Some(first) = invalid.first() {
+			let span = identifiers(&extracted)
+				.into_iter()
+				.find(|&(s, e)| &extracted[s..e] == first)
+				.map(|(s, e)| locate(s..e));
+
+			let error = ParseError::new(ParseErrorKind::InvalidVariables(invalid));
+			return Err(match span {
+				Some(span) => error.at(span),
+				None => error,
+			});
+		}
+
+		Ok(Self {
+			operators,
+			..Self::new(func)
+		})
+	}
+
+	/// The operator that the variable `name` is a placeholder for
+	fn operator(&self, name: &str) -> Option<&Operator> {
+		name.strip_prefix(PLACEHOLDER)
+			.and_then(|i| i.parse::<usize>().ok())
+			.and_then(|i| self.operators.get(i))
+	}
+
+	/// Returns true if the expression depends on the variable `name`
+	pub(crate) fn depends_on(&self, name: &str) -> bool {
+		self.func.as_ref().is_some_and(|f| {
+			f.var_names()
+				.iter()
+				.any(|var| match self.operator(var) {
+					Some(operator) => operator.depends_on(name),
+					None => var == name,
+				})
+		})
+	}
+
+	/// Numerically differentiates the expression `order` more times
+	#[inline]
+	fn numeric(self, order: usize) -> Self {
+		Self {
+			numeric_order: self.numeric_order + order,
+			..self
 		}
 	}
 
+	/// Returns true if the expression is approximated numerically
+	#[inline]
+	pub const fn is_numeric(&self) -> bool { self.numeric_order > 0 }
+
 	#[inline]
 	const fn is_none(&self) -> bool { self.func.is_none() }
 
+	/// Returns true if the expression doesn't depend on any variables
+	#[inline]
+	fn is_constant(&self) -> bool { !self.is_none() && !self.depends_on("x") }
+
 	#[inline]
 	pub fn eval(&self, x: &[f64]) -> f64 {
+		match (self.numeric_order, x.first()) {
+			(0, _) => self.eval_exact(x),
+			(order, Some(&x)) => numeric_derivative(|x| self.eval_exact(&[x]), order, x),
+			(_, None) => f64::NAN,
+		}
+	}
+
+	/// Evaluates `func` itself, ignoring `numeric_order`
+	#[inline]
+	fn eval_exact(&self, x: &[f64]) -> f64 {
+		if !self.operators.is_empty() {
+			let env: Vec<(&str, f64)> = x.first().map(|&x| ("x", x)).into_iter().collect();
+			return self.eval_in(&env);
+		}
+
 		self.func
 			.as_ref()
-			.map(|f| f.eval(x).unwrap_or(f64::NAN))
+			.map(|f| {
+				// Constant expressions don't take any variables
+				let n = f.var_names().len().min(x.len());
+				f.eval(&x[..n]).unwrap_or(f64::NAN)
+			})
 			.unwrap_or(f64::NAN)
 	}
 
+	/// Evaluates `func` where each variable has its last value in `env`, ignoring `numeric_order`
+	pub(crate) fn eval_in(&self, env: &[(&str, f64)]) -> f64 {
+		let Some(f) = self.func.as_ref() else {
+			return f64::NAN;
+		};
+
+		let values: Vec<f64> = f
+			.var_names()
+			.iter()
+			.map(|name| match self.operator(name) {
+				Some(operator) => operator.eval(env),
+				None => env
+					.iter()
+					.rev()
+					.find(|(var, _)| var == name)
+					.map(|(_, value)| *value)
+					.unwrap_or(f64::NAN),
+			})
+			.collect();
+		f.eval(&values).unwrap_or(f64::NAN)
+	}
+
+	/// Expression that is always zero, used as the derivative of constant expressions
+	fn zero() -> Self { exmex::parse::<f64>("0").map(Self::new).unwrap_or(Self::EMPTY) }
+
 	#[inline]
 	fn partial(&self, x: usize) -> Self {
+		// exmex would treat operators as independent variables
+		if !self.operators.is_empty() {
+			return self.clone().numeric(1);
+		}
+
 		self.func
 			.as_ref()
-			.map(|f| f.clone().partial(x).map(Self::new).unwrap_or(Self::EMPTY))
+			.map(|f| {
+				f.clone()
+					.partial(x)
+					.map(Self::new)
+					.unwrap_or_else(|_| self.clone().numeric(1))
+			})
 			.unwrap_or(Self::EMPTY)
 	}
 
@@ -52,20 +235,27 @@ impl FlatExWrapper {
 		}
 	}
 
+	/// Symbolically computes the `n`th derivative, `None` if exmex can't differentiate the expression
 	#[inline]
-	fn partial_iter(&self, n: usize) -> Self {
-		self.func
-			.as_ref()
-			.map(|f| {
-				f.clone()
-					.partial_iter((0..n).map(|_| 0))
-					.map(Self::new)
-					.unwrap_or(Self::EMPTY)
-			})
-			.unwrap_or(Self::EMPTY)
+	fn partial_iter(&self, n: usize) -> Option<Self> {
+		let f = self.func.as_ref()?;
+		if self.is_constant() {
+			return Some(Self::zero());
+		}
+
+		// exmex would treat operators as independent variables
+		if !self.operators.is_empty() {
+			return None;
+		}
+
+		f.clone().partial_iter((0..n).map(|_| 0)).map(Self::new).ok()
 	}
 }
 
+/// Returned for derivatives that haven't been generated, as `FlatExWrapper::EMPTY` can't be borrowed
+/// for `'static`
+static EMPTY_WRAPPER: FlatExWrapper = FlatExWrapper::EMPTY;
+
 impl Default for FlatExWrapper {
 	fn default() -> FlatExWrapper { FlatExWrapper::EMPTY }
 }
@@ -86,8 +276,11 @@ impl Default for BackingFunction {
 impl BackingFunction {
 	pub const fn is_none(&self) -> bool { self.function.is_none() }
 
+	/// Returns true if f(x) doesn't actually depend on `x`
+	pub fn is_constant(&self) -> bool { self.function.is_constant() }
+
 	/// Create new [`BackingFunction`] instance
-	pub fn new(func_str: &str) -> Result<Self, String> {
+	pub fn new(func_str: &str) -> Result<Self, ParseError> {
 		if func_str.is_empty() {
 			return Ok(Self {
 				function: FlatExWrapper::EMPTY,
@@ -95,32 +288,12 @@ impl BackingFunction {
 			});
 		}
 
-		let function = FlatExWrapper::new({
-			let parse_result = exmex::parse::<f64>(func_str);
-
-			match &parse_result {
-				Err(e) => return Err(e.to_string()),
-				Ok(ok_result) => {
-					let var_names = ok_result.var_names().to_vec();
-
-					if var_names != ["x"] {
-						let var_names_not_x: Vec<&String> = var_names
-							.iter()
-							.filter(|ele| ele != &"x")
-							.collect::<Vec<&String>>();
-
-						return Err(format!(
-							"Error: invalid variable{}",
-							match var_names_not_x.len() {
-								1 => String::from(": ") + var_names_not_x[0].as_str(),
-								_ => format!("s: {:?}", var_names_not_x),
-							}
-						));
-					}
-				}
-			}
-			unsafe { parse_result.unwrap_unchecked() }
-		});
+		let function = FlatExWrapper::parse(func_str, &["x"])?;
+
+		// Constant expressions (such as `f(x) = 3`) are fine as long as they are defined
+		if function.is_constant() && !function.eval(&[]).is_finite() {
+			return Err(ParseErrorKind::Undefined.into());
+		}
 
 		Ok(Self {
 			function,
@@ -135,10 +308,26 @@ impl BackingFunction {
 			return;
 		}
 
-		if !self.nth_derivative.contains_key(&derivative) {
-			let new_func = self.function.partial_iter(derivative);
-			self.nth_derivative.insert(derivative, new_func.clone());
+		if self.function.is_none() | self.nth_derivative.contains_key(&derivative) {
+			return;
 		}
+
+		// Use the highest derivative that can be found symbolically, numerically differentiating it
+		// the rest of the way
+		let new_func = (1..=derivative.min(MAX_SYMBOLIC_DERIVATIVE))
+			.rev()
+			.find_map(|n| {
+				self.function
+					.partial_iter(n)
+					.map(|f| f.numeric(derivative - n))
+			})
+			.unwrap_or_else(|| self.function.clone().numeric(derivative));
+		self.nth_derivative.insert(derivative, new_func);
+	}
+
+	/// Returns true if the `derivative`th derivative is approximated numerically
+	pub fn is_numeric(&self, derivative: usize) -> bool {
+		self.get_function_derivative(derivative).is_numeric()
 	}
 
 	pub fn get_function_derivative(&self, derivative: usize) -> &FlatExWrapper {
@@ -148,8 +337,28 @@ impl BackingFunction {
 			return self
 				.nth_derivative
 				.get(&derivative)
-				.unwrap_or(&FlatExWrapper::EMPTY);
+				.unwrap_or(&EMPTY_WRAPPER);
+		}
+	}
+
+	/// The `derivative`th derivative (or the function itself for `0`) as an expression, written in
+	/// terms of `deriv` where it's approximated numerically. `None` if it hasn't been generated or
+	/// exmex's form of it can't be read back. Derivatives are simplified, as exmex leaves terms
+	/// such as `0.0*x` in them.
+	pub fn derivative_expression(&self, derivative: usize) -> Option<Expr> {
+		let function = self.get_function_derivative(derivative);
+		// Operators are only in exmex's form as placeholders
+		if !function.operators.is_empty() {
+			return None;
 		}
+
+		let func_str = prettyify_function_str(function.func.as_ref()?.unparse());
+		let expr = parse_expression(&func_str).ok()?;
+		let expr = match derivative {
+			0 => expr,
+			_ => expr.simplified(),
+		};
+		Some(expr.differentiated("x", function.numeric_order))
 	}
 
 	pub fn get(&mut self, derivative: usize, x: f64) -> f64 {
@@ -167,15 +376,6 @@ fn prettyify_function_str(func: &str) -> String {
 	}
 }
 
-// pub const VALID_VARIABLES: [char; 3] = ['x', 'e', 'π'];
-
-/// Case insensitive checks for if `c` is a character used to represent a variable
-#[inline]
-pub const fn is_variable(c: &char) -> bool {
-	let c = c.to_ascii_lowercase();
-	(c == 'x') | (c == 'e') | (c == 'π')
-}
-
 /// Adds asterisks where needed in a function
 pub fn process_func_str(function_in: &str) -> String {
 	if function_in.is_empty() {
@@ -0,0 +1,249 @@
+use crate::algebra::{as_fraction, gcd, polynomial_coefficients, rational_roots};
+use crate::ast::Expr;
+use crate::complex::Complex;
+use std::cmp::Ordering;
+
+/// Largest degree of polynomials whose roots are found
+const MAX_DEGREE: usize = 64;
+
+/// Most iterations of the Durand–Kerner method before giving up on converging further
+const MAX_ITERATIONS: usize = 1000;
+
+/// Roots found numerically that are closer together than this (relative to their size) are taken
+/// to be one repeated root. Repeated roots are only found to about `ε^(1/m)` for multiplicity `m`.
+const CLUSTER_TOLERANCE: f64 = 1e-5;
+
+/// Imaginary parts smaller than this (relative to the root's size) are rounding error
+const REAL_TOLERANCE: f64 = 1e-9;
+
+/// Root of a polynomial, which may be complex
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Root {
+	/// Real part
+	pub re: f64,
+
+	/// Imaginary part, zero for real roots
+	pub im: f64,
+
+	/// Number of times the root is repeated, such as 2 for the root of `(x - 1)^2`
+	pub multiplicity: usize,
+}
+
+impl Root {
+	const fn real(x: f64, multiplicity: usize) -> Self {
+		Self {
+			re: x,
+			im: 0.0,
+			multiplicity,
+		}
+	}
+
+	pub fn is_real(&self) -> bool { self.im == 0.0 }
+}
+
+/// Polynomial in one variable, such as `x^3 - 2·x + 1`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polynomial {
+	/// Coefficients from the constant term up, the last of which isn't zero
+	coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+	/// `expr` as a polynomial in `variable`, if it is one (once multiplied out) of degree at least 1
+	/// and no more than [`MAX_DEGREE`]
+	pub fn from_expr(expr: &Expr, variable: &str) -> Option<Self> {
+		let mut coefficients = polynomial_coefficients(expr, variable)?;
+		while coefficients.last() == Some(&0.0) {
+			coefficients.pop();
+		}
+
+		let degree = coefficients.len().checked_sub(1)?;
+		let finite = coefficients.iter().all(|a| a.is_finite());
+		((1..=MAX_DEGREE).contains(&degree) && finite).then_some(Self { coefficients })
+	}
+
+	pub fn degree(&self) -> usize { self.coefficients.len() - 1 }
+
+	/// Coefficients from the constant term up
+	pub fn coefficients(&self) -> &[f64] { &self.coefficients }
+
+	/// Value of the polynomial at `x`
+	pub fn eval(&self, x: f64) -> f64 { horner(&self.coefficients, x) }
+
+	/// Every root of the polynomial, real and complex, with repeated roots listed once along with
+	/// their multiplicity. Rational roots (of polynomials with rational coefficients) are found
+	/// exactly, and the rest to machine precision. Real roots come first, from smallest to largest.
+	pub fn roots(&self) -> Vec<Root> {
+		let mut roots = Vec::new();
+
+		// Roots at zero, such as those of `x^3 + x^2`
+		let zeros = self.coefficients.iter().take_while(|a| **a == 0.0).count();
+		if zeros > 0 {
+			roots.push(Root::real(0.0, zeros));
+		}
+		let mut remaining = self.coefficients[zeros..].to_vec();
+
+		if let Some(integers) = integer_coefficients(&remaining).filter(|c| c.len() > 1) {
+			let rational = rational_roots(integers);
+			for ((p, q), multiplicity) in rational.factors {
+				roots.push(Root::real(p as f64 / q as f64, multiplicity));
+			}
+			remaining = rational.quotient.into_iter().map(|a| a as f64).collect();
+		}
+
+		match remaining.as_slice() {
+			[] | [_] => {}
+			[c, b] => roots.push(Root::real(-c / b, 1)),
+			[c, b, a] => roots.extend(quadratic_roots(*a, *b, *c)),
+			_ => roots.extend(numeric_roots(&remaining)),
+		}
+
+		roots.sort_by(|a, b| {
+			(b.is_real().cmp(&a.is_real()))
+				.then(a.re.partial_cmp(&b.re).unwrap_or(Ordering::Equal))
+				.then(a.im.partial_cmp(&b.im).unwrap_or(Ordering::Equal))
+		});
+		roots
+	}
+}
+
+/// Value of the polynomial with `coefficients` (from the constant term up) at `x`
+fn horner(coefficients: &[f64], x: f64) -> f64 {
+	coefficients.iter().rev().fold(0.0, |total, a| total * x + a)
+}
+
+/// `coefficients` scaled to integers, if they're all fractions
+fn integer_coefficients(coefficients: &[f64]) -> Option<Vec<i128>> {
+	let fractions: Vec<(i64, i64)> =
+		coefficients.iter().map(|a| as_fraction(*a)).collect::<Option<_>>()?;
+	let denominator = (fractions.iter())
+		.try_fold(1i128, |l, (_, q)| (l / gcd(l, *q as i128)).checked_mul(*q as i128))?;
+	(fractions.iter())
+		.map(|(p, q)| (*p as i128).checked_mul(denominator / *q as i128))
+		.collect()
+}
+
+/// Roots of `a·x^2 + b·x + c`
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<Root> {
+	let discriminant = b * b - 4.0 * a * c;
+	if discriminant == 0.0 {
+		return vec![Root::real(-b / (2.0 * a), 2)];
+	}
+
+	if discriminant < 0.0 {
+		// Adding zero turns `-0` into `0`
+		let (re, im) = (-b / (2.0 * a) + 0.0, (-discriminant).sqrt() / (2.0 * a).abs());
+		return vec![
+			Root {
+				re,
+				im: -im,
+				multiplicity: 1,
+			},
+			Root {
+				re,
+				im,
+				multiplicity: 1,
+			},
+		];
+	}
+
+	// Written to avoid subtracting nearly equal numbers, which loses precision
+	let q = -(b + b.signum() * discriminant.sqrt()) / 2.0;
+	let mut roots = [q / a, c / q];
+	roots.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+	roots.into_iter().map(|x| Root::real(x, 1)).collect()
+}
+
+/// Value of the polynomial with `coefficients` at `z`
+fn eval_complex(coefficients: &[f64], z: Complex) -> Complex {
+	(coefficients.iter().rev()).fold(Complex::ZERO, |total, a| total * z + Complex::real(*a))
+}
+
+/// Roots of the polynomial with `coefficients` (of degree at least 3), found with the
+/// Durand–Kerner method, which improves guesses of every root at once until they stop moving
+fn numeric_roots(coefficients: &[f64]) -> Vec<Root> {
+	let leading = coefficients[coefficients.len() - 1];
+	let monic: Vec<f64> = coefficients.iter().map(|a| a / leading).collect();
+	let degree = monic.len() - 1;
+
+	// Starting guesses spread around a circle, none of them conjugates of each other
+	let seed = Complex::new(0.4, 0.9);
+	let mut guesses: Vec<Complex> = (0..degree).map(|k| seed.powi(k)).collect();
+	for _ in 0..MAX_ITERATIONS {
+		let mut largest_step: f64 = 0.0;
+		for k in 0..degree {
+			let denominator = (0..degree)
+				.filter(|j| *j != k)
+				.fold(Complex::ONE, |product, j| product * (guesses[k] - guesses[j]));
+			let step = eval_complex(&monic, guesses[k]) / denominator;
+			if step.re.is_finite() && step.im.is_finite() {
+				guesses[k] = guesses[k] - step;
+				largest_step = largest_step.max(step.abs() / guesses[k].abs().max(1.0));
+			}
+		}
+
+		if largest_step < 1e-15 {
+			break;
+		}
+	}
+
+	// Guesses that converged to the same repeated root
+	let mut clusters: Vec<(Complex, usize)> = Vec::new();
+	for guess in guesses {
+		let tolerance = CLUSTER_TOLERANCE * guess.abs().max(1.0);
+		match clusters.iter_mut().find(|(root, count)| {
+			((*root / Complex::real(*count as f64)) - guess).abs() < tolerance
+		}) {
+			Some((sum, count)) => {
+				*sum = *sum + guess;
+				*count += 1;
+			}
+			None => clusters.push((guess, 1)),
+		}
+	}
+
+	clusters
+		.into_iter()
+		.map(|(sum, multiplicity)| {
+			let root = sum / Complex::real(multiplicity as f64);
+			match root.im.abs() < REAL_TOLERANCE * root.abs().max(1.0) {
+				true => Root::real(polish(&monic, root.re, multiplicity), multiplicity),
+				false => Root {
+					re: root.re,
+					im: root.im,
+					multiplicity,
+				},
+			}
+		})
+		.collect()
+}
+
+/// Refines the real root `x` of the polynomial with `coefficients` with a few steps of Newton's
+/// method (modified for roots repeated `multiplicity` times), as long as they get closer
+fn polish(coefficients: &[f64], mut x: f64, multiplicity: usize) -> f64 {
+	let derivative: Vec<f64> = (coefficients.iter().enumerate().skip(1))
+		.map(|(i, a)| i as f64 * a)
+		.collect();
+
+	for _ in 0..8 {
+		let slope = horner(&derivative, x);
+		if slope == 0.0 {
+			break;
+		}
+
+		let next = x - multiplicity as f64 * horner(coefficients, x) / slope;
+		if !next.is_finite() || (horner(coefficients, next).abs() >= horner(coefficients, x).abs())
+		{
+			break;
+		}
+		x = next;
+	}
+	x
+}
+
+impl Expr {
+	/// Roots of the expression if it's a polynomial in `variable` (see [`Polynomial::roots`])
+	pub fn polynomial_roots(&self, variable: &str) -> Option<Vec<Root>> {
+		Polynomial::from_expr(self, variable).map(|polynomial| polynomial.roots())
+	}
+}
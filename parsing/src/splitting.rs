@@ -1,18 +1,11 @@
-use crate::parsing::is_variable;
+use crate::lexer::{implicit_multiplication, tokenize, TokenKind};
 
+/// Splits `input` at each multiplication (implicit, such as in `2x`, or explicit) and, when `split`
+/// is [`SplitType::Term`], after each operator, comma, or opening parenthesis. Explicit
+/// multiplication signs are removed, and the pieces are written the way exmex expects (so `pi`
+/// becomes `π`, `**` becomes `^`, and `×` becomes `*`).
 pub fn split_function(input: &str, split: SplitType) -> Vec<String> {
-	split_function_chars(
-		&input
-			.replace("pi", "π") // replace "pi" text with pi symbol
-			.replace("**", "^") // support alternate manner of expressing exponents
-			.replace("exp", "\u{1fc93}") // stop-gap solution to fix the `exp` function
-			.chars()
-			.collect::<Vec<char>>(),
-		split,
-	)
-	.iter()
-	.map(|x| x.replace('\u{1fc93}', "exp")) // Convert back to `exp` text
-	.collect::<Vec<String>>()
+	split_tokens(input, split, true)
 }
 
 // Specifies how to split a function
@@ -22,124 +15,49 @@ pub enum SplitType {
 	Term,
 }
 
-/// Used to store info about a character
-struct BoolSlice {
-	closing_parens: bool,
-	open_parens: bool,
-	number: bool,
-	letter: bool,
-	variable: bool,
-	masked_num: bool,
-	masked_var: bool,
+// Splits a function (which is represented as an array of characters) based off of the value of SplitType
+pub fn split_function_chars(chars: &[char], split: SplitType) -> Vec<String> {
+	split_tokens(&chars.iter().collect::<String>(), split, false)
 }
 
-impl BoolSlice {
-	const fn from_char(c: &char, prev_masked_num: bool, prev_masked_var: bool) -> Self {
-		let isnumber = c.is_ascii_digit();
-		let isvariable = is_variable(c);
-		Self {
-			closing_parens: *c == ')',
-			open_parens: *c == '(',
-			number: isnumber,
-			letter: c.is_ascii_alphabetic(),
-			variable: isvariable,
-			masked_num: match isnumber {
-				true => prev_masked_num,
-				false => false,
-			},
-			masked_var: match isvariable {
-				true => prev_masked_var,
-				false => false,
-			},
-		}
+/// Splits `input` like [`split_function`], keeping the text of its tokens as they were written unless
+/// `normalize` is true
+fn split_tokens(input: &str, split: SplitType, normalize: bool) -> Vec<String> {
+	if input.is_empty() {
+		return Vec::new();
 	}
 
-	const fn is_unmasked_variable(&self) -> bool { self.variable && !self.masked_var }
-
-	const fn is_unmasked_number(&self) -> bool { self.number && !self.masked_num }
-
-	const fn calculate_mask(&mut self, other: &BoolSlice) {
-		if other.masked_num && self.number {
-			// If previous char was a masked number, and current char is a number, mask current char's variable status
-			self.masked_num = true;
-		} else if other.masked_var && self.variable {
-			// If previous char was a masked variable, and current char is a variable, mask current char's variable status
-			self.masked_var = true;
-		} else if other.letter && !other.is_unmasked_variable() {
-			self.masked_num = self.number;
-			self.masked_var = self.variable;
+	let tokens = tokenize(input);
+	let mut data: Vec<String> = Vec::new();
+	let mut piece = String::new();
+
+	// End of the previous token, so the whitespace between tokens is kept
+	let mut last = 0;
+	for (i, token) in tokens.iter().enumerate() {
+		let splitable = i.checked_sub(1).map(|i| &tokens[i]).is_some_and(|previous| {
+			implicit_multiplication(previous, token)
+				| ((split == SplitType::Term) && previous.kind.separates_terms())
+		});
+		if splitable {
+			data.push(std::mem::take(&mut piece));
 		}
-	}
 
-	const fn splitable(&self, c: &char, other: &BoolSlice, split: &SplitType) -> bool {
-		if (*c == '*') | (matches!(split, &SplitType::Term) && other.open_parens) {
-			true
-		} else if other.closing_parens {
-			// Cases like `)x`, `)2`, and `)(`
-			return (*c == '(')
-				| (self.letter && !self.is_unmasked_variable())
-				| self.is_unmasked_variable()
-				| self.is_unmasked_number();
-		} else if *c == '(' {
-			// Cases like `x(` and `2(`
-			return (other.is_unmasked_variable() | other.is_unmasked_number()) && !other.letter;
-		} else if other.is_unmasked_number() {
-			// Cases like `2x` and `2sin(x)`
-			return self.is_unmasked_variable() | self.letter;
-		} else if self.is_unmasked_variable() | self.letter {
-			// Cases like `e2` and `xx`
-			return other.is_unmasked_number()
-				| (other.is_unmasked_variable() && self.is_unmasked_variable())
-				| other.is_unmasked_variable();
-		} else if (self.is_unmasked_number() | self.letter | self.is_unmasked_variable())
-			&& (other.is_unmasked_number() | other.letter)
-		{
-			return true;
-		} else {
-			return self.is_unmasked_number() && other.is_unmasked_variable();
-		}
-	}
-}
+		piece.push_str(&input[last..token.span.start]);
+		last = token.span.end;
 
-// Splits a function (which is represented as an array of characters) based off of the value of SplitType
-pub fn split_function_chars(chars: &[char], split: SplitType) -> Vec<String> {
-	// Catch some basic cases
-	match chars.len() {
-		0 => return Vec::new(),
-		1 => return vec![chars[0].to_string()],
-		_ => {}
-	}
-
-	// Resulting split-up data
-	let mut data: Vec<String> = std::vec::from_elem(chars[0].to_string(), 1);
-
-	// Setup first char here
-	let mut prev_char: BoolSlice = BoolSlice::from_char(&chars[0], false, false);
-
-	let mut last = unsafe { data.last_mut().unwrap_unchecked() };
-
-	// Iterate through all chars excluding the first one
-	for c in chars.iter().skip(1) {
-		// Set data about current character
-		let mut curr_c = BoolSlice::from_char(c, prev_char.masked_num, prev_char.masked_var);
-
-		curr_c.calculate_mask(&prev_char);
-
-		// Append split
-		if curr_c.splitable(c, &prev_char, &split) {
-			// create new buffer
-			data.push(String::new());
-			last = unsafe { data.last_mut().unwrap_unchecked() };
+		// Explicit multiplication splits without being part of either piece
+		if token.kind == TokenKind::Star {
+			data.push(std::mem::take(&mut piece));
+			continue;
 		}
 
-		// Exclude asterisks
-		if c != &'*' {
-			last.push(*c);
+		match normalize {
+			true => piece.push_str(&token.normalized(input)),
+			false => piece.push_str(token.text(input)),
 		}
-
-		// Move current character data to `prev_char`
-		prev_char = curr_c;
 	}
+	piece.push_str(&input[last..]);
+	data.push(piece);
 
 	data
 }
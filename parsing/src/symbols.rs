@@ -0,0 +1,450 @@
+use crate::error::{span_within, ParseError, ParseErrorKind};
+use crate::operators::{operator_call, OperatorKind};
+use crate::{
+	format_expression, parse_expression, process_func_str, BackingFunction, Rewrite,
+	SUPPORTED_FUNCTIONS,
+};
+use std::collections::HashMap;
+
+/// What a function entry's string defines
+#[derive(Clone, PartialEq, Debug)]
+pub enum Definition {
+	/// A plain expression of `x` such as `x^2` (or `y = x^2`)
+	Expression(String),
+
+	/// A named function such as `f(x) = x^2`, which other entries can call
+	Function {
+		name: String,
+		param: String,
+		body: String,
+	},
+
+	/// A named constant such as `k = 9.81`, which other entries can use
+	Constant { name: String, body: String },
+}
+
+impl Definition {
+	/// Name other entries can refer to this definition by
+	pub fn name(&self) -> Option<&str> {
+		match self {
+			Definition::Expression(_) => None,
+			Definition::Function { name, .. } | Definition::Constant { name, .. } => Some(name),
+		}
+	}
+
+	/// The right hand side of the definition
+	pub fn body(&self) -> &str {
+		match self {
+			Definition::Expression(body) => body,
+			Definition::Function { body, .. } | Definition::Constant { body, .. } => body,
+		}
+	}
+
+	/// The definition in canonical form (see [`format_expression`]), such as `f(x) = 2·x` for
+	/// `f( x )=2x`. Plain expressions are written without `y =`.
+	pub fn canonical(&self) -> Result<String, ParseError> {
+		format_expression(self.body()).map(|body| self.with_body(&body))
+	}
+
+	/// The definition with its body rewritten by `rewrite`, such as `f(x) = x^2 + 2·x + 1` for
+	/// expanding `f(x) = (x+1)^2`. Functions are rewritten in terms of their parameter, and
+	/// everything else in terms of `x`.
+	pub fn rewritten(&self, rewrite: Rewrite) -> Result<String, ParseError> {
+		let variable = match self {
+			Definition::Function { param, .. } => param,
+			_ => "x",
+		};
+		let body = rewrite.apply(&parse_expression(self.body())?, variable);
+		Ok(self.with_body(&body.to_string()))
+	}
+
+	/// The definition written out with `body` as its right hand side
+	fn with_body(&self, body: &str) -> String {
+		match self {
+			Definition::Expression(_) => body.to_owned(),
+			Definition::Function { name, param, .. } => format!("{}({}) = {}", name, param, body),
+			Definition::Constant { name, .. } => format!("{} = {}", name, body),
+		}
+	}
+}
+
+/// Returns true if `c` can be part of an identifier
+#[inline]
+const fn is_ident_char(c: char) -> bool { c.is_ascii_alphanumeric() | (c == '_') }
+
+/// Returns true if `s` is a valid identifier (a letter followed by letters, digits, or underscores)
+pub(crate) fn is_identifier(s: &str) -> bool {
+	let mut chars = s.chars();
+	match chars.next() {
+		Some(c) if c.is_ascii_alphabetic() => chars.all(is_ident_char),
+		_ => false,
+	}
+}
+
+/// Checks that `name` (a substring of `raw`) can be used to name a user definition
+fn validate_name(raw: &str, name: &str) -> Result<(), ParseError> {
+	if !is_identifier(name) {
+		return Err(ParseError::new(ParseErrorKind::InvalidName(name.to_owned()))
+			.at(span_within(raw, name)));
+	}
+
+	if matches!(name, "x" | "e" | "E" | "pi") | SUPPORTED_FUNCTIONS.contains(&name) {
+		return Err(ParseError::new(ParseErrorKind::Reserved(name.to_owned()))
+			.at(span_within(raw, name)));
+	}
+
+	Ok(())
+}
+
+/// Parses the raw string of a function entry into a [`Definition`]
+pub fn parse_definition(raw: &str) -> Result<Definition, ParseError> {
+	let Some((lhs, rhs)) = raw.split_once('=') else {
+		return Ok(Definition::Expression(raw.to_owned()));
+	};
+
+	let lhs = lhs.trim();
+	let body = rhs.trim().to_owned();
+
+	if let Some(i) = rhs.find('=') {
+		let start = span_within(raw, rhs).start + i;
+		return Err(ParseError::new(ParseErrorKind::MultipleEquals).at(start..(start + 1)));
+	}
+
+	// `y = ...` is the same as just writing the expression
+	if lhs == "y" {
+		return Ok(Definition::Expression(body));
+	}
+
+	if is_identifier(lhs) {
+		validate_name(raw, lhs)?;
+		return Ok(Definition::Constant {
+			name: lhs.to_owned(),
+			body,
+		});
+	}
+
+	match lhs.strip_suffix(')').and_then(|lhs| lhs.split_once('(')) {
+		Some((name, param)) => {
+			let (name, param) = (name.trim(), param.trim());
+			validate_name(raw, name)?;
+			if !is_identifier(param) {
+				return Err(ParseError::new(ParseErrorKind::InvalidParameter(param.to_owned()))
+					.at(span_within(raw, param)));
+			}
+
+			// The parameter is renamed to `x`, which would be mixed up with any `x` in the body
+			let uses_x = referenced_names(&process_func_str(&body)).iter().any(|n| n == "x");
+			if (param != "x") && uses_x {
+				let body_span = span_within(raw, rhs.trim());
+				let span = match identifiers(&body).into_iter().find(|&(s, e)| &body[s..e] == "x") {
+					Some((s, e)) => (body_span.start + s)..(body_span.start + e),
+					None => body_span,
+				};
+				let kind = ParseErrorKind::XNotParameter(param.to_owned());
+				return Err(ParseError::new(kind).at(span));
+			}
+
+			Ok(Definition::Function {
+				name: name.to_owned(),
+				param: param.to_owned(),
+				body,
+			})
+		}
+		None => Err(ParseError::new(ParseErrorKind::InvalidDefinition(lhs.to_owned()))
+			.at(span_within(raw, lhs))),
+	}
+}
+
+/// Splits `expr` into a list of identifiers along with their byte ranges
+pub(crate) fn identifiers(expr: &str) -> Vec<(usize, usize)> {
+	let mut result = Vec::new();
+	let mut start: Option<usize> = None;
+
+	for (i, c) in expr.char_indices() {
+		match (start, is_ident_char(c)) {
+			(None, true) => start = Some(i),
+			(Some(s), false) => {
+				result.push((s, i));
+				start = None;
+			}
+			_ => {}
+		}
+	}
+
+	if let Some(s) = start {
+		result.push((s, expr.len()));
+	}
+
+	// Numbers such as `10` or `2e5` are not identifiers
+	result.retain(|(s, _)| expr[*s..].starts_with(|c: char| c.is_ascii_alphabetic()));
+	result
+}
+
+/// Returns the names of everything referenced by `expr`
+pub fn referenced_names(expr: &str) -> Vec<String> {
+	let mut names: Vec<String> = identifiers(expr)
+		.into_iter()
+		.map(|(s, e)| expr[s..e].to_owned())
+		.collect();
+	names.sort_unstable();
+	names.dedup();
+	names
+}
+
+/// Returns the index of the parenthesis closing the one opened just before `expr[start..]`
+pub(crate) fn matching_paren(expr: &str, start: usize) -> Option<usize> {
+	let mut depth: usize = 1;
+	for (i, c) in expr[start..].char_indices() {
+		match c {
+			'(' => depth += 1,
+			')' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(start + i);
+				}
+			}
+			_ => {}
+		}
+	}
+	None
+}
+
+/// Replaces every occurrence of the identifier `name` in `expr` with `replacement`, except inside
+/// of operators whose variable is also `name` (such as `x` in `deriv(sin(x), x)`)
+fn replace_identifier(expr: &str, name: &str, replacement: &str) -> String {
+	let mut result = String::with_capacity(expr.len());
+	let mut last = 0;
+	for (s, e) in identifiers(expr) {
+		// Skip identifiers that are inside of an already replaced operator
+		if s < last {
+			continue;
+		}
+
+		if let Some((close, arguments)) = operator_call(expr, s, e) {
+			let binds_name = arguments.get(1).map(|variable| variable.trim()) == Some(name);
+			let mut replaced: Vec<String> = arguments
+				.iter()
+				.enumerate()
+				.map(|(i, argument)| match binds_name && (i < 2) {
+					true => (*argument).to_owned(),
+					false => replace_identifier(argument, name, replacement),
+				})
+				.collect();
+
+			// `deriv(f, t)` is taken at `x`, so it's now taken at `replacement`
+			let is_derivative = &expr[s..e] == OperatorKind::Derivative.name();
+			if is_derivative && (arguments.len() == 2) && (name == "x") {
+				replaced.push(replacement.to_owned());
+			}
+
+			result.push_str(&expr[last..e]);
+			result.push('(');
+			result.push_str(&replaced.join(","));
+			result.push(')');
+			last = close + 1;
+			continue;
+		}
+
+		if &expr[s..e] == name {
+			result.push_str(&expr[last..s]);
+			result.push_str(replacement);
+			last = e;
+		}
+	}
+	result.push_str(&expr[last..]);
+	result
+}
+
+/// A user defined function
+#[derive(Clone, PartialEq, Debug)]
+struct UserFunction {
+	param: String,
+
+	/// Processed body with every reference to other definitions already expanded
+	body: String,
+}
+
+/// Stores user definitions so that expressions can refer to them by name
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SymbolTable {
+	functions: HashMap<String, UserFunction>,
+	constants: HashMap<String, f64>,
+}
+
+impl SymbolTable {
+	pub fn is_empty(&self) -> bool { self.functions.is_empty() && self.constants.is_empty() }
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.functions.contains_key(name) | self.constants.contains_key(name)
+	}
+
+	/// Adds a constant `name = value`
+	pub fn insert_constant(&mut self, name: &str, value: f64) {
+		self.constants.insert(name.to_owned(), value);
+	}
+
+	/// Removes the definition of `name`
+	fn remove(&mut self, name: &str) {
+		self.functions.remove(name);
+		self.constants.remove(name);
+	}
+
+	/// Value of the constant `name`
+	pub fn get_constant(&self, name: &str) -> Option<f64> { self.constants.get(name).copied() }
+
+	/// Evaluates the raw expression `raw`, which may only use numbers and constants
+	pub fn eval_constant(&self, raw: &str) -> Result<f64, ParseError> {
+		let processed = process_func_str(raw);
+		let expanded = self
+			.expand(&processed)
+			.map_err(|error| error.in_source(&processed, raw))?;
+		if expanded.trim().is_empty() {
+			return Err(ParseErrorKind::ExpectedValue.into());
+		}
+
+		let function =
+			BackingFunction::new(&expanded).map_err(|error| error.in_source(&expanded, raw))?;
+		if !function.is_constant() {
+			return Err(ParseErrorKind::DependsOnX.into());
+		}
+
+		Ok(function.get_function_derivative(0).eval(&[]))
+	}
+
+	/// Adds a function `name(param) = body` where `body` has already been expanded
+	pub fn insert_function(&mut self, name: &str, param: &str, body: &str) {
+		self.functions.insert(
+			name.to_owned(),
+			UserFunction {
+				param: param.to_owned(),
+				body: body.to_owned(),
+			},
+		);
+	}
+
+	/// Replaces user defined constants and calls to user defined functions in the processed expression
+	/// `expr` with their values and bodies
+	pub fn expand(&self, expr: &str) -> Result<String, ParseError> {
+		if self.is_empty() {
+			return Ok(expr.to_owned());
+		}
+
+		let mut result = String::with_capacity(expr.len());
+		let mut last = 0;
+		for (s, e) in identifiers(expr) {
+			// Skip identifiers that are inside of an already expanded call
+			if s < last {
+				continue;
+			}
+
+			let name = &expr[s..e];
+
+			// The variable of an operator (such as `n` in `sum(n^2, n, 1, 10)`) hides the definition of
+			// the same name inside of the operator's expression
+			if let Some((close, arguments)) = operator_call(expr, s, e) {
+				let variable = arguments.get(1).map(|variable| variable.trim());
+				if variable.is_some_and(|variable| self.contains(variable)) {
+					let mut inner = self.clone();
+					inner.remove(variable.unwrap_or_default());
+
+					let expand_argument = |symbols: &SymbolTable, argument: &str| {
+						symbols
+							.expand(argument)
+							.map_err(|error| error.offset(span_within(expr, argument).start))
+					};
+
+					let mut expanded = vec![
+						expand_argument(&inner, arguments[0])?,
+						arguments[1].to_owned(),
+					];
+					for argument in &arguments[2..] {
+						expanded.push(expand_argument(self, argument)?);
+					}
+
+					result.push_str(&expr[last..e]);
+					result.push('(');
+					result.push_str(&expanded.join(","));
+					result.push(')');
+					last = close + 1;
+					continue;
+				}
+			}
+
+			if let Some(value) = self.constants.get(name) {
+				result.push_str(&expr[last..s]);
+				result.push_str(&format!("({})", value));
+				// `k(x+1)` is multiplication when `k` is a constant
+				if expr[e..].starts_with('(') {
+					result.push('*');
+				}
+				last = e;
+				continue;
+			}
+
+			let Some(function) = self.functions.get(name) else {
+				continue;
+			};
+
+			let usage = format!("{}(x)", name);
+			if !expr[e..].starts_with('(') {
+				return Err(ParseError::new(ParseErrorKind::NotCalled {
+					function: name.to_owned(),
+					usage,
+				})
+				.at(s..e)
+				.with_fix(e..e, "(x)"));
+			}
+
+			let Some(close) = matching_paren(expr, e + 1) else {
+				return Err(ParseError::new(ParseErrorKind::UnclosedCall(name.to_owned()))
+					.at(s..e)
+					.with_fix(expr.len()..expr.len(), ")"));
+			};
+			let argument = &expr[(e + 1)..close];
+
+			if argument.trim().is_empty() {
+				return Err(ParseError::new(ParseErrorKind::EmptyArgument {
+					function: name.to_owned(),
+					usage,
+				})
+				.at(s..(close + 1)));
+			}
+
+			if argument.contains(',') {
+				return Err(ParseError::new(ParseErrorKind::WrongArgumentCount {
+					function: name.to_owned(),
+					usage,
+				})
+				.at(s..(close + 1)));
+			}
+
+			let argument = self
+				.expand(argument)
+				.map_err(|error| error.offset(e + 1))?;
+			result.push_str(&expr[last..s]);
+			result.push('(');
+			result.push_str(&replace_identifier(
+				&function.body,
+				&function.param,
+				&format!("({})", argument),
+			));
+			result.push(')');
+			last = close + 1;
+		}
+		result.push_str(&expr[last..]);
+
+		Ok(result)
+	}
+}
+
+/// Processes the body of `definition` via [`process_func_str`]
+pub fn process_definition(definition: &Definition) -> String {
+	match definition {
+		Definition::Expression(body) | Definition::Constant { body, .. } => process_func_str(body),
+		Definition::Function { body, param, .. } if param == "x" => process_func_str(body),
+		// Rename the parameter to `x` so the body can be graphed and processed like any other function
+		Definition::Function { body, param, .. } => {
+			replace_identifier(&process_func_str(body), param, "x")
+		}
+	}
+}
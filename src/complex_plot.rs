@@ -0,0 +1,167 @@
+use crate::exact::format_complex;
+use crate::function_manager::FunctionManager;
+use egui::{ColorImage, TextureHandle, TextureOptions};
+use egui_plot::{PlotImage, PlotPoint, PlotUi, Points, Text};
+use emath::{Align2, vec2};
+use epaint::{Color32, Hsva};
+use parsing::{Complex, ComplexFunction, ZeroOrPole};
+use std::f64::consts::{FRAC_2_PI, TAU};
+
+/// Most pixels across the domain coloring image, which is stretched to cover the plot
+const MAX_COLUMNS: usize = 320;
+
+/// Cells across the plot that are searched for zeros and poles
+const SEARCH_COLUMNS: usize = 48;
+
+/// Parts of zeros and poles smaller than this are rounding error, so they're labeled as zero
+const LABEL_TOLERANCE: f64 = 1e-9;
+
+/// Color of the value `w` of a complex function, where the hue is its argument (red for positive
+/// real numbers) and the brightness grows with its magnitude, from black at zeros to white at
+/// poles. Undefined values are transparent.
+pub fn domain_color(w: Complex) -> Color32 {
+    let magnitude = w.abs();
+    if magnitude.is_nan() {
+        return Color32::TRANSPARENT;
+    }
+
+    let hue = (w.arg() / TAU).rem_euclid(1.0);
+    let value = FRAC_2_PI * magnitude.atan();
+    Hsva::new(hue as f32, 1.0, value as f32, 1.0).into()
+}
+
+/// Label of a zero or pole, such as `Zero: 1 + i` or `Pole ×2: 0`
+fn label(found: &ZeroOrPole) -> String {
+    let kind = match found.is_pole() {
+        true => "Pole",
+        false => "Zero",
+    };
+    let clean = |x: f64| match x.abs() < LABEL_TOLERANCE {
+        true => 0.0,
+        false => x,
+    };
+    let z = format_complex(clean(found.z.re), clean(found.z.im));
+    match found.multiplicity() {
+        1 => format!("{}: {}", kind, z),
+        multiplicity => format!("{} ×{}: {}", kind, multiplicity, z),
+    }
+}
+
+/// What a domain coloring image shows: the function, the plot's bounds, and the image's size
+type ImageKey = (ComplexFunction, [f64; 4], [usize; 2]);
+
+/// Domain coloring of a complex function, which colors each point `z` of the plot by the value of
+/// `f(z)` (see [`domain_color`]), along with its zeros and poles
+#[derive(Default)]
+pub struct DomainColoring {
+    /// Image of the last domain coloring displayed
+    texture: Option<TextureHandle>,
+
+    /// What `texture` shows, so it's only rebuilt when the function or plot changes
+    key: Option<ImageKey>,
+
+    /// Zeros and poles of the function within the plot
+    zeros_and_poles: Vec<ZeroOrPole>,
+}
+
+impl DomainColoring {
+    /// Displays the domain coloring of the first visible function that is a complex function,
+    /// labeling its zeros and poles
+    pub fn display(&mut self, plot_ui: &mut PlotUi, functions: &FunctionManager) {
+        let function = functions
+            .get_entries()
+            .iter()
+            .filter(|(_, function)| function.visible)
+            .find_map(|(_, function)| function.complex_function());
+        let Some(function) = function else {
+            return;
+        };
+
+        let bounds = plot_ui.plot_bounds();
+        let (min, max) = (bounds.min(), bounds.max());
+        let rect = plot_ui.response().rect;
+        if (rect.width() < 1.0) | (rect.height() < 1.0) {
+            return;
+        }
+
+        let rows_for = |columns: usize| {
+            ((columns as f32 * rect.height() / rect.width()).round() as usize).max(1)
+        };
+        let columns = (rect.width() as usize).min(MAX_COLUMNS);
+        let size = [columns, rows_for(columns)];
+
+        let key = (function.clone(), [min[0], min[1], max[0], max[1]], size);
+        if self.key.as_ref() != Some(&key) {
+            let image = Self::image(function, min, max, size);
+            match &mut self.texture {
+                Some(texture) => texture.set(image, TextureOptions::LINEAR),
+                None => {
+                    self.texture = Some(plot_ui.ctx().load_texture(
+                        "domain_coloring",
+                        image,
+                        TextureOptions::LINEAR,
+                    ))
+                }
+            }
+
+            self.zeros_and_poles = function.zeros_and_poles(
+                Complex::new(min[0], min[1]),
+                Complex::new(max[0], max[1]),
+                [SEARCH_COLUMNS, rows_for(SEARCH_COLUMNS)],
+            );
+            self.key = Some(key);
+        }
+
+        if let Some(texture) = &self.texture {
+            plot_ui.image(PlotImage::new(
+                "",
+                texture.id(),
+                PlotPoint::new((min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0),
+                vec2(bounds.width() as f32, bounds.height() as f32),
+            ));
+        }
+
+        for found in self.zeros_and_poles.iter() {
+            let point = PlotPoint::new(found.z.re, found.z.im);
+            plot_ui.points(
+                Points::new("", vec![[point.x, point.y]])
+                    .color(Color32::WHITE)
+                    .filled(false)
+                    .radius(8.0),
+            );
+            plot_ui.text(
+                Text::new("", point, label(found))
+                    .anchor(Align2::LEFT_BOTTOM)
+                    .color(Color32::WHITE),
+            );
+        }
+    }
+
+    /// Image of the domain coloring of `function` from `min` to `max` with `size` pixels, where
+    /// each pixel is colored by the function's value at its center
+    fn image(
+        function: &ComplexFunction,
+        min: [f64; 2],
+        max: [f64; 2],
+        size: [usize; 2],
+    ) -> ColorImage {
+        let [columns, rows] = size;
+        let step = [
+            (max[0] - min[0]) / columns as f64,
+            (max[1] - min[1]) / rows as f64,
+        ];
+
+        // The first row of the image is the top of the plot
+        let rgba: Vec<u8> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .flat_map(|(row, column)| {
+                let z = Complex::new(
+                    min[0] + (column as f64 + 0.5) * step[0],
+                    max[1] - (row as f64 + 0.5) * step[1],
+                );
+                domain_color(function.eval(z)).to_srgba_unmultiplied()
+            })
+            .collect();
+        ColorImage::from_rgba_unmultiplied(size, &rgba)
+    }
+}
@@ -28,6 +28,9 @@ const_assert!(DEFAULT_MAX_X > DEFAULT_MIN_X);
 /// Default number of integral boxes
 pub const DEFAULT_INTEGRAL_NUM: usize = 100;
 
+/// Highest degree of Taylor polynomials that can be displayed
+pub const MAX_TAYLOR_DEGREE: usize = 10;
+
 /// Colors used for plotting
 // Colors commented out are used elsewhere and are not included here for better user experience
 pub const COLORS: [Color32; 13] = [
@@ -0,0 +1,252 @@
+use crate::{
+    exact::{format_complex, format_exact},
+    function_manager::FunctionManager,
+    hover::special_points,
+    widgets::{ExpressionInput, error_indicator},
+};
+use egui::{Button, Checkbox, Context, DragValue, Grid, ScrollArea, Ui, Window};
+
+/// Number of rows the value table is limited to
+const MAX_TABLE_ROWS: usize = 1000;
+
+/// Writes `table` (rows of cells) as CSV following RFC 4180, where cells containing commas,
+/// quotes, or line breaks (such as the label `atan2(x, 1)`) are quoted, with their quotes doubled
+pub fn to_csv(table: &[Vec<String>]) -> String {
+    let cell = |cell: &String| match cell.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", cell.replace('"', "\"\"")),
+        false => cell.clone(),
+    };
+
+    table
+        .iter()
+        .map(|row| row.iter().map(cell).collect::<Vec<String>>().join(","))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+/// Displays `table` (rows of cells, starting with a header row) along with a button to copy it
+fn table_grid(ui: &mut Ui, id: &str, table: &[Vec<String>]) {
+    if ui
+        .button("Copy as CSV")
+        .on_hover_text("Copy the table to the clipboard")
+        .clicked()
+    {
+        ui.ctx().copy_text(to_csv(table));
+    }
+
+    ScrollArea::vertical()
+        .id_salt(id)
+        .max_height(300.0)
+        .show(ui, |ui| {
+            Grid::new(id).striped(true).show(ui, |ui| {
+                for row in table.iter() {
+                    for cell in row.iter() {
+                        ui.label(cell);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+/// Window that evaluates every function at a chosen `x` and builds tables of values
+pub struct Evaluator {
+    /// `x` value to evaluate functions at
+    x_input: ExpressionInput,
+
+    /// If markers should be drawn on each function at `x`
+    pub show_markers: bool,
+
+    /// `x` value the plot was requested to move to
+    pan_request: Option<f64>,
+
+    /// First `x` value of the table
+    table_start: ExpressionInput,
+
+    /// Distance between `x` values of the table
+    table_step: ExpressionInput,
+
+    /// Number of rows in the table
+    table_count: usize,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self {
+            x_input: ExpressionInput::new(0.0),
+            show_markers: true,
+            pan_request: None,
+            table_start: ExpressionInput::new(0.0),
+            table_step: ExpressionInput::new(1.0),
+            table_count: 10,
+        }
+    }
+}
+
+impl Evaluator {
+    /// `x` value markers should be drawn at
+    pub fn marker_x(&self) -> Option<f64> {
+        (self.show_markers && self.x_input.error().is_none()).then_some(self.x_input.value())
+    }
+
+    /// Takes the `x` value the plot should be moved to, if one was requested
+    pub fn take_pan_request(&mut self) -> Option<f64> {
+        self.pan_request.take()
+    }
+
+    /// Displays the evaluation window
+    pub fn window(&mut self, ctx: &Context, open: &mut bool, functions: &mut FunctionManager) {
+        Window::new("Evaluate")
+            .open(open)
+            .default_pos([200.0, 200.0])
+            .resizable(true)
+            .collapsible(true)
+            .show(ctx, |ui| {
+                let symbols = functions.constant_symbols().clone();
+
+                ui.horizontal(|ui| {
+                    ui.label("x =");
+                    if let Some(x) = self.x_input.show(ui, &symbols) {
+                        self.x_input.set_value(x);
+                    }
+
+                    if ui
+                        .add_enabled(self.x_input.error().is_none(), Button::new("Go to"))
+                        .on_hover_text("Move the graph to this x value")
+                        .clicked()
+                    {
+                        self.pan_request = Some(self.x_input.value());
+                    }
+
+                    ui.add(Checkbox::new(&mut self.show_markers, "Markers"));
+                });
+
+                if let Some(error) = self.x_input.error() {
+                    error_indicator(ui, error);
+                } else {
+                    let x = self.x_input.value();
+                    Grid::new("evaluate_values").striped(true).show(ui, |ui| {
+                        ui.label("Function");
+                        ui.label("f(x)");
+                        ui.label("f'(x)");
+                        ui.label("f''(x)");
+                        ui.end_row();
+
+                        for (_, function) in functions.get_entries_mut().iter_mut() {
+                            if !function.can_evaluate() {
+                                continue;
+                            }
+
+                            ui.label(function.label());
+                            for derivative in 0..=2 {
+                                ui.label(format_exact(
+                                    function.eval(derivative, x).unwrap_or(f64::NAN),
+                                ));
+                            }
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                ui.separator();
+
+                ui.collapsing("Table", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Start:");
+                        if let Some(start) = self.table_start.show(ui, &symbols) {
+                            self.table_start.set_value(start);
+                        }
+                        ui.label("Step:");
+                        if let Some(step) = self.table_step.show(ui, &symbols) {
+                            self.table_step.set_value(step);
+                        }
+                        ui.label("Rows:");
+                        ui.add(DragValue::new(&mut self.table_count).range(1..=MAX_TABLE_ROWS));
+                    });
+
+                    if let Some(error) = self.table_start.error().or(self.table_step.error()) {
+                        error_indicator(ui, error);
+                        return;
+                    }
+
+                    table_grid(ui, "evaluate_table", &self.table(functions));
+                });
+
+                ui.collapsing("Points", |ui| {
+                    table_grid(ui, "evaluate_points", &Self::points_table(functions));
+                });
+
+                ui.collapsing("Polynomial Roots", |ui| {
+                    ui.label("Every root of each polynomial, including complex roots and those outside of the graph.");
+                    table_grid(ui, "evaluate_roots", &Self::roots_table(functions));
+                });
+            });
+    }
+
+    /// Builds the table of values as rows of cells, starting with a header row
+    fn table(&self, functions: &mut FunctionManager) -> Vec<Vec<String>> {
+        let start = self.table_start.value();
+        let step = self.table_step.value();
+
+        let mut header = vec!["x".to_owned()];
+        let mut rows: Vec<Vec<String>> = (0..self.table_count)
+            .map(|i| vec![format_exact(start + (i as f64 * step))])
+            .collect();
+
+        for (_, function) in functions.get_entries_mut().iter_mut() {
+            if !function.can_evaluate() {
+                continue;
+            }
+
+            header.push(function.label());
+            for (i, row) in rows.iter_mut().enumerate() {
+                let x = start + (i as f64 * step);
+                row.push(format_exact(function.eval(0, x).unwrap_or(f64::NAN)));
+            }
+        }
+
+        rows.insert(0, header);
+        rows
+    }
+
+    /// Builds a table of every root of the functions that are polynomials, starting with a header
+    /// row
+    fn roots_table(functions: &FunctionManager) -> Vec<Vec<String>> {
+        let header = vec![
+            "Function".to_owned(),
+            "Root".to_owned(),
+            "Multiplicity".to_owned(),
+        ];
+        let mut rows = vec![header];
+        for (_, function) in functions.get_entries().iter() {
+            for root in function.polynomial_roots().unwrap_or_default() {
+                rows.push(vec![
+                    function.label(),
+                    format_complex(root.re, root.im),
+                    root.multiplicity.to_string(),
+                ]);
+            }
+        }
+        rows
+    }
+
+    /// Builds a table of every calculated root and extremum, starting with a header row
+    fn points_table(functions: &mut FunctionManager) -> Vec<Vec<String>> {
+        let header = vec!["Point".to_owned(), "x".to_owned(), "y".to_owned()];
+        std::iter::once(header)
+            .chain(
+                functions
+                    .get_entries_mut()
+                    .iter_mut()
+                    .flat_map(|(_, function)| special_points(function, true, true))
+                    .map(|snap| {
+                        vec![
+                            snap.title(),
+                            format_exact(snap.point.x),
+                            format_exact(snap.point.y),
+                        ]
+                    }),
+            )
+            .collect()
+    }
+}
@@ -0,0 +1,288 @@
+use std::f64::consts::{E, PI};
+use std::fmt;
+
+/// Relative tolerance a value must be within to be recognized
+const TOLERANCE: f64 = 1e-9;
+
+/// Largest denominator of plain fractions that are recognized (such as `1/3`)
+const MAX_RATIONAL_DENOMINATOR: u64 = 1000;
+
+/// Largest denominator of fractions of symbols that are recognized (such as `π/12`)
+const MAX_SYMBOL_DENOMINATOR: u64 = 12;
+
+/// Largest radicand of square roots that are recognized (such as `√2`)
+const MAX_RADICAND: u64 = 100;
+
+/// Returns the greatest common divisor of `a` and `b`
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns true if `a` is within [`TOLERANCE`] of `b`
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(1.0)
+}
+
+/// Finds the fraction closest to `x` whose denominator is at most `max_denominator` by computing
+/// the convergents of `x`'s continued fraction. Returns the numerator and denominator.
+pub fn rational_approximation(x: f64, max_denominator: u64) -> Option<(i64, u64)> {
+    if !x.is_finite() || (x.abs() > (i64::MAX / 2) as f64) {
+        return None;
+    }
+
+    // Convergents `h/k`, starting with the two seeds of the recurrence
+    let (mut h_prev, mut h) = (1_i64, x.floor() as i64);
+    let (mut k_prev, mut k) = (0_u64, 1_u64);
+    let mut remainder = x - x.floor();
+
+    while remainder > f64::EPSILON {
+        let inverse = 1.0 / remainder;
+        let term = inverse.floor();
+        if term > max_denominator as f64 {
+            break;
+        }
+
+        let next_k = (term as u64) * k + k_prev;
+        if next_k > max_denominator {
+            break;
+        }
+
+        (h_prev, h) = (h, (term as i64) * h + h_prev);
+        (k_prev, k) = (k, next_k);
+        remainder = inverse - term;
+
+        if close(x, h as f64 / k as f64) {
+            break;
+        }
+    }
+
+    Some((h, k))
+}
+
+/// Symbols that values can be recognized as multiples of
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Symbol {
+    Pi,
+    E,
+}
+
+impl Symbol {
+    const fn value(self) -> f64 {
+        match self {
+            Symbol::Pi => PI,
+            Symbol::E => E,
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Symbol::Pi => "π",
+            Symbol::E => "e",
+        }
+    }
+}
+
+/// A value recognized as an exact expression. Every variant stores its magnitude, with the sign
+/// kept in `negative`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum ExactValue {
+    /// `numerator/denominator`
+    Rational {
+        negative: bool,
+        numerator: u64,
+        denominator: u64,
+    },
+
+    /// `numerator·symbol/denominator` (such as `3π/4`)
+    Multiple {
+        negative: bool,
+        numerator: u64,
+        denominator: u64,
+        symbol: Symbol,
+    },
+
+    /// `coefficient·√radicand/denominator` (such as `3√2/2`)
+    Sqrt {
+        negative: bool,
+        coefficient: u64,
+        radicand: u64,
+        denominator: u64,
+    },
+}
+
+/// Writes `numerator·symbol/denominator` the way it would be written by hand
+fn write_fraction(
+    f: &mut fmt::Formatter,
+    negative: bool,
+    numerator: u64,
+    symbol: &str,
+    denominator: u64,
+) -> fmt::Result {
+    if negative {
+        write!(f, "-")?;
+    }
+
+    // A coefficient of 1 is left out when there is a symbol to multiply
+    if (numerator != 1) | symbol.is_empty() {
+        write!(f, "{}", numerator)?;
+    }
+
+    write!(f, "{}", symbol)?;
+
+    if denominator != 1 {
+        write!(f, "/{}", denominator)?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for ExactValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExactValue::Rational {
+                negative,
+                numerator,
+                denominator,
+            } => write_fraction(f, negative, numerator, "", denominator),
+            ExactValue::Multiple {
+                negative,
+                numerator,
+                denominator,
+                symbol,
+            } => write_fraction(f, negative, numerator, symbol.as_str(), denominator),
+            ExactValue::Sqrt {
+                negative,
+                coefficient,
+                radicand,
+                denominator,
+            } => write_fraction(
+                f,
+                negative,
+                coefficient,
+                &format!("√{}", radicand),
+                denominator,
+            ),
+        }
+    }
+}
+
+/// Splits `n` into `(s, r)` such that `n = s²·r` where `r` has no square factors
+fn split_square(n: u64) -> (u64, u64) {
+    let mut square = 1;
+    let mut rest = n;
+    let mut i = 2;
+    while i * i <= rest {
+        while rest.is_multiple_of(i * i) {
+            square *= i;
+            rest /= i * i;
+        }
+        i += 1;
+    }
+    (square, rest)
+}
+
+/// Attempts to recognize `x` as a fraction, a fraction of π or e, or a multiple of a square root.
+/// Integers aren't recognized as they are already exact.
+pub fn recognize(x: f64) -> Option<ExactValue> {
+    if !x.is_finite() || close(x, x.round()) {
+        return None;
+    }
+
+    let negative = x.is_sign_negative();
+    let abs = x.abs();
+
+    // Plain fractions (such as `1/3`)
+    if let Some((numerator, denominator)) = rational_approximation(abs, MAX_RATIONAL_DENOMINATOR)
+        && close(abs, numerator as f64 / denominator as f64)
+    {
+        return Some(ExactValue::Rational {
+            negative,
+            numerator: numerator as u64,
+            denominator,
+        });
+    }
+
+    // Fractions and multiples of symbols (such as `π/2` or `2e`)
+    for symbol in [Symbol::Pi, Symbol::E] {
+        let ratio = abs / symbol.value();
+        if let Some((numerator, denominator)) =
+            rational_approximation(ratio, MAX_SYMBOL_DENOMINATOR)
+            && numerator > 0
+            && close(ratio, numerator as f64 / denominator as f64)
+        {
+            return Some(ExactValue::Multiple {
+                negative,
+                numerator: numerator as u64,
+                denominator,
+                symbol,
+            });
+        }
+    }
+
+    // Square roots (such as `√2` or `√3/2`): x² = p/q means x = √(pq)/q
+    if let Some((p, q)) = rational_approximation(abs * abs, MAX_SYMBOL_DENOMINATOR)
+        && p > 0
+        && close(abs * abs, p as f64 / q as f64)
+    {
+        let (square, radicand) = split_square(p as u64 * q);
+        if (radicand > 1) && (radicand <= MAX_RADICAND) {
+            let divisor = gcd(square, q);
+            return Some(ExactValue::Sqrt {
+                negative,
+                coefficient: square / divisor,
+                radicand,
+                denominator: q / divisor,
+            });
+        }
+    }
+
+    None
+}
+
+/// Attempts to see what constant `x` is almost (such as `π/2`, `2e`, `1/3`, or `√2`), returning how
+/// it would be written
+pub fn almost_variable(x: f64) -> Option<String> {
+    recognize(x).map(|exact| exact.to_string())
+}
+
+/// Formats `x` for display. Values that are short decimals are written as such, otherwise they are
+/// written as a recognized constant if possible.
+pub fn format_exact(x: f64) -> String {
+    if !x.is_finite() {
+        return "undefined".to_owned();
+    }
+
+    // Adding zero turns `-0` into `0`
+    let rounded = emath::round_to_decimals(x, 6) + 0.0;
+    if rounded == x {
+        return rounded.to_string();
+    }
+
+    almost_variable(x).unwrap_or_else(|| rounded.to_string())
+}
+
+/// Writes `coefficient·term`, leaving out a coefficient of 1 and wrapping fractions in parenthesis so
+/// `(1/3)x` isn't read as `1/(3x)`
+pub fn format_term(coefficient: f64, term: &str) -> String {
+    match format_exact(coefficient).as_str() {
+        "1" => term.to_owned(),
+        "-1" => format!("-{}", term),
+        exact if exact.contains('/') => format!("({}){}", exact, term),
+        exact => format!("{}{}", exact, term),
+    }
+}
+
+/// Writes the complex number `re + im·i` as `a + bi`, leaving out parts that are zero
+pub fn format_complex(re: f64, im: f64) -> String {
+    let imaginary = format_term(im.abs(), "i");
+    match (re, im) {
+        (re, 0.0) => format_exact(re),
+        (0.0, im) if im < 0.0 => format!("-{}", imaginary),
+        (0.0, _) => imaginary,
+        (re, im) if im < 0.0 => format!("{} - {}", format_exact(re), imaginary),
+        (re, _) => format!("{} + {}", format_exact(re), imaginary),
+    }
+}
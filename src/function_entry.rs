@@ -1,17 +1,39 @@
+use crate::consts::{COLORS, MAX_TAYLOR_DEGREE};
+use crate::exact::format_exact;
 use crate::math_app::AppSettings;
-use crate::misc::{EguiHelper, newtons_method_helper, step_helper};
-use egui::{Checkbox, Context};
-use egui_plot::{Bar, BarChart, PlotPoint, PlotUi};
+use crate::misc::{
+    EguiHelper, accumulate, interpolate, newtons_method_helper, palette_color, step_helper,
+    trapezoidal_integral,
+};
+use crate::quadrature::{find_singularities, format_area, improper_integral};
+use crate::tangent::TangentLine;
+use crate::taylor::TaylorPolynomial;
+use egui::{Button, Checkbox, ComboBox, Context, DragValue};
+use egui_plot::{Bar, BarChart, LineStyle, PlotPoint, PlotUi};
 
 use epaint::Color32;
-use parsing::{AutoComplete, generate_hint};
-use parsing::{BackingFunction, process_func_str};
+use parsing::AutoComplete;
+use parsing::{
+    BackingFunction, ComplexFunction, Definition, Expr, MAX_DERIVATIVE, ParseError, ParseErrorKind,
+    Rewrite, Root, SymbolTable, asciimath_to_plain, is_latex, latex_to_plain, parse_definition,
+    parse_expression, process_definition, referenced_names,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, ser::SerializeStruct};
 use std::{
+    borrow::Cow,
     fmt::{self, Debug},
-    hash::{Hash, Hasher},
+    hash::{DefaultHasher, Hash, Hasher},
 };
 
+/// Returns true if `function` is zero at `x`, relative to its size around `x`
+fn is_root(function: &mut BackingFunction, x: f64) -> bool {
+    let scale = [x - 1.0, x + 1.0]
+        .into_iter()
+        .map(|x| function.get(0, x).abs())
+        .fold(1.0, f64::max);
+    function.get(0, x).abs() <= 1e-6 * scale
+}
+
 /// Represents the possible variations of Riemann Sums
 #[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
 pub enum Riemann {
@@ -28,6 +50,111 @@ impl fmt::Display for Riemann {
     }
 }
 
+/// Represents the dash pattern used when drawing a function's line
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default, Hash, Serialize, Deserialize)]
+pub enum LineDash {
+    #[default]
+    Solid,
+
+    Dashed,
+    Dotted,
+}
+
+impl fmt::Display for LineDash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl LineDash {
+    /// Converts to egui_plot's `LineStyle`, scaling the pattern with the line's `width`
+    fn to_line_style(self, width: f32) -> LineStyle {
+        match self {
+            LineDash::Solid => LineStyle::Solid,
+            LineDash::Dashed => LineStyle::Dashed {
+                length: width * 4.0,
+            },
+            LineDash::Dotted => LineStyle::Dotted {
+                spacing: width * 2.5,
+            },
+        }
+    }
+}
+
+/// Syntax a function is written in
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default, Hash, Serialize, Deserialize)]
+pub enum InputSyntax {
+    /// Plain syntax, or LaTeX if it looks like LaTeX (contains `\` or `{`)
+    #[default]
+    Auto,
+
+    LaTeX,
+    AsciiMath,
+}
+
+impl fmt::Display for InputSyntax {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl InputSyntax {
+    /// Converts `raw` from this syntax into the plain syntax functions are parsed from
+    fn to_plain(self, raw: &str) -> Cow<'_, str> {
+        match self {
+            InputSyntax::Auto if is_latex(raw) => Cow::Owned(latex_to_plain(raw)),
+            InputSyntax::Auto => Cow::Borrowed(raw),
+            InputSyntax::LaTeX => Cow::Owned(latex_to_plain(raw)),
+            InputSyntax::AsciiMath => Cow::Owned(asciimath_to_plain(raw)),
+        }
+    }
+}
+
+/// Default color of the `order`th derivative, picked from past the end of [`COLORS`] so that it
+/// is unlikely to match the color of a function
+fn nth_derivative_color(order: usize) -> Color32 {
+    palette_color(COLORS.len() + order)
+}
+
+/// A derivative of the function displayed along with it
+#[derive(Clone)]
+pub struct NthDerivative {
+    pub order: usize,
+    pub color: Color32,
+
+    /// Calculated values, `None` if they need to be recalculated
+    data: Option<Vec<PlotPoint>>,
+}
+
+impl NthDerivative {
+    pub const fn new(order: usize, color: Color32) -> Self {
+        Self {
+            order,
+            color,
+            data: None,
+        }
+    }
+}
+
+impl Hash for NthDerivative {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.order.hash(state);
+        self.color.hash(state);
+    }
+}
+
+/// Default width of a function's line
+pub const DEFAULT_LINE_WIDTH: f32 = 4.0;
+
+/// Default degree of a function's Taylor polynomial
+const DEFAULT_TAYLOR_DEGREE: usize = 3;
+
+/// Most trapezoids used to integrate from the accumulation function's lower limit to the plot
+const MAX_ACCUMULATION_STEPS: usize = 100_000;
+
+/// `x` values a rewritten definition is evaluated at to check it's still the same function
+const REWRITE_CHECK_XS: [f64; 6] = [-2.7, -1.3, -0.4, 0.6, 1.9, 3.1];
+
 /// `FunctionEntry` is a function that can calculate values, integrals, derivatives, etc etc
 #[derive(Clone)]
 pub struct FunctionEntry {
@@ -37,40 +164,144 @@ pub struct FunctionEntry {
     /// Stores a function string (that hasn't been processed via `process_func_str`) to display to the user
     pub raw_func_str: String,
 
+    /// What `raw_func_str` defines, `None` if it couldn't be parsed
+    definition: Option<Definition>,
+
+    /// `raw_func_str` in canonical form (such as `2·sin(x)·x` for `2sin(x)x`), or trimmed if it
+    /// couldn't be parsed
+    canonical_func_str: String,
+
+    /// Body of `definition` after being processed via `process_definition`
+    processed_func_str: String,
+
+    /// Names referenced by `processed_func_str`
+    references: Vec<String>,
+
+    /// String `function` was last built from (`processed_func_str` with user definitions expanded)
+    expanded_func_str: Option<String>,
+
+    /// User definitions `expanded_func_str` was expanded with, which rewrites are expanded with too
+    symbols: SymbolTable,
+
+    /// Rewrite that was last refused because it would have changed the function
+    rejected_rewrite: Option<Rewrite>,
+
+    /// Every root of the function (including complex ones) if it's a polynomial, which are found
+    /// exactly instead of with Newton's method
+    polynomial_roots: Option<Vec<Root>>,
+
+    /// The function of a complex variable (`z`, or the function's parameter) graphed in complex mode
+    complex_function: Option<ComplexFunction>,
+
+    /// Error from parsing `raw_func_str` as a complex function, with its spans into `raw_func_str`
+    complex_error: Option<ParseError>,
+
     /// If calculating/displayingintegrals are enabled
     pub integral: bool,
 
     /// If displaying derivatives are enabled (note, they are still calculated for other purposes)
     pub derivative: bool,
 
-    pub nth_derviative: bool,
+    /// Higher order derivatives displayed along with the function
+    pub nth_derivatives: Vec<NthDerivative>,
 
     /// If the function is visible on the graph
     pub visible: bool,
 
+    /// Color the function is drawn with
+    pub color: Color32,
+
+    /// Width of the function's line
+    pub line_width: f32,
+
+    /// Dash pattern of the function's line
+    pub line_dash: LineDash,
+
+    /// Syntax `raw_func_str` is written in
+    input_syntax: InputSyntax,
+
+    /// If the tangent line at `tangent_x` is displayed
+    pub tangent: bool,
+
+    /// If the normal line at `tangent_x` is displayed along with the tangent line
+    pub normal: bool,
+
+    /// `x` value the tangent line touches the function at
+    pub tangent_x: f64,
+
+    /// If the Taylor polynomial about `taylor_center` is displayed
+    pub taylor: bool,
+
+    /// If the truncation error of the Taylor polynomial (`f(x) - P(x)`) is displayed
+    pub taylor_error: bool,
+
+    /// `x` value the Taylor polynomial is centered about
+    pub taylor_center: f64,
+
+    /// Degree of the Taylor polynomial
+    pub taylor_degree: usize,
+
+    /// If the accumulation function `F(x) = ∫ f(t) dt` from `accumulation_start` to `x` is displayed
+    pub accumulation: bool,
+
+    /// Lower limit of the accumulation function's integral
+    pub accumulation_start: f64,
+
     pub back_data: Vec<PlotPoint>,
     pub integral_data: Option<(Vec<Bar>, f64)>,
     pub derivative_data: Vec<PlotPoint>,
     pub extrema_data: Vec<PlotPoint>,
     pub root_data: Vec<PlotPoint>,
-    nth_derivative_data: Option<Vec<PlotPoint>>,
+    taylor_polynomial: Option<TaylorPolynomial>,
+    taylor_data: Option<Vec<PlotPoint>>,
+    taylor_error_data: Option<Vec<PlotPoint>>,
+    accumulation_data: Option<Vec<PlotPoint>>,
 
     pub autocomplete: AutoComplete<'static>,
 
-    test_result: Option<String>,
-    curr_nth: usize,
+    /// Error from parsing `raw_func_str`, with its spans into `raw_func_str`
+    test_result: Option<ParseError>,
 
     pub settings_opened: bool,
 }
 
 impl Hash for FunctionEntry {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.raw_func_str.hash(state);
-        self.integral.hash(state);
-        self.nth_derviative.hash(state);
-        self.curr_nth.hash(state);
+        self.hash_drawing(state);
         self.settings_opened.hash(state);
+    }
+}
+
+impl FunctionEntry {
+    /// Hashes everything that affects how the function is drawn: its canonical form and the
+    /// settings of what is drawn with it
+    fn hash_drawing<H: Hasher>(&self, state: &mut H) {
+        self.canonical_func_str.hash(state);
+        self.integral.hash(state);
+        self.derivative.hash(state);
+        self.nth_derivatives.hash(state);
         self.visible.hash(state);
+        self.color.hash(state);
+        self.line_width.to_bits().hash(state);
+        self.line_dash.hash(state);
+        self.input_syntax.hash(state);
+        self.tangent.hash(state);
+        self.normal.hash(state);
+        self.tangent_x.to_bits().hash(state);
+        self.taylor.hash(state);
+        self.taylor_error.hash(state);
+        self.taylor_center.to_bits().hash(state);
+        self.taylor_degree.hash(state);
+        self.accumulation.hash(state);
+        self.accumulation_start.to_bits().hash(state);
+    }
+
+    /// Hash of the function's canonical form and drawing settings, so functions with the same hash
+    /// are drawn identically
+    pub fn drawing_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_drawing(&mut hasher);
+        hasher.finish()
     }
 }
 
@@ -79,56 +310,135 @@ impl Serialize for FunctionEntry {
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("FunctionEntry", 5)?;
+        let mut s = serializer.serialize_struct("FunctionEntry", 18)?;
         s.serialize_field("raw_func_str", &self.raw_func_str)?;
         s.serialize_field("integral", &self.integral)?;
         s.serialize_field("derivative", &self.derivative)?;
-        s.serialize_field("curr_nth", &self.curr_nth)?;
+        s.serialize_field(
+            "nth_derivatives",
+            &self
+                .nth_derivatives
+                .iter()
+                .map(|nth| (nth.order, nth.color.to_array()))
+                .collect::<Vec<(usize, [u8; 4])>>(),
+        )?;
         s.serialize_field("visible", &self.visible)?;
+        s.serialize_field("color", &self.color.to_array())?;
+        s.serialize_field("line_width", &self.line_width)?;
+        s.serialize_field("line_dash", &self.line_dash)?;
+        s.serialize_field("input_syntax", &self.input_syntax)?;
+        s.serialize_field("tangent", &self.tangent)?;
+        s.serialize_field("normal", &self.normal)?;
+        s.serialize_field("tangent_x", &self.tangent_x)?;
+        s.serialize_field("taylor", &self.taylor)?;
+        s.serialize_field("taylor_error", &self.taylor_error)?;
+        s.serialize_field("taylor_center", &self.taylor_center)?;
+        s.serialize_field("taylor_degree", &self.taylor_degree)?;
+        s.serialize_field("accumulation", &self.accumulation)?;
+        s.serialize_field("accumulation_start", &self.accumulation_start)?;
 
         s.end()
     }
 }
 
-impl<'de> Deserialize<'de> for FunctionEntry {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        struct Helper {
-            raw_func_str: String,
-            integral: bool,
-            derivative: bool,
-            curr_nth: usize,
-            #[serde(default = "default_visible")]
-            visible: bool,
-        }
+/// A [`FunctionEntry`] as it's saved. Fields added after the first saves have defaults, and the
+/// color is left for the [`FunctionManager`](crate::function_manager::FunctionManager) to pick
+/// when it's missing, since it depends on the entry's position.
+#[derive(Deserialize)]
+pub(crate) struct SavedFunctionEntry {
+    raw_func_str: String,
+    integral: bool,
+    derivative: bool,
+    #[serde(default)]
+    nth_derivatives: Vec<(usize, [u8; 4])>,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    color: [u8; 4],
+    #[serde(default = "default_line_width")]
+    line_width: f32,
+    #[serde(default)]
+    line_dash: LineDash,
+    #[serde(default)]
+    input_syntax: InputSyntax,
+    #[serde(default)]
+    tangent: bool,
+    #[serde(default)]
+    normal: bool,
+    #[serde(default)]
+    tangent_x: f64,
+    #[serde(default)]
+    taylor: bool,
+    #[serde(default)]
+    taylor_error: bool,
+    #[serde(default)]
+    taylor_center: f64,
+    #[serde(default = "default_taylor_degree")]
+    taylor_degree: usize,
+    #[serde(default)]
+    accumulation: bool,
+    #[serde(default)]
+    accumulation_start: f64,
+}
 
-        fn default_visible() -> bool {
-            true
-        }
+fn default_visible() -> bool {
+    true
+}
 
-        let helper = Helper::deserialize(deserializer)?;
-        let mut new_func_entry = FunctionEntry::default();
-        let gen_func = BackingFunction::new(&helper.raw_func_str);
-        match gen_func {
-            Ok(func) => new_func_entry.function = func,
-            Err(x) => new_func_entry.test_result = Some(x),
-        }
+fn default_line_width() -> f32 {
+    DEFAULT_LINE_WIDTH
+}
+
+fn default_taylor_degree() -> usize {
+    DEFAULT_TAYLOR_DEGREE
+}
 
-        new_func_entry.autocomplete = AutoComplete {
-            i: 0,
-            hint: generate_hint(&helper.raw_func_str),
-            string: helper.raw_func_str,
+impl SavedFunctionEntry {
+    /// Rebuilds the entry that was saved
+    pub(crate) fn into_entry(self) -> FunctionEntry {
+        let mut new_func_entry = FunctionEntry {
+            input_syntax: self.input_syntax,
+            ..FunctionEntry::default()
         };
+        new_func_entry.update_string(&self.raw_func_str);
 
-        new_func_entry.integral = helper.integral;
-        new_func_entry.derivative = helper.derivative;
-        new_func_entry.curr_nth = helper.curr_nth;
-        new_func_entry.visible = helper.visible;
+        new_func_entry.autocomplete = AutoComplete::new(self.raw_func_str);
 
-        Ok(new_func_entry)
+        new_func_entry.integral = self.integral;
+        new_func_entry.derivative = self.derivative;
+        new_func_entry.nth_derivatives = self
+            .nth_derivatives
+            .into_iter()
+            .map(|(order, [r, g, b, a])| {
+                NthDerivative::new(
+                    order.clamp(2, MAX_DERIVATIVE),
+                    Color32::from_rgba_premultiplied(r, g, b, a),
+                )
+            })
+            .collect();
+        new_func_entry.visible = self.visible;
+        let [r, g, b, a] = self.color;
+        new_func_entry.color = Color32::from_rgba_premultiplied(r, g, b, a);
+        new_func_entry.line_width = self.line_width;
+        new_func_entry.line_dash = self.line_dash;
+        new_func_entry.tangent = self.tangent;
+        new_func_entry.normal = self.normal;
+        new_func_entry.tangent_x = self.tangent_x;
+        new_func_entry.taylor = self.taylor;
+        new_func_entry.taylor_error = self.taylor_error;
+        new_func_entry.taylor_center = self.taylor_center;
+        new_func_entry.taylor_degree = self.taylor_degree.min(MAX_TAYLOR_DEGREE);
+        new_func_entry.accumulation = self.accumulation;
+        new_func_entry.accumulation_start = self.accumulation_start;
+        new_func_entry
+    }
+}
+
+impl<'de> Deserialize<'de> for FunctionEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SavedFunctionEntry::deserialize(deserializer).map(|saved| saved.into_entry())
     }
 }
 
@@ -138,19 +448,44 @@ impl Default for FunctionEntry {
         FunctionEntry {
             function: BackingFunction::default(),
             raw_func_str: String::new(),
+            definition: Some(Definition::Expression(String::new())),
+            canonical_func_str: String::new(),
+            processed_func_str: String::new(),
+            references: Vec::new(),
+            expanded_func_str: Some(String::new()),
+            symbols: SymbolTable::default(),
+            rejected_rewrite: None,
+            polynomial_roots: None,
             integral: false,
             derivative: false,
-            nth_derviative: false,
+            nth_derivatives: Vec::new(),
             visible: true,
+            color: palette_color(0),
+            line_width: DEFAULT_LINE_WIDTH,
+            line_dash: LineDash::default(),
+            input_syntax: InputSyntax::default(),
+            tangent: false,
+            normal: false,
+            tangent_x: 0.0,
+            taylor: false,
+            taylor_error: false,
+            taylor_center: 0.0,
+            taylor_degree: DEFAULT_TAYLOR_DEGREE,
+            accumulation: false,
+            accumulation_start: 0.0,
             back_data: Vec::new(),
             integral_data: None,
             derivative_data: Vec::new(),
             extrema_data: Vec::new(),
             root_data: Vec::new(),
-            nth_derivative_data: None,
+            taylor_polynomial: None,
+            taylor_data: None,
+            taylor_error_data: None,
+            accumulation_data: None,
             autocomplete: AutoComplete::EMPTY,
             test_result: None,
-            curr_nth: 3,
+            complex_function: None,
+            complex_error: None,
             settings_opened: false,
         }
     }
@@ -162,37 +497,415 @@ impl FunctionEntry {
     }
 
     pub fn settings_window(&mut self, ctx: &Context) {
-        let mut invalidate_nth = false;
-        egui::Window::new(format!("Settings: {}", self.raw_func_str))
+        let mut invalidate_taylor = false;
+        let mut invalidate_accumulation = false;
+        let mut input_syntax = self.input_syntax;
+        let mut rewrite = None;
+        let can_rewrite = self.definition.is_some();
+        let tangent = self.tangent_line();
+        let numeric: Vec<bool> = self
+            .nth_derivatives
+            .iter()
+            .map(|nth| self.function.is_numeric(nth.order))
+            .collect();
+        egui::Window::new(format!("Settings: {}", self.canonical_func_str))
             .open(&mut self.settings_opened)
             .default_pos([200.0, 200.0])
             .resizable(false)
             .collapsible(false)
             .show(ctx, |ui| {
-                ui.add(Checkbox::new(
-                    &mut self.nth_derviative,
-                    "Display Nth Derivative",
-                ));
+                ui.label("Higher Derivatives:");
+
+                let mut removed = None;
+                for (i, nth) in self.nth_derivatives.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgba(&mut nth.color);
+
+                        if ui
+                            .add(
+                                DragValue::new(&mut nth.order)
+                                    .range(2..=MAX_DERIVATIVE)
+                                    .prefix("Order: "),
+                            )
+                            .changed()
+                        {
+                            nth.data = None;
+                        }
+
+                        if numeric[i] {
+                            ui.label("(numerical)").on_hover_text(
+                                "This derivative couldn't be found exactly, so it is approximated",
+                            );
+                        }
+
+                        if ui.button("✖").on_hover_text("Remove Derivative").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+
+                if let Some(i) = removed {
+                    self.nth_derivatives.remove(i);
+                }
 
+                let order = self
+                    .nth_derivatives
+                    .iter()
+                    .map(|nth| nth.order + 1)
+                    .max()
+                    .unwrap_or(2);
                 if ui
-                    .add(egui::Slider::new(&mut self.curr_nth, 3..=5).text("Nth Derivative"))
-                    .changed()
+                    .add_enabled(order <= MAX_DERIVATIVE, Button::new("Add Derivative"))
+                    .clicked()
                 {
-                    invalidate_nth = true;
+                    self.nth_derivatives
+                        .push(NthDerivative::new(order, nth_derivative_color(order)));
                 }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Color:");
+                    ui.color_edit_button_srgba(&mut self.color);
+                });
+
+                ui.add(egui::Slider::new(&mut self.line_width, 0.5..=10.0).text("Line Width"));
+
+                ComboBox::from_label("Line Style")
+                    .selected_text(self.line_dash.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.line_dash, LineDash::Solid, "Solid");
+                        ui.selectable_value(&mut self.line_dash, LineDash::Dashed, "Dashed");
+                        ui.selectable_value(&mut self.line_dash, LineDash::Dotted, "Dotted");
+                    });
+
+                ComboBox::from_label("Input Syntax")
+                    .selected_text(input_syntax.to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut input_syntax, InputSyntax::Auto, "Auto");
+                        ui.selectable_value(&mut input_syntax, InputSyntax::LaTeX, "LaTeX");
+                        ui.selectable_value(&mut input_syntax, InputSyntax::AsciiMath, "AsciiMath");
+                    });
+
+                ui.add_enabled_ui(can_rewrite, |ui| {
+                    ui.horizontal(|ui| {
+                        for option in Rewrite::ALL {
+                            if ui
+                                .button(option.name())
+                                .on_hover_text(option.description())
+                                .clicked()
+                            {
+                                rewrite = Some(option);
+                            }
+                        }
+                    });
+                });
+
+                if let Some(rejected) = self.rejected_rewrite {
+                    ui.label(format!("{} would change the function", rejected.name()));
+                }
+
+                ui.separator();
+
+                ui.add(Checkbox::new(&mut self.tangent, "Display Tangent Line"));
+
+                ui.add_enabled_ui(self.tangent, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("At x =");
+                        ui.add(DragValue::new(&mut self.tangent_x).speed(0.1));
+                    });
+
+                    ui.add(Checkbox::new(&mut self.normal, "Display Normal Line"));
+                });
+
+                if let Some(tangent) = tangent {
+                    ui.label(format!("Tangent: {}", tangent.equation()));
+                    if self.normal {
+                        ui.label(format!("Normal: {}", tangent.normal_equation()));
+                    }
+                }
+
+                ui.separator();
+
+                ui.add(Checkbox::new(&mut self.taylor, "Display Taylor Polynomial"));
+
+                ui.add_enabled_ui(self.taylor, |ui| {
+                    invalidate_taylor |= ui
+                        .add(
+                            egui::Slider::new(&mut self.taylor_degree, 0..=MAX_TAYLOR_DEGREE)
+                                .text("Degree"),
+                        )
+                        .changed();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Center:");
+                        invalidate_taylor |= ui
+                            .add(DragValue::new(&mut self.taylor_center).speed(0.1))
+                            .changed();
+                    });
+
+                    ui.add(Checkbox::new(
+                        &mut self.taylor_error,
+                        "Display Truncation Error",
+                    ));
+                });
+
+                if self.taylor
+                    && let Some(ref polynomial) = self.taylor_polynomial
+                {
+                    ui.label(polynomial.to_string());
+                }
+
+                ui.separator();
+
+                ui.add(Checkbox::new(
+                    &mut self.accumulation,
+                    "Display Accumulation Function",
+                ));
+
+                ui.add_enabled_ui(self.accumulation, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Lower Limit:");
+                        invalidate_accumulation |= ui
+                            .add(DragValue::new(&mut self.accumulation_start).speed(0.1))
+                            .changed();
+                    });
+                });
             });
 
-        if invalidate_nth {
-            self.function.generate_derivative(self.curr_nth);
-            self.clear_nth();
+        if invalidate_taylor {
+            self.clear_taylor();
         }
+
+        if invalidate_accumulation {
+            self.clear_accumulation();
+        }
+
+        if input_syntax != self.input_syntax {
+            self.set_input_syntax(input_syntax);
+        }
+
+        if let Some(rewrite) = rewrite {
+            self.rewrite(rewrite);
+        }
+    }
+
+    /// Replaces `raw_func_str` with its definition simplified, expanded, or factored
+    pub fn rewrite(&mut self, rewrite: Rewrite) {
+        let Some(Ok(rewritten)) = self
+            .definition
+            .as_ref()
+            .map(|definition| definition.rewritten(rewrite))
+        else {
+            return;
+        };
+
+        // The rewriter and exmex could read a definition differently, so the rewrite is only kept
+        // if it's still the same function
+        if !self.evaluates_same(&rewritten) {
+            self.rejected_rewrite = Some(rewrite);
+            return;
+        }
+
+        // The rewritten definition is written in the plain syntax, whatever it was entered in
+        self.input_syntax = InputSyntax::Auto;
+        self.autocomplete.update_string(&rewritten);
+        self.update_string(&rewritten);
+    }
+
+    /// Returns true if `rewritten` evaluates the same as the function at `REWRITE_CHECK_XS`
+    fn evaluates_same(&mut self, rewritten: &str) -> bool {
+        if self.test_result.is_some() {
+            return false;
+        }
+
+        let Some(mut function) = parse_definition(rewritten)
+            .ok()
+            .and_then(|definition| self.symbols.expand(&process_definition(&definition)).ok())
+            .and_then(|expanded| BackingFunction::new(&expanded).ok())
+        else {
+            return false;
+        };
+
+        REWRITE_CHECK_XS.iter().all(|&x| {
+            let (expected, actual) = (self.function.get(0, x), function.get(0, x));
+            (expected == actual)
+                || (expected.is_nan() && actual.is_nan())
+                || ((expected - actual).abs() <= 1e-9 * expected.abs().max(1.0))
+        })
+    }
+
+    /// Syntax `raw_func_str` is written in
+    pub const fn input_syntax(&self) -> InputSyntax {
+        self.input_syntax
+    }
+
+    /// Changes the syntax `raw_func_str` is written in, parsing it again
+    pub fn set_input_syntax(&mut self, input_syntax: InputSyntax) {
+        self.input_syntax = input_syntax;
+        let raw_func_str = std::mem::take(&mut self.raw_func_str);
+        self.update_string(&raw_func_str);
     }
 
     /// Get function's cached test result
-    pub fn get_test_result(&self) -> &Option<String> {
+    pub fn get_test_result(&self) -> &Option<ParseError> {
         &self.test_result
     }
 
+    /// Error from parsing the function, either as a real function or (if `complex`) as a function of
+    /// a complex variable
+    pub fn error(&self, complex: bool) -> Option<&ParseError> {
+        match complex {
+            true => self.complex_error.as_ref(),
+            false => self.test_result.as_ref(),
+        }
+    }
+
+    /// The function as a function of a complex variable, `None` if it isn't one
+    pub fn complex_function(&self) -> Option<&ComplexFunction> {
+        self.complex_function.as_ref()
+    }
+
+    /// Name other functions can call this function by (such as `f` in `f(x) = x^2`)
+    pub fn name(&self) -> Option<&str> {
+        self.definition.as_ref().and_then(Definition::name)
+    }
+
+    /// What `raw_func_str` defines, `None` if it couldn't be parsed
+    pub fn definition(&self) -> Option<&Definition> {
+        self.definition.as_ref()
+    }
+
+    /// Returns true if this entry defines a constant (such as `k = 9.81`) instead of a function
+    pub fn is_constant(&self) -> bool {
+        matches!(self.definition, Some(Definition::Constant { .. }))
+    }
+
+    /// Value of the constant this entry defines
+    pub fn constant_value(&self) -> Option<f64> {
+        if !self.is_constant() | self.test_result.is_some() {
+            return None;
+        }
+
+        Some(self.function.get_function_derivative(0).eval(&[]))
+    }
+
+    /// Text used to refer to the function in tables (its name if it has one)
+    pub fn label(&self) -> String {
+        match self.name() {
+            Some(name) => format!("{}(x)", name),
+            None => self.canonical_func_str.clone(),
+        }
+    }
+
+    /// The expression the entry defines, `None` if it has an error
+    pub fn expression(&self) -> Option<Expr> {
+        if self.test_result.is_some() {
+            return None;
+        }
+
+        parse_expression(self.definition.as_ref()?.body()).ok()
+    }
+
+    /// The function's `order`th derivative as an expression of `x`
+    pub fn derivative_expression(&mut self, order: usize) -> Option<Expr> {
+        if !self.can_evaluate() {
+            return None;
+        }
+
+        self.function.generate_derivative(order);
+        self.function.derivative_expression(order).or_else(|| {
+            parse_expression(&self.processed_func_str)
+                .ok()
+                .map(|expr| expr.differentiated("x", order))
+        })
+    }
+
+    /// Label the `order`th derivative of the entry is previewed with (such as `f'(x)` or `k`), `None`
+    /// for expressions without a name
+    pub fn preview_label(&self, order: usize) -> Option<String> {
+        let primes = "'".repeat(order);
+        match self.definition.as_ref()? {
+            Definition::Function { name, param, .. } if order == 0 => {
+                Some(format!("{}({})", name, param))
+            }
+            Definition::Function { name, .. } => Some(format!("{}{}(x)", name, primes)),
+            Definition::Constant { name, .. } => Some(name.clone()),
+            Definition::Expression(_) if order == 0 => None,
+            Definition::Expression(_) => Some(format!("y{}", primes)),
+        }
+    }
+
+    /// Every root of the function, real or complex, if it's a polynomial
+    pub fn polynomial_roots(&self) -> Option<&[Root]> {
+        match self.can_evaluate() {
+            true => self.polynomial_roots.as_deref(),
+            false => None,
+        }
+    }
+
+    /// Returns true if the function can be evaluated at some `x`
+    pub fn can_evaluate(&self) -> bool {
+        self.test_result.is_none() && !self.function.is_none() && !self.is_constant()
+    }
+
+    /// Evaluates the `derivative`th derivative of the function at `x`
+    pub fn eval(&mut self, derivative: usize, x: f64) -> Option<f64> {
+        if !self.can_evaluate() {
+            return None;
+        }
+
+        self.function.generate_derivative(derivative);
+        Some(self.function.get(derivative, x))
+    }
+
+    /// Tangent line at `tangent_x`, `None` if it isn't enabled or the function is undefined there
+    pub fn tangent_line(&mut self) -> Option<TangentLine> {
+        if !self.tangent {
+            return None;
+        }
+
+        let y = self.eval(0, self.tangent_x)?;
+        let slope = self.eval(1, self.tangent_x)?;
+        TangentLine::new(self.tangent_x, y, slope)
+    }
+
+    /// Name of the function's accumulation function (such as `∫[0, x] f(x)`)
+    pub fn accumulation_label(&self) -> String {
+        format!(
+            "∫[{}, x] {}",
+            format_exact(self.accumulation_start),
+            self.label()
+        )
+    }
+
+    /// Value of the accumulation function at `x`, `None` if it isn't displayed or defined there
+    pub fn accumulation_at(&self, x: f64) -> Option<f64> {
+        if !self.accumulation {
+            return None;
+        }
+
+        interpolate(self.accumulation_data.as_ref()?, x)
+    }
+
+    /// Names referenced by this function
+    pub fn references(&self) -> &[String] {
+        &self.references
+    }
+
+    /// Processed string of the function with user definitions expanded
+    pub fn expanded_func_str(&self) -> Option<&str> {
+        self.expanded_func_str.as_deref()
+    }
+
+    /// Marks the function as invalid with the error `error`
+    pub fn set_error(&mut self, error: ParseError) {
+        self.expanded_func_str = None;
+        self.complex_function = None;
+        self.complex_error = Some(error.clone());
+        self.test_result = Some(error);
+    }
+
     /// Update function string and test it
     pub fn update_string(&mut self, raw_func_str: &str) {
         if raw_func_str == self.raw_func_str {
@@ -200,19 +913,89 @@ impl FunctionEntry {
         }
 
         self.raw_func_str = raw_func_str.to_owned();
-        let processed_func = process_func_str(raw_func_str);
-        let new_func_result = BackingFunction::new(&processed_func);
+        self.rejected_rewrite = None;
+
+        let plain = self.input_syntax.to_plain(raw_func_str);
+        match parse_definition(&plain) {
+            Ok(definition) => {
+                self.canonical_func_str = definition
+                    .canonical()
+                    .unwrap_or_else(|_| raw_func_str.trim().to_owned());
+                self.processed_func_str = process_definition(&definition);
+                self.references = referenced_names(&self.processed_func_str);
+                self.definition = Some(definition);
+                self.expanded_func_str = None;
+                self.resolve(&SymbolTable::default());
+            }
+            Err(error) => {
+                self.canonical_func_str = raw_func_str.trim().to_owned();
+                self.definition = None;
+                self.references.clear();
+                self.set_error(error.in_source(&plain, raw_func_str));
+            }
+        }
+    }
 
-        match new_func_result {
+    /// Expands references to the user definitions in `symbols`, rebuilding the function if that changes it
+    pub fn resolve(&mut self, symbols: &SymbolTable) {
+        if self.definition.is_none() {
+            return;
+        }
+
+        let expanded = match symbols.expand(&self.processed_func_str) {
+            Ok(expanded) => expanded,
+            Err(error) => {
+                self.set_error(error.in_definition(&self.processed_func_str, &self.raw_func_str));
+                return;
+            }
+        };
+
+        if self.expanded_func_str.as_ref() == Some(&expanded) {
+            return;
+        }
+        self.symbols = symbols.clone();
+
+        // Functions keep their parameter (renamed to `x`), and expressions are functions of `z`
+        let variable = match self.definition {
+            Some(Definition::Function { .. }) => Some("x"),
+            Some(Definition::Expression(_)) => Some("z"),
+            _ => None,
+        };
+        let complex = variable.map(|variable| ComplexFunction::new(&expanded, variable));
+        (self.complex_function, self.complex_error) = match complex {
+            Some(Ok(complex_function)) => (Some(complex_function), None),
+            Some(Err(error)) => (
+                None,
+                Some(error.in_definition(&expanded, &self.raw_func_str)),
+            ),
+            None => (None, None),
+        };
+
+        match BackingFunction::new(&expanded) {
+            Ok(new_function) if self.is_constant() && !new_function.is_constant() => {
+                self.test_result = Some(ParseErrorKind::ConstantDependsOnX.into());
+            }
             Ok(new_function) => {
                 self.test_result = None;
                 self.function = new_function;
+                // Roots are found from how the AST reads the function, so they're only used if exmex
+                // agrees they're roots
+                self.polynomial_roots = parse_expression(&expanded)
+                    .ok()
+                    .and_then(|expr| expr.polynomial_roots("x"))
+                    .filter(|roots| {
+                        roots
+                            .iter()
+                            .filter(|root| root.is_real())
+                            .all(|root| is_root(&mut self.function, root.re))
+                    });
                 self.invalidate_whole();
             }
             Err(error) => {
-                self.test_result = Some(error);
+                self.test_result = Some(error.in_definition(&expanded, &self.raw_func_str));
             }
         }
+        self.expanded_func_str = Some(expanded);
     }
 
     /// Creates and does the math for creating all the rectangles under the graph
@@ -256,6 +1039,39 @@ impl FunctionEntry {
         (data2, area)
     }
 
+    /// Calculates the rectangles under the graph and the area of the integral. Improper integrals
+    /// (with infinite bounds or points where the function is undefined) are integrated numerically
+    /// instead of being approximated by the rectangles, which only cover the visible part of
+    /// infinite bounds. The area is infinite if the integral diverges, or `NaN` if it is undefined.
+    fn integral_area(&mut self, settings: &AppSettings) -> (Vec<(f64, f64)>, f64) {
+        let (min_x, max_x) = settings.visible_integral_bounds();
+        if min_x >= max_x {
+            return (Vec::new(), f64::NAN);
+        }
+
+        let (data, area) =
+            self.integral_rectangles(min_x, max_x, settings.riemann_sum, settings.integral_num);
+
+        // Points between the bounds where the function is undefined or has a pole
+        let step = (max_x - min_x) / (settings.integral_num as f64);
+        let singularities = find_singularities(
+            |x| self.function.get(0, x),
+            &step_helper(settings.integral_num + 1, min_x, step),
+        );
+
+        if !settings.integral_is_infinite() && singularities.is_empty() {
+            return (data, area);
+        }
+
+        let area = improper_integral(
+            |x| self.function.get(0, x),
+            settings.integral_min_x,
+            settings.integral_max_x,
+            &singularities,
+        );
+        (data, area)
+    }
+
     /// Helps with processing newton's method depending on level of derivative
     fn newtons_method_helper(
         &mut self,
@@ -297,7 +1113,7 @@ impl FunctionEntry {
         did_zoom: bool,
         settings: AppSettings,
     ) {
-        if self.test_result.is_some() | self.function.is_none() {
+        if self.test_result.is_some() | self.function.is_none() | self.is_constant() {
             return;
         }
 
@@ -310,10 +1126,17 @@ impl FunctionEntry {
             self.clear_integral();
         }
 
+        // Rectangles of infinite integrals only cover the visible part of the plot
+        if settings.integral_is_infinite() && (width_changed | min_max_changed) {
+            self.clear_integral();
+        }
+
         if width_changed | min_max_changed | did_zoom {
             self.clear_back();
             self.clear_derivative();
             self.clear_nth();
+            self.clear_taylor();
+            self.clear_accumulation();
         }
 
         if self.back_data.is_empty() {
@@ -338,23 +1161,78 @@ impl FunctionEntry {
             self.derivative_data = data;
         }
 
-        if self.nth_derviative && self.nth_derivative_data.is_none() {
-            let data: Vec<PlotPoint> = resolution_iter
-                .into_iter()
-                .map(|x| PlotPoint::new(x, self.function.get(self.curr_nth, x)))
-                .collect();
-            debug_assert_eq!(data.len(), settings.plot_width + 1);
-            self.nth_derivative_data = Some(data);
+        for nth in self.nth_derivatives.iter_mut() {
+            if nth.data.is_none() {
+                self.function.generate_derivative(nth.order);
+                let data: Vec<PlotPoint> = resolution_iter
+                    .iter()
+                    .map(|&x| PlotPoint::new(x, self.function.get(nth.order, x)))
+                    .collect();
+                debug_assert_eq!(data.len(), settings.plot_width + 1);
+                nth.data = Some(data);
+            }
+        }
+
+        if self.taylor {
+            if self.taylor_polynomial.is_none() {
+                let derivatives: Vec<f64> = (0..=self.taylor_degree)
+                    .map(|k| {
+                        self.function.generate_derivative(k);
+                        self.function.get(k, self.taylor_center)
+                    })
+                    .collect();
+                self.taylor_polynomial = TaylorPolynomial::new(self.taylor_center, &derivatives);
+                self.taylor_data = None;
+                self.taylor_error_data = None;
+            }
+
+            if let Some(ref polynomial) = self.taylor_polynomial
+                && self.taylor_data.is_none()
+            {
+                let data: Vec<PlotPoint> = self
+                    .back_data
+                    .iter()
+                    .map(|point| PlotPoint::new(point.x, polynomial.eval(point.x)))
+                    .collect();
+
+                // Remainder of the function that the polynomial doesn't account for
+                self.taylor_error_data = Some(
+                    self.back_data
+                        .iter()
+                        .zip(data.iter())
+                        .map(|(function, taylor)| PlotPoint::new(function.x, function.y - taylor.y))
+                        .collect(),
+                );
+                self.taylor_data = Some(data);
+            }
+        } else {
+            self.clear_taylor();
+        }
+
+        if self.accumulation {
+            if self.accumulation_data.is_none()
+                && let Some(first_x) = self.back_data.first().map(|point| point.x)
+            {
+                // Area from the lower limit up to the first point on the plot
+                let steps = (((first_x - self.accumulation_start).abs() / resolution).ceil()
+                    as usize)
+                    .clamp(1, MAX_ACCUMULATION_STEPS);
+                let start = trapezoidal_integral(
+                    |x| self.function.get(0, x),
+                    self.accumulation_start,
+                    first_x,
+                    steps,
+                );
+
+                self.accumulation_data = Some(accumulate(&self.back_data, start));
+            }
+        } else {
+            self.clear_accumulation();
         }
 
         if self.integral {
             if self.integral_data.is_none() {
-                let (data, area) = self.integral_rectangles(
-                    settings.integral_min_x,
-                    settings.integral_max_x,
-                    settings.riemann_sum,
-                    settings.integral_num,
-                );
+                let (data, area) = self.integral_area(&settings);
 
                 self.integral_data = Some((
                     data.into_iter().map(|(x, y)| Bar::new(x, y)).collect(),
@@ -373,27 +1251,30 @@ impl FunctionEntry {
             self.extrema_data = self.newtons_method_helper(threshold, 1, &x_range);
         }
 
-        // Calculates roots
+        // Calculates roots, which are already known exactly for polynomials
         if settings.do_roots && (min_max_changed | self.root_data.is_empty()) {
-            self.root_data = self.newtons_method_helper(threshold, 0, &x_range);
+            self.root_data = match self.polynomial_roots {
+                Some(ref roots) => roots
+                    .iter()
+                    .filter(|root| root.is_real() && x_range.contains(&root.re))
+                    .map(|root| PlotPoint::new(root.re, 0.0))
+                    .collect(),
+                None => self.newtons_method_helper(threshold, 0, &x_range),
+            };
         }
     }
 
     /// Displays the function's output on PlotUI `plot_ui` with settings `settings`.
-    /// Returns an `Option<f64>` of the calculated integral.
-    pub fn display(
-        &self,
-        plot_ui: &mut PlotUi,
-        settings: &AppSettings,
-        main_plot_color: Color32,
-    ) -> Option<f64> {
-        if self.test_result.is_some() | self.function.is_none() | !self.visible {
+    /// Returns the formatted area of the calculated integral.
+    pub fn display(&self, plot_ui: &mut PlotUi, settings: &AppSettings) -> Option<String> {
+        if self.test_result.is_some() | self.function.is_none() | !self.visible | self.is_constant()
+        {
             return None;
         }
 
-        let integral_step =
-            (settings.integral_max_x - settings.integral_min_x) / (settings.integral_num as f64);
-        debug_assert!(integral_step > 0.0);
+        let (integral_min_x, integral_max_x) = settings.visible_integral_bounds();
+        let integral_step = (integral_max_x - integral_min_x) / (settings.integral_num as f64);
+        debug_assert!(integral_step >= 0.0);
 
         let step = (settings.max_x - settings.min_x) / (settings.plot_width as f64);
         debug_assert!(step > 0.0);
@@ -420,7 +1301,8 @@ impl FunctionEntry {
                 self.back_data
                     .clone()
                     .to_line()
-                    .stroke((4.0, main_plot_color)),
+                    .stroke((self.line_width, self.color))
+                    .style(self.line_dash.to_line_style(self.line_width)),
             );
         }
 
@@ -451,10 +1333,47 @@ impl FunctionEntry {
             );
         }
 
-        if self.nth_derviative
-            && let Some(ref nth_derviative) = self.nth_derivative_data
+        for nth in self.nth_derivatives.iter() {
+            if let Some(ref data) = nth.data {
+                plot_ui.line(data.clone().to_line().color(nth.color));
+            }
+        }
+
+        // Plot Taylor polynomial and its truncation error
+        if self.taylor {
+            if let Some(ref taylor_data) = self.taylor_data {
+                plot_ui.line(
+                    taylor_data
+                        .clone()
+                        .to_line()
+                        .stroke((self.line_width / 2.0, self.color.gamma_multiply(0.7)))
+                        .style(LineDash::Dashed.to_line_style(self.line_width / 2.0)),
+                );
+            }
+
+            if self.taylor_error
+                && let Some(ref taylor_error_data) = self.taylor_error_data
+            {
+                plot_ui.line(
+                    taylor_error_data
+                        .clone()
+                        .to_line()
+                        .stroke((self.line_width / 2.0, self.color.gamma_multiply(0.4)))
+                        .style(LineDash::Dotted.to_line_style(self.line_width / 2.0)),
+                );
+            }
+        }
+
+        // Plot accumulation function
+        if self.accumulation
+            && let Some(ref accumulation_data) = self.accumulation_data
         {
-            plot_ui.line(nth_derviative.clone().to_line().color(Color32::DARK_RED));
+            plot_ui.line(
+                accumulation_data
+                    .clone()
+                    .to_line()
+                    .stroke((self.line_width / 2.0, self.color.gamma_multiply(0.6))),
+            );
         }
 
         // Plot integral data
@@ -468,8 +1387,7 @@ impl FunctionEntry {
                     );
                 }
 
-                // return value rounded to 8 decimal places
-                Some(emath::round_to_decimals(integral_data.1, 8))
+                Some(format_area(integral_data.1))
             }
             None => None,
         }
@@ -481,6 +1399,8 @@ impl FunctionEntry {
         self.clear_integral();
         self.clear_derivative();
         self.clear_nth();
+        self.clear_taylor();
+        self.clear_accumulation();
         self.clear_extrema();
         self.clear_roots();
     }
@@ -506,7 +1426,23 @@ impl FunctionEntry {
     /// Invalidates `n`th derivative data
     #[inline]
     fn clear_nth(&mut self) {
-        self.nth_derivative_data = None
+        self.nth_derivatives
+            .iter_mut()
+            .for_each(|nth| nth.data = None);
+    }
+
+    /// Invalidates Taylor polynomial data
+    #[inline]
+    fn clear_taylor(&mut self) {
+        self.taylor_polynomial = None;
+        self.taylor_data = None;
+        self.taylor_error_data = None;
+    }
+
+    /// Invalidates accumulation function data
+    #[inline]
+    fn clear_accumulation(&mut self) {
+        self.accumulation_data = None;
     }
 
     /// Invalidate extrema data
@@ -1,31 +1,41 @@
 use crate::{
-    consts::COLORS,
-    function_entry::FunctionEntry,
-    misc::{create_id, get_u64_id, random_u64},
-    widgets::widgets_ontop,
+    function_entry::{FunctionEntry, SavedFunctionEntry},
+    misc::{create_id, get_u64_id, palette_color, random_u64},
+    typeset::typeset,
+    widgets::{error_indicator, underlined_text, widgets_ontop},
 };
-use egui::{Button, Id, Key, Modifiers, Popup, TextEdit, WidgetText};
+use egui::{Button, Color32, Id, Key, Modifiers, Popup, TextEdit, WidgetText};
 use emath::vec2;
-use parsing::Movement;
+use parsing::{CompletionContext, Expr, Movement, ParseError, ParseErrorKind, SymbolTable};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::DefaultHasher};
 use std::hash::{Hash, Hasher};
 use std::ops::BitXorAssign;
 
 type Functions = Vec<(Id, FunctionEntry)>;
 pub struct FunctionManager {
     functions: Functions,
+
+    /// Hash of the definitions (see [`FunctionManager::definitions_hash`]) when they were last
+    /// resolved, so they're only resolved again after one of them changes
+    resolved_hash: Option<u64>,
+
+    /// Every valid constant definition, which expressions outside of functions (such as integral
+    /// bounds) can use
+    constant_symbols: SymbolTable,
 }
 
 impl Default for FunctionManager {
     fn default() -> Self {
-        let mut vec: Functions = Vec::with_capacity(COLORS.len());
-        vec.push((
-            create_id(11414819524356497634), // Random number here to avoid call to crate::misc::random_u64()
-            FunctionEntry::default(),
-        ));
-        Self { functions: vec }
+        Self {
+            functions: vec![(
+                create_id(11414819524356497634), // Random number here to avoid call to crate::misc::random_u64()
+                FunctionEntry::default(),
+            )],
+            resolved_hash: None,
+            constant_symbols: SymbolTable::default(),
+        }
     }
 }
 
@@ -53,18 +63,21 @@ impl<'de> Deserialize<'de> for FunctionManager {
         D: Deserializer<'de>,
     {
         #[derive(Deserialize)]
-        struct Helper(Vec<(u64, FunctionEntry)>);
+        struct Helper(Vec<(u64, SavedFunctionEntry)>);
 
         let helper = Helper::deserialize(deserializer)?;
 
-        Ok(FunctionManager {
+        let mut function_manager = FunctionManager {
             functions: helper
                 .0
-                .iter()
-                .cloned()
-                .map(|(id, func)| (create_id(id), func))
+                .into_iter()
+                .map(|(id, saved)| (create_id(id), saved.into_entry()))
                 .collect::<Vec<(Id, FunctionEntry)>>(),
-        })
+            resolved_hash: None,
+            constant_symbols: SymbolTable::default(),
+        };
+        function_manager.resolve_changed_definitions();
+        Ok(function_manager)
     }
 }
 
@@ -73,6 +86,25 @@ fn button_area_button<'a>(text: impl Into<WidgetText>) -> Button<'a> {
     Button::new(text).frame(false)
 }
 
+/// Displays `expr` typeset after `label` (such as `f(x)`), along with a button to copy it as LaTeX
+fn expression_preview(ui: &mut egui::Ui, label: Option<&str>, expr: &Expr) {
+    ui.horizontal(|ui| {
+        typeset(ui, label, expr);
+
+        if ui
+            .small_button("Copy LaTeX")
+            .on_hover_text("Copy the expression as LaTeX")
+            .clicked()
+        {
+            let latex = match label {
+                Some(label) => format!("{} = {}", label, expr.to_latex()),
+                None => expr.to_latex(),
+            };
+            ui.ctx().copy_text(latex);
+        }
+    });
+}
+
 impl FunctionManager {
     #[inline]
     fn get_hash(&self) -> u64 {
@@ -81,12 +113,149 @@ impl FunctionManager {
         hasher.finish()
     }
 
-    /// Displays function entries alongside returning whether or not functions have been modified
-    pub fn display_entries(&mut self, ui: &mut egui::Ui) -> bool {
+    /// Hash of what the functions define: the text of each function, in order, along with the
+    /// syntax it's written in
+    fn definitions_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for (_, function) in self.functions.iter() {
+            function.raw_func_str.hash(&mut hasher);
+            function.input_syntax().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Resolves the definitions (see [`FunctionManager::resolve_definitions`]) if any of them
+    /// changed since they were last resolved
+    fn resolve_changed_definitions(&mut self) {
+        let hash = self.definitions_hash();
+        if self.resolved_hash != Some(hash) {
+            self.resolve_definitions();
+            self.resolved_hash = Some(hash);
+        }
+    }
+
+    /// Expands references between functions and constants (such as `g(x) = f(x) + k`). Functions are resolved
+    /// in dependency order so each one sees the definitions it uses, and cycles are reported as errors.
+    fn resolve_definitions(&mut self) {
+        let num_functions = self.functions.len();
+
+        // Maps names to the index of the function defining them
+        let mut names: HashMap<String, usize> = HashMap::new();
+        let mut errors: Vec<Option<ParseError>> = vec![None; num_functions];
+        for (i, (_, function)) in self.functions.iter().enumerate() {
+            if let Some(name) = function.name() {
+                if names.contains_key(name) {
+                    errors[i] = Some(ParseErrorKind::AlreadyDefined(name.to_owned()).into());
+                } else {
+                    names.insert(name.to_owned(), i);
+                }
+            }
+        }
+
+        // Indices of the functions each function depends on
+        let dependencies: Vec<Vec<usize>> = self
+            .functions
+            .iter()
+            .map(|(_, function)| {
+                function
+                    .references()
+                    .iter()
+                    .filter_map(|name| names.get(name).copied())
+                    .collect()
+            })
+            .collect();
+
+        // Topologically sort functions so definitions are resolved before the functions using them
+        let mut remaining: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+        let mut queue: VecDeque<usize> =
+            (0..num_functions).filter(|&i| remaining[i] == 0).collect();
+        let mut order: Vec<usize> = Vec::with_capacity(num_functions);
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            for (j, deps) in dependencies.iter().enumerate() {
+                for _ in deps.iter().filter(|&&dep| dep == i) {
+                    remaining[j] -= 1;
+                    if remaining[j] == 0 {
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+
+        let mut symbols = SymbolTable::default();
+        let mut failed: Vec<bool> = vec![true; num_functions];
+        for i in order {
+            // Don't bother resolving functions that use a definition which has an error
+            if errors[i].is_none()
+                && let Some(&dep) = dependencies[i].iter().find(|&&dep| failed[dep])
+            {
+                let dep_name = self.functions[dep].1.name().unwrap_or_default();
+                errors[i] = Some(ParseErrorKind::BrokenDependency(dep_name.to_owned()).into());
+            }
+
+            let function = &mut self.functions[i].1;
+            if let Some(error) = errors[i].take() {
+                function.set_error(error);
+                continue;
+            }
+
+            function.resolve(&symbols);
+            if function.get_test_result().is_none() {
+                failed[i] = false;
+                if let (Some(name), Some(value)) = (function.name(), function.constant_value()) {
+                    symbols.insert_constant(name, value);
+                } else if let (Some(name), Some(expanded)) =
+                    (function.name(), function.expanded_func_str())
+                {
+                    symbols.insert_function(name, "x", expanded);
+                }
+            }
+        }
+
+        // Functions that were never reached are part of (or depend on) a circular definition
+        for (i, (_, function)) in self.functions.iter_mut().enumerate() {
+            if remaining[i] != 0 {
+                function.set_error(ParseErrorKind::CircularDefinition.into());
+            }
+        }
+
+        // Autocomplete the names functions define, ranking names by how many functions use them
+        let mut completion_context = CompletionContext::default();
+        for (_, function) in self.functions.iter() {
+            if function.get_test_result().is_none()
+                && let Some(definition) = function.definition()
+            {
+                completion_context.define(definition);
+            }
+
+            for name in function.references() {
+                completion_context.record_usage(name);
+            }
+        }
+
+        for (_, function) in self.functions.iter_mut() {
+            function.autocomplete.set_context(&completion_context);
+        }
+
+        let mut constant_symbols = SymbolTable::default();
+        self.constants()
+            .into_iter()
+            .for_each(|(name, value)| constant_symbols.insert_constant(name, value));
+        self.constant_symbols = constant_symbols;
+    }
+
+    /// Displays function entries alongside returning whether or not functions have been modified.
+    /// Errors shown are those of the functions as complex functions if `complex` is enabled.
+    pub fn display_entries(&mut self, ui: &mut egui::Ui, complex: bool) -> bool {
         let initial_hash = self.get_hash();
 
+        self.functions.iter_mut().for_each(|(_, function)| {
+            let new_string = function.autocomplete.string.clone();
+            function.update_string(&new_string);
+        });
+        self.resolve_changed_definitions();
+
         let can_remove = self.functions.len() > 1;
-        let can_add = self.functions.len() < COLORS.len();
         let num_functions = self.functions.len();
 
         let available_width = ui.available_width();
@@ -97,10 +266,15 @@ impl FunctionManager {
         let target_size = vec2(available_width, crate::consts::FONT_SIZE);
         for (i, (te_id, function)) in self.functions.iter_mut().map(|(a, b)| (*a, b)).enumerate() {
             let mut new_string = function.autocomplete.string.clone();
-            function.update_string(&new_string);
 
             let mut movement: Movement = Movement::default();
 
+            // Underline the characters causing the function's error
+            let error_span = function.error(complex).and_then(|error| error.span.clone());
+            let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+                underlined_text(ui, text.as_str(), error_span.clone(), wrap_width)
+            };
+
             let size_multiplier = vec2(1.0, {
                 let had_focus = ui.memory(|x| x.has_focus(te_id));
                 (ui.ctx().animate_bool(te_id, had_focus) * 1.5) + 1.0
@@ -112,36 +286,62 @@ impl FunctionManager {
                     .hint_forward(true) // Make the hint appear after the last text in the textbox
                     .lock_focus(true)
                     .id(te_id) // Set widget's id to `te_id`
+                    .layouter(&mut layouter)
                     .hint_text(
-                        // If there's a single hint, go ahead and apply the hint here, if not, set the hint to an empty string
-                        function.autocomplete.hint.single().unwrap_or(""),
+                        // If there's a single hint, go ahead and apply the hint here, if not, set the hint to an empty string.
+                        // Hints are shown after the text, so they're only shown when the caret is at the end.
+                        match function.autocomplete.at_end() {
+                            true => function.autocomplete.hint_text(),
+                            false => "",
+                        },
                     ),
             );
 
             // Only keep valid chars
             new_string.retain(crate::misc::is_valid_char);
 
-            // Display error indicator with tooltip if there's a parsing error
-            if let Some(error) = function.get_test_result() {
-                ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("⚠").color(egui::Color32::YELLOW))
-                        .on_hover_text(error);
-                    ui.label(
-                        egui::RichText::new(error)
-                            .color(egui::Color32::LIGHT_RED)
-                            .small(),
-                    );
-                });
+            // Display error indicator with tooltip if there's a parsing error, along with its fix
+            if let Some(error) = function.error(complex) {
+                error_indicator(ui, &error.to_string());
+
+                let fixed = error
+                    .fix
+                    .as_ref()
+                    .and_then(|fix| fix.apply(&function.raw_func_str));
+                if let Some(fixed) = fixed
+                    && ui
+                        .small_button(format!("Fix: {}", fixed))
+                        .on_hover_text("Apply the suggested fix")
+                        .clicked()
+                {
+                    new_string = fixed;
+                    function.autocomplete.update_string(&new_string);
+                }
+            }
+
+            // Typeset preview of the function (and its derivative if it's shown)
+            if let Some(expr) = function.expression() {
+                expression_preview(ui, function.preview_label(0).as_deref(), &expr);
+
+                if function.derivative
+                    && let Some(derivative) = function.derivative_expression(1)
+                {
+                    expression_preview(ui, function.preview_label(1).as_deref(), &derivative);
+                }
             }
 
             // If not fully open, return here as buttons cannot yet be displayed, therefore the user is inable to mark it for deletion
             let animate_bool = ui.ctx().animate_bool(te_id, re.has_focus());
             if animate_bool == 1.0 {
-                function.autocomplete.update_string(&new_string);
-
-                if function.autocomplete.hint.is_some() {
-                    // only register up and down arrow movements if hint is type `Hint::Many`
-                    if !function.autocomplete.hint.is_single() {
+                // Hints complete the term just before the caret
+                let cursor = TextEdit::load_state(ui.ctx(), te_id)
+                    .and_then(|state| state.cursor.char_range())
+                    .map_or(new_string.chars().count(), |range| range.primary.index);
+                function.autocomplete.update(&new_string, cursor);
+
+                if function.autocomplete.has_hint() {
+                    // only register up and down arrow movements if there are several completions
+                    if function.autocomplete.has_many() {
                         let (arrow_down, arrow_up) = ui.input(|x| {
                             (x.key_pressed(Key::ArrowDown), x.key_pressed(Key::ArrowUp))
                         });
@@ -152,11 +352,13 @@ impl FunctionManager {
                         }
                     }
 
-                    // Put here so these key presses don't interact with other elements
+                    // Put here so these key presses don't interact with other elements. The right arrow only
+                    // completes at the end of the text, as elsewhere it moves the caret.
+                    let at_end = function.autocomplete.at_end();
                     let movement_complete_action = ui.input_mut(|x| {
                         x.consume_key(Modifiers::NONE, Key::Enter)
                             | x.consume_key(Modifiers::NONE, Key::Tab)
-                            | x.key_pressed(Key::ArrowRight)
+                            | (at_end && x.key_pressed(Key::ArrowRight))
                     });
 
                     if movement_complete_action {
@@ -166,33 +368,42 @@ impl FunctionManager {
                     // Register movement and apply proper changes
                     function.autocomplete.register_movement(&movement);
 
-                    if movement != Movement::Complete
-                        && let Some(hints) = function.autocomplete.hint.many()
-                    {
-                        let mut clicked = false;
+                    // Completions are listed when there's a choice, or when their hint can't be shown after the text
+                    let list_completions = function.autocomplete.has_many()
+                        || (!at_end && !function.autocomplete.completions.is_empty());
+                    if movement != Movement::Complete && list_completions {
+                        let mut clicked = None;
                         let selected_i = function.autocomplete.i;
 
-                        if let Some(popup_response) = Popup::menu(&re).show(|ui| {
-                            hints.iter().enumerate().for_each(|(i, candidate)| {
-                                if ui.selectable_label(i == selected_i, *candidate).clicked() {
-                                    clicked = true;
-                                    function.autocomplete.i = i;
-                                }
-                            });
-                        }) && clicked
-                        {
-                            function
-                                .autocomplete
-                                .apply_hint(hints[function.autocomplete.i]);
+                        // List each completion's signature along with what it does
+                        Popup::menu(&re).show(|ui| {
+                            for (i, completion) in
+                                function.autocomplete.completions.iter().enumerate()
+                            {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .selectable_label(i == selected_i, &completion.signature)
+                                        .clicked()
+                                    {
+                                        clicked = Some(i);
+                                    }
+                                    ui.weak(&completion.description);
+                                });
+                            }
+                        });
+
+                        if let Some(i) = clicked {
+                            function.autocomplete.i = i;
+                            function.autocomplete.register_movement(&Movement::Complete);
                             movement = Movement::Complete;
                         }
                     }
 
-                    // Push cursor to end if needed
+                    // Move the caret past the completion
                     if movement == Movement::Complete
                         && let Some(mut state) = TextEdit::load_state(ui.ctx(), te_id)
                     {
-                        let ccursor = egui::text::CCursor::new(function.autocomplete.string.len());
+                        let ccursor = egui::text::CCursor::new(function.autocomplete.cursor);
                         state
                             .cursor
                             .set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
@@ -231,7 +442,7 @@ impl FunctionManager {
 
                         // Clone function
                         if ui
-                            .add_enabled(can_add, button_area_button("⎘"))
+                            .add(button_area_button("⎘"))
                             .on_hover_text("Clone Function")
                             .clicked()
                         {
@@ -256,7 +467,7 @@ impl FunctionManager {
                             move_down_i = Some(i);
                         }
 
-                        ui.add_enabled_ui(function.is_some(), |ui| {
+                        ui.add_enabled_ui(function.is_some() && !function.is_constant(), |ui| {
                             // Toggle integral being enabled or not
                             function.integral.bitxor_assign(
                                 ui.add(button_area_button("∫"))
@@ -324,11 +535,21 @@ impl FunctionManager {
         initial_hash != final_hash
     }
 
+    /// Returns the first palette color not already used by a function entry
+    fn next_color(&self) -> Color32 {
+        (0..)
+            .map(palette_color)
+            .find(|color| !self.functions.iter().any(|(_, func)| &func.color == color))
+            .expect("palette is infinite")
+    }
+
     /// Create and push new empty function entry
     pub fn push_empty(&mut self) {
+        let mut entry = FunctionEntry::default();
+        entry.color = self.next_color();
         self.functions.push((
             create_id(random_u64().expect("unable to generate random id")),
-            FunctionEntry::default(),
+            entry,
         ));
     }
 
@@ -336,22 +557,55 @@ impl FunctionManager {
     pub fn push_cloned(&mut self, mut entry: FunctionEntry) {
         // Reset settings_opened so the cloned function doesn't have settings open
         entry.settings_opened = false;
+        // Give the clone its own color so it can be told apart from the original
+        entry.color = self.next_color();
         self.functions.push((
             create_id(random_u64().expect("unable to generate random id")),
             entry,
         ));
     }
 
+    /// Returns true if any function is the same as an earlier one once both are in canonical form,
+    /// and is drawn the same way
+    pub fn has_duplicates(&self) -> bool {
+        let mut seen = HashSet::new();
+        !self
+            .functions
+            .iter()
+            .all(|(_, func)| seen.insert(func.drawing_hash()))
+    }
+
+    /// Removes functions that are the same as an earlier one once both are in canonical form (such
+    /// as `2x` after `2 * x`) and are drawn the same way, returning how many were removed. Functions
+    /// with different settings (such as an integral or color) are kept, as removing them would
+    /// change the graph.
+    pub fn dedupe(&mut self) -> usize {
+        let num_functions = self.functions.len();
+        let mut seen = HashSet::new();
+        self.functions
+            .retain(|(_, func)| seen.insert(func.drawing_hash()));
+        self.resolve_changed_definitions();
+        num_functions - self.functions.len()
+    }
+
+    /// Returns the name and value of every valid constant definition
+    pub fn constants(&self) -> Vec<(&str, f64)> {
+        self.functions
+            .iter()
+            .filter_map(|(_, func)| Some((func.name()?, func.constant_value()?)))
+            .collect()
+    }
+
+    /// Symbol table containing every valid constant definition
+    pub const fn constant_symbols(&self) -> &SymbolTable {
+        &self.constant_symbols
+    }
+
     /// Detect if any functions are using integrals
     pub fn any_using_integral(&self) -> bool {
         self.functions.iter().any(|(_, func)| func.integral)
     }
 
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.functions.len()
-    }
-
     #[inline]
     pub fn get_entries_mut(&mut self) -> &mut Functions {
         &mut self.functions
@@ -0,0 +1,187 @@
+use crate::{
+    exact::format_exact, function_entry::FunctionEntry, function_manager::FunctionManager,
+};
+use egui_plot::{PlotPoint, PlotUi, Points, Text};
+use emath::Align2;
+use epaint::Color32;
+
+/// Maximum distance (in pixels) from the pointer that roots, extrema, and intersections are snapped to
+const SPECIAL_SNAP_DISTANCE: f32 = 12.0;
+
+/// Maximum distance (in pixels) from the pointer that curves are snapped to
+const CURVE_SNAP_DISTANCE: f32 = 10.0;
+
+/// The kind of point the pointer snapped to
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum SnapKind {
+    Curve,
+    Root,
+    Minimum,
+    Maximum,
+    Extremum,
+    Intersection,
+}
+
+/// A point on the plot that can be inspected by hovering over it
+#[derive(PartialEq, Debug, Clone)]
+pub struct SnapPoint {
+    pub point: PlotPoint,
+    pub kind: SnapKind,
+
+    /// Name of the function(s) the point belongs to
+    pub name: String,
+
+    pub color: Color32,
+}
+
+impl SnapPoint {
+    pub const fn new(point: PlotPoint, kind: SnapKind, name: String, color: Color32) -> Self {
+        Self {
+            point,
+            kind,
+            name,
+            color,
+        }
+    }
+
+    /// Describes what the point is
+    pub fn title(&self) -> String {
+        match self.kind {
+            SnapKind::Curve => self.name.clone(),
+            SnapKind::Root => format!("Root of {}", self.name),
+            SnapKind::Minimum => format!("Minimum of {}", self.name),
+            SnapKind::Maximum => format!("Maximum of {}", self.name),
+            SnapKind::Extremum => format!("Extremum of {}", self.name),
+            SnapKind::Intersection => format!("Intersection of {}", self.name),
+        }
+    }
+
+    /// Text displayed next to the point
+    pub fn label(&self) -> String {
+        format!(
+            "{}\n({}, {})",
+            self.title(),
+            format_exact(self.point.x),
+            format_exact(self.point.y)
+        )
+    }
+
+    /// Returns true if `other` marks the same point
+    pub fn same_point(&self, other: &SnapPoint) -> bool {
+        (self.kind == other.kind) && (self.point.x == other.point.x) && (self.name == other.name)
+    }
+
+    /// Highlights the point and displays its label
+    pub fn display(&self, plot_ui: &mut PlotUi) {
+        plot_ui.points(
+            Points::new("", vec![[self.point.x, self.point.y]])
+                .color(self.color)
+                .filled(false)
+                .radius(8.0),
+        );
+        plot_ui.text(
+            Text::new("", self.point, self.label())
+                .anchor(Align2::LEFT_BOTTOM)
+                .color(Color32::WHITE),
+        );
+    }
+}
+
+/// Roots and extrema of `function` that have been calculated
+pub fn special_points(
+    function: &mut FunctionEntry,
+    do_extrema: bool,
+    do_roots: bool,
+) -> Vec<SnapPoint> {
+    let mut points = Vec::new();
+    if !function.can_evaluate() {
+        return points;
+    }
+
+    let name = function.label();
+    if do_roots {
+        points.extend(
+            function
+                .root_data
+                .iter()
+                .map(|point| SnapPoint::new(*point, SnapKind::Root, name.clone(), function.color)),
+        );
+    }
+
+    if do_extrema {
+        for point in function.extrema_data.clone() {
+            let kind = match function.eval(2, point.x) {
+                Some(y) if y > 0.0 => SnapKind::Minimum,
+                Some(y) if y < 0.0 => SnapKind::Maximum,
+                _ => SnapKind::Extremum,
+            };
+            points.push(SnapPoint::new(point, kind, name.clone(), function.color));
+        }
+    }
+
+    points
+}
+
+/// Finds the point the pointer should snap to. Roots, extrema, and `intersections` are preferred
+/// over points along a curve.
+pub fn find_snap_point(
+    plot_ui: &PlotUi,
+    functions: &mut FunctionManager,
+    intersections: &[SnapPoint],
+    do_extrema: bool,
+    do_roots: bool,
+) -> Option<SnapPoint> {
+    let pointer = plot_ui.pointer_coordinate()?;
+    let pointer_pos = plot_ui.screen_from_plot(pointer);
+    let distance = |point: PlotPoint| plot_ui.screen_from_plot(point).distance(pointer_pos);
+
+    let mut special: Vec<SnapPoint> = intersections.to_vec();
+    for (_, function) in functions.get_entries_mut().iter_mut() {
+        if function.visible {
+            special.extend(special_points(function, do_extrema, do_roots));
+        }
+    }
+
+    let closest_special = special
+        .into_iter()
+        .map(|snap| (distance(snap.point), snap))
+        .filter(|(dist, _)| *dist <= SPECIAL_SNAP_DISTANCE)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    if let Some((_, snap)) = closest_special {
+        return Some(snap);
+    }
+
+    // Snap to the closest curve directly above or below the pointer
+    let mut curves = Vec::new();
+    for (_, function) in functions.get_entries_mut().iter_mut() {
+        if !function.visible || !function.can_evaluate() {
+            continue;
+        }
+
+        if let Some(y) = function.eval(0, pointer.x).filter(|y| y.is_finite()) {
+            curves.push(SnapPoint::new(
+                PlotPoint::new(pointer.x, y),
+                SnapKind::Curve,
+                function.label(),
+                function.color,
+            ));
+        }
+
+        if let Some(y) = function.accumulation_at(pointer.x) {
+            curves.push(SnapPoint::new(
+                PlotPoint::new(pointer.x, y),
+                SnapKind::Curve,
+                function.accumulation_label(),
+                function.color,
+            ));
+        }
+    }
+
+    curves
+        .into_iter()
+        .map(|snap| (distance(snap.point), snap))
+        .filter(|(dist, _)| *dist <= CURVE_SNAP_DISTANCE)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, snap)| snap)
+}
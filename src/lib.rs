@@ -1,22 +1,39 @@
 #[macro_use]
 extern crate static_assertions;
 
+mod complex_plot;
 mod consts;
+mod evaluator;
+mod exact;
 mod function_entry;
 mod function_manager;
+mod hover;
 mod math_app;
 mod misc;
+mod quadrature;
+mod tangent;
+mod taylor;
+mod typeset;
 mod unicode_helper;
 mod widgets;
 
 pub use crate::{
-    function_entry::{FunctionEntry, Riemann},
+    evaluator::to_csv,
+    exact::{
+        ExactValue, almost_variable, format_exact, format_term, rational_approximation, recognize,
+    },
+    function_entry::{FunctionEntry, InputSyntax, LineDash, Riemann},
+    function_manager::FunctionManager,
     math_app::AppSettings,
     misc::{
-        EguiHelper, HashBytes, hashed_storage_create, hashed_storage_read, newtons_method,
-        option_vec_printer, step_helper,
+        EguiHelper, HashBytes, accumulate, hashed_storage_create, hashed_storage_read, interpolate,
+        newtons_method, option_vec_printer, palette_color, step_helper, trapezoidal_integral,
     },
+    quadrature::{find_singularities, format_area, improper_integral},
+    tangent::{TangentLine, line_equation},
+    taylor::TaylorPolynomial,
     unicode_helper::{to_chars_array, to_unicode_hash},
+    widgets::ExpressionInput,
 };
 
 cfg_if::cfg_if! {
@@ -1,11 +1,19 @@
 #[macro_use]
 extern crate static_assertions;
 
+mod complex_plot;
 mod consts;
+mod evaluator;
+mod exact;
 mod function_entry;
 mod function_manager;
+mod hover;
 mod math_app;
 mod misc;
+mod quadrature;
+mod tangent;
+mod taylor;
+mod typeset;
 mod unicode_helper;
 mod widgets;
 
@@ -1,15 +1,20 @@
 use crate::{
-    consts::{BUILD_INFO, COLORS, DEFAULT_INTEGRAL_NUM, DEFAULT_MAX_X, DEFAULT_MIN_X, build},
+    complex_plot::DomainColoring,
+    consts::{BUILD_INFO, DEFAULT_INTEGRAL_NUM, DEFAULT_MAX_X, DEFAULT_MIN_X, build},
+    evaluator::Evaluator,
     function_entry::Riemann,
     function_manager::FunctionManager,
+    hover::{SnapKind, SnapPoint, find_snap_point},
     misc::{EguiHelper, find_intersections, option_vec_printer},
+    tangent,
+    widgets::{ExpressionInput, error_indicator},
 };
 use eframe::App;
 use egui::{
-    Button, CentralPanel, Color32, ComboBox, Context, DragValue, Frame, Key, Layout, Panel, Ui,
-    Vec2, Window,
+    Button, CentralPanel, Color32, ComboBox, Context, CursorIcon, DragValue, Frame, Grid, Key,
+    Layout, Panel, ScrollArea, Ui, Vec2, Window,
 };
-use egui_plot::Plot;
+use egui_plot::{Plot, PlotBounds, PlotPoint, Points};
 
 use emath::{Align, Align2};
 use epaint::{CornerRadius, Margin};
@@ -52,6 +57,33 @@ pub struct AppSettings {
 
     /// Stores current plot pixel width
     pub plot_width: usize,
+
+    /// Stores whether functions are graphed as functions of a complex variable `z`, by domain
+    /// coloring, instead of as functions of `x`
+    pub complex: bool,
+}
+
+impl AppSettings {
+    /// Integral bounds where infinite bounds are limited to the visible part of the plot, used for
+    /// the rectangles displayed under the graph
+    pub fn visible_integral_bounds(&self) -> (f64, f64) {
+        let min_x = match self.integral_min_x.is_finite() {
+            true => self.integral_min_x,
+            false => self.min_x.min(self.integral_max_x),
+        };
+
+        let max_x = match self.integral_max_x.is_finite() {
+            true => self.integral_max_x,
+            false => self.max_x.max(min_x),
+        };
+
+        (min_x, max_x)
+    }
+
+    /// Returns true if either integral bound is infinite
+    pub fn integral_is_infinite(&self) -> bool {
+        !self.integral_min_x.is_finite() | !self.integral_max_x.is_finite()
+    }
 }
 
 impl Default for AppSettings {
@@ -69,6 +101,7 @@ impl Default for AppSettings {
             do_roots: true,
             do_intersections: true,
             plot_width: 0,
+            complex: false,
         }
     }
 }
@@ -86,6 +119,9 @@ struct Opened {
 
     /// Welcome introduction
     pub welcome: bool,
+
+    /// Evaluate window
+    pub evaluate: bool,
 }
 
 impl Default for Opened {
@@ -95,6 +131,7 @@ impl Default for Opened {
             info: false,
             side_panel: true,
             welcome: true,
+            evaluate: false,
         }
     }
 }
@@ -114,9 +151,33 @@ pub struct MathApp {
     settings: AppSettings,
 
     /// Stores intersection points between functions
-    intersections: Vec<egui_plot::PlotPoint>,
+    intersections: Vec<SnapPoint>,
+
+    /// Labels of points the user clicked on to keep them displayed
+    pinned_points: Vec<SnapPoint>,
+
+    /// Input for the lower bound of integrals
+    integral_min_input: ExpressionInput,
+
+    /// Input for the upper bound of integrals
+    integral_max_input: ExpressionInput,
+
+    /// Evaluates functions at specific `x` values
+    evaluator: Evaluator,
+
+    /// Index of the function whose tangent point is being dragged
+    dragging_tangent: Option<usize>,
+
+    /// If the pointer was over a tangent point last frame (disables dragging the plot)
+    tangent_hovered: bool,
+
+    /// Domain coloring of complex functions, displayed instead of graphs in complex mode
+    domain_coloring: DomainColoring,
 }
 
+/// Color intersection points are drawn with (hot pink for visibility)
+const INTERSECTION_COLOR: Color32 = Color32::from_rgb(255, 105, 180);
+
 #[cfg(target_arch = "wasm32")]
 fn get_window() -> web_sys::Window {
     web_sys::window().expect("Could not get web_sys window")
@@ -255,6 +316,13 @@ impl MathApp {
             opened: Opened::default(),
             settings: AppSettings::default(),
             intersections: Vec::new(),
+            pinned_points: Vec::new(),
+            integral_min_input: ExpressionInput::new(DEFAULT_MIN_X).allow_infinite(),
+            integral_max_input: ExpressionInput::new(DEFAULT_MAX_X).allow_infinite(),
+            evaluator: Evaluator::default(),
+            dragging_tangent: None,
+            tangent_hovered: false,
+            domain_coloring: DomainColoring::default(),
         }
     }
 
@@ -289,40 +357,52 @@ impl MathApp {
 
                 let riemann_changed = prev_sum != self.settings.riemann_sum;
 
-                let min_x_old = self.settings.integral_min_x;
-                let max_x_old = self.settings.integral_max_x;
+                // Bounds may use constants defined by the user (such as `2k`)
+                let symbols = self.functions.constant_symbols();
 
-                let (min_x_changed, max_x_changed) = ui
+                let (new_min_x, new_max_x) = ui
                     .horizontal(|ui: &mut Ui| {
-                        // let spacing_mut = ui.spacing_mut();
-
-                        // spacing_mut.item_spacing = Vec2::new(1.0, 0.0);
-                        // spacing_mut.interact_size *= 0.5;
-
                         ui.label("Integral: [");
-                        let min_x_changed = ui
-                            .add(DragValue::new(&mut self.settings.integral_min_x))
-                            .changed();
+                        let new_min_x = self.integral_min_input.show(ui, symbols);
                         ui.label(",");
-                        let max_x_changed = ui
-                            .add(DragValue::new(&mut self.settings.integral_max_x))
-                            .changed();
+                        let new_max_x = self.integral_max_input.show(ui, symbols);
                         ui.label("]");
-                        (min_x_changed, max_x_changed)
+                        (new_min_x, new_max_x)
                     })
                     .inner;
 
-                // Checks integral bounds, and if they are invalid, fix them
-                if self.settings.integral_min_x >= self.settings.integral_max_x {
-                    if max_x_changed {
-                        self.settings.integral_max_x = max_x_old;
-                    } else if min_x_changed {
-                        self.settings.integral_min_x = min_x_old;
+                // Only accept new bounds if the minimum stays below the maximum
+                let min_x = new_min_x.unwrap_or(self.settings.integral_min_x);
+                let max_x = new_max_x.unwrap_or(self.settings.integral_max_x);
+                let (min_x_changed, max_x_changed) = if min_x < max_x {
+                    (new_min_x.is_some(), new_max_x.is_some())
+                } else {
+                    let error = "Error: minimum must be less than maximum".to_owned();
+                    if new_max_x.is_some() {
+                        self.integral_max_input.set_error(error);
                     } else {
-                        // No clue how this would happen, but just in case
-                        self.settings.integral_min_x = DEFAULT_MIN_X;
-                        self.settings.integral_max_x = DEFAULT_MAX_X;
+                        self.integral_min_input.set_error(error);
                     }
+                    (false, false)
+                };
+
+                if min_x_changed {
+                    self.integral_min_input.set_value(min_x);
+                    self.settings.integral_min_x = min_x;
+                }
+
+                if max_x_changed {
+                    self.integral_max_input.set_value(max_x);
+                    self.settings.integral_max_x = max_x;
+                }
+
+                // Display errors from either of the bounds
+                if let Some(error) = self
+                    .integral_min_input
+                    .error()
+                    .or(self.integral_max_input.error())
+                {
+                    error_indicator(ui, error);
                 }
 
                 // Number of Rectangles for Riemann sum
@@ -374,7 +454,10 @@ impl MathApp {
                 );
             });
 
-            if self.functions.display_entries(ui) {
+            if self.functions.display_entries(ui, self.settings.complex) {
+                // Pinned points may no longer lie on their functions
+                self.pinned_points.clear();
+
                 #[cfg(target_arch = "wasm32")]
                 {
                     tracing::info!("Saving function data");
@@ -393,6 +476,20 @@ impl MathApp {
                 }
             }
 
+            // Table of the constants defined by the user
+            let constants = self.functions.constants();
+            if !constants.is_empty() {
+                ui.collapsing("Constants", |ui| {
+                    Grid::new("constants_table").striped(true).show(ui, |ui| {
+                        for (name, value) in constants {
+                            ui.label(name);
+                            ui.label(value.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+
             // Only render if there's enough space
             if ui.available_height() > crate::consts::FONT_SIZE {
                 ui.with_layout(Layout::bottom_up(Align::Min), |ui| {
@@ -445,16 +542,35 @@ impl App for MathApp {
 
                 // Button to add a new function
                 if ui
-                    .add_enabled(
-                        COLORS.len() > self.functions.len(),
-                        Button::new("Add Function"),
-                    )
+                    .add(Button::new("Add Function"))
                     .on_hover_text("Create and graph new function")
                     .clicked()
                 {
                     self.functions.push_empty();
                 }
 
+                // Button to remove functions that are the same as another
+                if ui
+                    .add_enabled(
+                        self.functions.has_duplicates(),
+                        Button::new("Remove Duplicates"),
+                    )
+                    .on_hover_text("Remove functions that are the same as an earlier function")
+                    .clicked()
+                {
+                    self.functions.dedupe();
+                }
+
+                // Toggles graphing functions of a complex variable
+                self.settings.complex.bitxor_assign(
+                    ui.add(Button::new("Complex"))
+                        .on_hover_text(match self.settings.complex {
+                            true => "Graph functions of x",
+                            false => "Graph functions of a complex variable z by domain coloring",
+                        })
+                        .clicked(),
+                );
+
                 // Toggles opening the Help window
                 self.opened.help.bitxor_assign(
                     ui.add(Button::new("Help"))
@@ -465,6 +581,16 @@ impl App for MathApp {
                         .clicked(),
                 );
 
+                // Toggles opening the Evaluate window
+                self.opened.evaluate.bitxor_assign(
+                    ui.add(Button::new("Evaluate"))
+                        .on_hover_text(match self.opened.evaluate {
+                            true => "Close Evaluate Window",
+                            false => "Evaluate functions at a value of x",
+                        })
+                        .clicked(),
+                );
+
                 // Toggles opening the Info window
                 self.opened.info.bitxor_assign(
                     ui.add(Button::new("Info"))
@@ -490,23 +616,58 @@ impl App for MathApp {
 			.collapsible(false)
 			.show(ctx, |ui| {
 				ui.collapsing("Supported Expressions", |ui| {
-					ui.label("abs, signum, sin, cos, tan, asin, acos, atan, sinh, cosh, tanh, floor, round, ceil, trunc, fract, exp, sqrt, cbrt, ln, log2, log10, log");
+					ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+						Grid::new("supported_expressions").striped(true).show(ui, |ui| {
+							for doc in parsing::function_docs() {
+								ui.label(doc.usage);
+								ui.label(doc.description);
+								ui.end_row();
+							}
+						});
+					});
+					ui.label("Operators such as 'integral(t^2, t, 0, x)', 'deriv(sin(t), t)', or 'sum(1/n^2, n, 1, 100)' are calculated numerically at every point. Probabilities can be shaded by integrating a distribution, such as 'normalpdf(x)' with bounds of -1 and 1.");
+					ui.label("Multiplication can be written as '*', '×', or '·', or left out entirely (such as '2x', '2sin(x)', or '(x+1)(x-1)'). Division can be written as '/' or '÷', and powers as '^' or '**'.");
+					ui.label("Functions can also be written in LaTeX (such as '\\frac{1}{2}x^{2}' or '\\sqrt{x}'), which is detected automatically, or in AsciiMath (such as 'frac(1)(2) x^2' or 'sqrt x'). A function's syntax can be chosen in its settings.");
 				});
 
 				ui.collapsing("Supported Constants", |ui| {
-					ui.label("- Euler's number is supported via 'e' or 'E'\n- PI is available through 'pi' or 'π'");
+					ui.label("- Euler's number is supported via 'e' or 'E'\n- PI is available through 'pi' or 'π'\n- Your own constants can be defined like 'k = 9.81' (or 'G = 6.674e-11') and then used in every function, such as '2k*x'. Defined constants are listed in the side panel.");
 				});
 
 				ui.collapsing("Panel", |ui| {
-					ui.label("- The 'Panel' button toggles if the side bar should be shown or not. This can also be accomplished by pressing the 'h' key.\n- The 'Add Function' button adds a new function to be graphed. You can then configure that function in the side panel.\n- The 'Help' button opens and closes this window!\n- The 'Info' button provides information on the build currently running.");
+					ui.label("- The 'Panel' button toggles if the side bar should be shown or not. This can also be accomplished by pressing the 'h' key.\n- The 'Add Function' button adds a new function to be graphed. You can then configure that function in the side panel.\n- The 'Remove Duplicates' button removes functions that are the same as an earlier one, such as '2 * x' after '2x'.\n- The 'Complex' button graphs functions of a complex variable 'z' instead of x.\n- The 'Help' button opens and closes this window!\n- The 'Evaluate' button opens a window with the value of every function at an x value, and tables of values, roots, and extrema.\n- The 'Info' button provides information on the build currently running.");
 				});
 
 				ui.collapsing("Functions", |ui| {
 					ui.label("(From Left to Right)\n`✖` allows you to delete the selected function. Deleting a function is prevented if only 1 function exists.\n`∫` toggles integration.\n`d/dx` toggles the calculation of derivatives.\n`⚙` opens a window to tweak function options.");
 				});
 
+				ui.collapsing("Function Settings", |ui| {
+					ui.label("- Tangent Line: drag the point it touches to move it along the graph. The normal line can be shown with it.\n- Taylor Polynomial: about any center, up to degree 10, optionally with its truncation error f(x) - P(x).\n- Derivatives: any order, each in its own color. Those that can't be found exactly are approximated numerically.\n- Accumulation Function: F(x), the integral from a chosen lower limit to x. Hover over it to see its value.\n- 'Simplify', 'Expand', and 'Factor' rewrite the function, such as 'x^2-1' into '(x + 1)·(x - 1)'.");
+				});
+
+				ui.collapsing("Integrals", |ui| {
+					ui.label("- Bounds accept expressions such as 'pi/2', '2e', or your own constants.\n- Bounds can be 'inf' or '-inf'. Integrals that don't converge are reported as diverging.");
+				});
+
+				ui.collapsing("Named Functions", |ui| {
+					ui.label("- Functions can be given a name like 'f(x) = x^2', after which other functions can use them, such as 'g(x) = f(x+1) - 3'.\n- Functions can be used by any other function, as long as they don't end up depending on themselves.");
+				});
+
+				ui.collapsing("Autocompletion", |ui| {
+					ui.label("- Names are completed from the built-in functions and your own functions and constants, the ones you use most first.\n- Tab, Enter, or the right arrow (at the end of the text) accepts a completion, and the up and down arrows choose between several.\n- Each completion shows how it's called and what it does.\n- Completions work anywhere in the text, and functions are completed with their closing parenthesis.");
+				});
+
+				ui.collapsing("Evaluate", |ui| {
+					ui.label("- 'Go to' moves the graph to the x value.\n- Fractions, multiples of π or e, and square roots (such as 'π/2' or '√2/2') are shown exactly.\n- Every root of a polynomial is listed, including complex ones, along with its multiplicity.");
+				});
+
+				ui.collapsing("Complex Mode", |ui| {
+					ui.label("- Functions of 'z' (such as 'sqrt(z)' or '(z^2 + 1)/(z - 2)') can use 'i', the imaginary unit.\n- The plane is colored by the first visible function, with the hue showing its argument and the brightness its magnitude.\n- Zeros and poles are labeled with their orders.");
+				});
+
 				ui.collapsing("Other", |ui| {
-					ui.label("- Extrema (local minimums and maximums) and Roots (intersections with the x-axis) are displayed though yellow and light blue points respectively located on the graph. These can be toggled in the side panel.");
+					ui.label("- Extrema (local minimums and maximums) and Roots (intersections with the x-axis) are displayed though yellow and light blue points respectively located on the graph. These can be toggled in the side panel.\n- Hovering near a function, root, extremum, or intersection shows its exact coordinates. Click to pin the label in place, and click it again to unpin it.\n- Errors underline the characters causing them. A 'Fix' button applies clear fixes, such as for 'sinn(x)'.\n- Each function is shown typeset beneath its text box, along with its derivative when that's shown. 'Copy LaTeX' copies it as LaTeX.");
 				});
 			});
 
@@ -543,6 +704,10 @@ impl App for MathApp {
                 }
             });
 
+        // Window for evaluating functions at a specific x value
+        self.evaluator
+            .window(ctx, &mut self.opened.evaluate, &mut self.functions);
+
         // If side panel is enabled, show it.
         if self.opened.side_panel {
             self.side_panel(ctx);
@@ -567,12 +732,35 @@ impl App for MathApp {
                     .set_margin_fraction(emath::Vec2::ZERO)
                     .data_aspect(1.0)
                     .include_y(0)
+                    // Hovered points are labeled by `find_snap_point` instead
+                    .show_x(false)
+                    .show_y(false)
+                    // Dragging a tangent point moves the point instead of the plot
+                    .allow_drag(!self.tangent_hovered && self.dragging_tangent.is_none())
                     .show(ui, |plot_ui| {
                         let (min_x, max_x): (f64, f64) = {
                             let bounds = plot_ui.plot_bounds();
                             (bounds.min()[0], bounds.max()[0])
                         };
 
+                        // Move the plot so the requested x value is centered
+                        if let Some(x) = self.evaluator.take_pan_request() {
+                            let bounds = plot_ui.plot_bounds();
+                            let half_width = bounds.width() / 2.0;
+                            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                                [x - half_width, bounds.min()[1]],
+                                [x + half_width, bounds.max()[1]],
+                            ));
+                            plot_ui.ctx().request_repaint();
+                        }
+
+                        // Complex functions are displayed by coloring the plane instead of graphing
+                        if self.settings.complex {
+                            self.domain_coloring.display(plot_ui, &self.functions);
+                            self.last_info.0 = None;
+                            return;
+                        }
+
                         let min_max_changed =
                             (min_x != self.settings.min_x) | (max_x != self.settings.max_x);
                         let did_zoom = (max_x - min_x).abs()
@@ -592,16 +780,77 @@ impl App for MathApp {
                                 )
                             });
 
-                        let area: Vec<Option<f64>> = self
+                        let area: Vec<Option<String>> = self
                             .functions
                             .get_entries()
                             .iter()
-                            .enumerate()
-                            .map(|(i, (_, function))| {
-                                function.display(plot_ui, &self.settings, COLORS[i])
-                            })
+                            .map(|(_, function)| function.display(plot_ui, &self.settings))
                             .collect();
 
+                        // Mark where each function is evaluated in the Evaluate window
+                        if self.opened.evaluate
+                            && let Some(x) = self.evaluator.marker_x()
+                        {
+                            for (_, function) in self.functions.get_entries_mut().iter_mut() {
+                                if !function.visible {
+                                    continue;
+                                }
+
+                                if let Some(y) = function.eval(0, x).filter(|y| y.is_finite()) {
+                                    plot_ui.points(
+                                        Points::new("", vec![[x, y]])
+                                            .color(function.color)
+                                            .radius(6.0),
+                                    );
+                                }
+                            }
+                        }
+
+                        // Display tangent lines, whose points can be dragged along their function
+                        let pointer = plot_ui.pointer_coordinate();
+                        let mut hovered_tangent = None;
+                        for (i, (_, function)) in
+                            self.functions.get_entries_mut().iter_mut().enumerate()
+                        {
+                            if !function.visible {
+                                continue;
+                            }
+
+                            if self.dragging_tangent == Some(i)
+                                && let Some(pointer) = pointer
+                            {
+                                function.tangent_x = pointer.x;
+                            }
+
+                            let Some(tangent) = function.tangent_line() else {
+                                continue;
+                            };
+
+                            if let Some(pointer) = pointer
+                                && plot_ui
+                                    .screen_from_plot(tangent.point)
+                                    .distance(plot_ui.screen_from_plot(pointer))
+                                    <= tangent::GRAB_DISTANCE
+                            {
+                                hovered_tangent = Some(i);
+                            }
+
+                            let grabbed =
+                                (self.dragging_tangent == Some(i)) | (hovered_tangent == Some(i));
+                            tangent.display(plot_ui, function.color, function.normal, grabbed);
+                        }
+
+                        if plot_ui.response().drag_started() {
+                            self.dragging_tangent = hovered_tangent;
+                        } else if !plot_ui.response().dragged() {
+                            self.dragging_tangent = None;
+                        }
+
+                        self.tangent_hovered = hovered_tangent.is_some();
+                        if self.tangent_hovered | self.dragging_tangent.is_some() {
+                            plot_ui.ctx().set_cursor_icon(CursorIcon::Grab);
+                        }
+
                         // Calculate and display intersections between functions
                         if self.settings.do_intersections {
                             let entries = self.functions.get_entries();
@@ -619,9 +868,19 @@ impl App for MathApp {
                                     let (_, func1) = visible_entries[i];
                                     let (_, func2) = visible_entries[j];
 
-                                    let mut intersections =
-                                        find_intersections(&func1.back_data, &func2.back_data);
-                                    self.intersections.append(&mut intersections);
+                                    let name = format!("{} and {}", func1.label(), func2.label());
+                                    self.intersections.extend(
+                                        find_intersections(&func1.back_data, &func2.back_data)
+                                            .into_iter()
+                                            .map(|point| {
+                                                SnapPoint::new(
+                                                    point,
+                                                    SnapKind::Intersection,
+                                                    name.clone(),
+                                                    INTERSECTION_COLOR,
+                                                )
+                                            }),
+                                    );
                                 }
                             }
 
@@ -629,14 +888,48 @@ impl App for MathApp {
                             if !self.intersections.is_empty() {
                                 plot_ui.points(
                                     self.intersections
-                                        .clone()
+                                        .iter()
+                                        .map(|intersection| intersection.point)
+                                        .collect::<Vec<PlotPoint>>()
                                         .to_points()
-                                        .color(Color32::from_rgb(255, 105, 180)) // Hot pink for visibility
+                                        .color(INTERSECTION_COLOR)
                                         .radius(6.0),
                                 );
                             }
+                        } else {
+                            self.intersections.clear();
+                        }
+
+                        // Label the point being hovered over, clicking it pins (or unpins) its label
+                        let hovered = find_snap_point(
+                            plot_ui,
+                            &mut self.functions,
+                            &self.intersections,
+                            self.settings.do_extrema,
+                            self.settings.do_roots,
+                        );
+
+                        if let Some(hovered) = hovered {
+                            if plot_ui.response().clicked() {
+                                match self
+                                    .pinned_points
+                                    .iter()
+                                    .position(|pinned| pinned.same_point(&hovered))
+                                {
+                                    Some(i) => {
+                                        self.pinned_points.remove(i);
+                                    }
+                                    None => self.pinned_points.push(hovered.clone()),
+                                }
+                            }
+
+                            hovered.display(plot_ui);
                         }
 
+                        self.pinned_points
+                            .iter()
+                            .for_each(|pinned| pinned.display(plot_ui));
+
                         self.last_info.0 = if area.iter().any(|e| e.is_some()) {
                             Some(format!("Area: {}", option_vec_printer(area.as_slice())))
                         } else {
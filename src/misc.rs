@@ -1,6 +1,8 @@
+use crate::consts::COLORS;
 use egui::Id;
 use egui_plot::{Line, PlotPoint, PlotPoints, Points};
 use emath::Pos2;
+use epaint::{Color32, Hsva};
 use itertools::Itertools;
 use parsing::FlatExWrapper;
 
@@ -158,18 +160,66 @@ pub fn step_helper(max_i: usize, min_x: f64, step: f64) -> Vec<f64> {
         .collect()
 }
 
-// TODO: use in hovering over points
-/// Attempts to see what variable `x` is almost
-#[allow(dead_code)]
-pub fn almost_variable(x: f64) -> Option<char> {
-    const EPSILON: f32 = f32::EPSILON * 2.0;
-    if emath::almost_equal(x as f32, std::f32::consts::E, EPSILON) {
-        Some('e')
-    } else if emath::almost_equal(x as f32, std::f32::consts::PI, EPSILON) {
-        Some('π')
+/// Integrates `f` from `a` to `b` using the trapezoidal rule with `n` trapezoids
+pub fn trapezoidal_integral(mut f: impl FnMut(f64) -> f64, a: f64, b: f64, n: usize) -> f64 {
+    let n = n.max(1);
+    let step = (b - a) / (n as f64);
+    let inner: f64 = (1..n).map(|i| f(a + (i as f64) * step)).sum();
+    step * (((f(a) + f(b)) / 2.0) + inner)
+}
+
+/// Accumulates the area under `data` using the trapezoidal rule, where `start` is the area up to
+/// the first point. Once `data` is undefined, the rest of the accumulation is as well.
+pub fn accumulate(data: &[PlotPoint], start: f64) -> Vec<PlotPoint> {
+    let Some(first) = data.first() else {
+        return Vec::new();
+    };
+
+    let mut area = start;
+    std::iter::once(PlotPoint::new(first.x, area))
+        .chain(data.iter().tuple_windows().map(|(prev, curr)| {
+            area += (curr.x - prev.x) * (prev.y + curr.y) / 2.0;
+            PlotPoint::new(curr.x, area)
+        }))
+        .collect()
+}
+
+/// Linearly interpolates the `y` value of `data` (sorted by `x`) at `x`, `None` if `x` is outside
+/// of `data` or it is undefined there
+pub fn interpolate(data: &[PlotPoint], x: f64) -> Option<f64> {
+    let i = data.partition_point(|point| point.x < x);
+    let next = data.get(i)?;
+    let y = if next.x == x {
+        next.y
     } else {
-        None
+        let prev = data.get(i.checked_sub(1)?)?;
+        let t = (x - prev.x) / (next.x - prev.x);
+        prev.y + t * (next.y - prev.y)
+    };
+
+    y.is_finite().then_some(y)
+}
+
+/// Returns the `i`th color of the plotting palette. The first colors come from [`COLORS`], after
+/// which new hues are generated by stepping around the color wheel by the golden angle so that
+/// neighboring colors stay distinguishable no matter how many functions exist
+pub fn palette_color(i: usize) -> Color32 {
+    if let Some(color) = COLORS.get(i) {
+        return *color;
     }
+
+    const GOLDEN_ANGLE: f32 = 0.381_966_02; // 1 - (1 / golden ratio), as a fraction of a turn
+    let n = (i - COLORS.len()) as f32;
+    let hue = (n * GOLDEN_ANGLE).fract();
+
+    // Alternate saturation and value slightly so hues that land close together still differ
+    let (saturation, value) = match (i - COLORS.len()) % 3 {
+        0 => (0.75, 0.95),
+        1 => (0.55, 0.85),
+        _ => (0.9, 0.7),
+    };
+
+    Hsva::new(hue, saturation, value, 1.0).into()
 }
 
 pub const HASH_LENGTH: usize = 8;
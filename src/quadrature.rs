@@ -0,0 +1,256 @@
+use std::f64::consts::FRAC_PI_2;
+
+/// Largest `t` that tanh-sinh quadrature samples at. Nodes at `t = 4` are within ~1e-37 of the bounds.
+const MAX_T: f64 = 4.0;
+
+/// Number of times the step between nodes is halved before giving up on reaching `TOLERANCE`
+const MAX_LEVEL: i32 = 10;
+
+/// Relative difference between successive estimates that is considered converged
+const TOLERANCE: f64 = 1e-10;
+
+/// Most pieces a tail is split into before the rest of it is estimated
+const MAX_PIECES: i32 = 64;
+
+/// Width (relative to the point they approach) that pieces stop at, as points closer than this
+/// can't be told apart precisely
+const MIN_PIECE_WIDTH: f64 = 1e-12;
+
+/// Largest ratio between successive pieces of a tail for the integral to be considered
+/// convergent. Tails that shrink slower than this (such as those of `1/x`) are divergent, or sum to
+/// something too large to be meaningful.
+const MAX_TAIL_RATIO: f64 = 0.9999;
+
+/// Most singularities an integral is split around, past which the function is considered undefined
+/// over part of the bounds
+const MAX_SINGULARITIES: usize = 16;
+
+/// How many times larger than the samples on either side a peak between them must be for it to be
+/// considered a pole
+const POLE_GROWTH: f64 = 1e6;
+
+/// Number of steps of the golden-section search for peaks, which narrows a peak down to well below
+/// the precision of `f64`
+const PEAK_STEPS: usize = 100;
+
+/// Integrates `f` from `a` to `b` (both finite) using tanh-sinh quadrature. Returns `None` if `f` is
+/// infinite between them, or `NaN` if `f` is undefined.
+fn tanh_sinh(f: &mut impl FnMut(f64) -> f64, a: f64, b: f64) -> Option<f64> {
+    let (middle, radius) = ((a + b) / 2.0, (b - a) / 2.0);
+    let mut previous: Option<f64> = None;
+    let mut estimate = 0.0;
+
+    for level in 0..=MAX_LEVEL {
+        let h = 0.5_f64.powi(level);
+        let n = (MAX_T / h).round() as i64;
+
+        let mut sum = 0.0;
+        for k in -n..=n {
+            let t = (k as f64) * h;
+            let u = FRAC_PI_2 * t.sinh();
+            let weight = FRAC_PI_2 * t.cosh() / u.cosh().powi(2);
+
+            let term = weight * f(middle + radius * u.tanh());
+            if term.is_nan() {
+                return Some(f64::NAN);
+            } else if !term.is_finite() {
+                return None;
+            }
+            sum += term;
+        }
+
+        estimate = sum * h * radius;
+        if let Some(previous) = previous
+            && (estimate - previous).abs() <= TOLERANCE * estimate.abs().max(1.0)
+        {
+            break;
+        }
+        previous = Some(estimate);
+    }
+
+    Some(estimate)
+}
+
+/// Integrates `f` over the tail made of `pieces(0)`, `pieces(1)`, and so on, which approach a point
+/// where `f` may be singular (or infinity). Pieces are added until they stop changing the sum
+/// relative to [`TOLERANCE`], after which the rest of the tail is estimated as a geometric series
+/// from the ratio between the last two pieces. Returns `None` if the pieces don't shrink, as the
+/// integral diverges.
+fn tail(
+    f: &mut impl FnMut(f64) -> f64,
+    pieces: impl Fn(i32) -> (f64, f64),
+    min_width: f64,
+) -> Option<f64> {
+    let mut sum = 0.0;
+    let mut last: f64 = 0.0;
+    let mut ratio: f64 = 0.0;
+
+    for k in 0..MAX_PIECES {
+        let (start, end) = pieces(k);
+        if (end - start) <= min_width {
+            break;
+        }
+
+        let piece = tanh_sinh(f, start, end)?;
+        if piece.is_nan() {
+            return Some(f64::NAN);
+        }
+
+        sum += piece;
+        if piece.abs() <= TOLERANCE * sum.abs() {
+            return Some(sum);
+        }
+
+        if k > 0 {
+            ratio = piece / last;
+        }
+        last = piece;
+    }
+
+    (ratio.abs() < MAX_TAIL_RATIO).then(|| sum + last * ratio / (1.0 - ratio))
+}
+
+/// Integrates `f` from `a` to `b` (where `a < b`), either of which may be infinite or a
+/// singularity. Finite bounds are approached by pieces that halve in width, and infinite ones by
+/// pieces that double in width, so that the integral converges if those pieces shrink.
+fn integrate_piece(f: &mut impl FnMut(f64) -> f64, a: f64, b: f64) -> Option<f64> {
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => {
+            let half = (b - a) / 2.0;
+            let halving = |k: i32| half * 0.5_f64.powi(k);
+            let left = tail(
+                f,
+                |k| (a + halving(k + 1), a + halving(k)),
+                MIN_PIECE_WIDTH * a.abs(),
+            );
+            let right = tail(
+                f,
+                |k| (b - halving(k), b - halving(k + 1)),
+                MIN_PIECE_WIDTH * b.abs(),
+            );
+            Some(left? + right?)
+        }
+        (true, false) => {
+            let scale = a.abs().max(1.0);
+            let doubling = |k: i32| scale * 2.0_f64.powi(k);
+            Some(
+                integrate_piece(f, a, a + scale)?
+                    + tail(f, |k| (a + doubling(k), a + doubling(k + 1)), 0.0)?,
+            )
+        }
+        (false, true) => {
+            let scale = b.abs().max(1.0);
+            let doubling = |k: i32| scale * 2.0_f64.powi(k);
+            Some(
+                integrate_piece(f, b - scale, b)?
+                    + tail(f, |k| (b - doubling(k + 1), b - doubling(k)), 0.0)?,
+            )
+        }
+        (false, false) => Some(integrate_piece(f, a, 0.0)? + integrate_piece(f, 0.0, b)?),
+    }
+}
+
+/// Finds where `|f|` is largest between `a` and `b` using a golden-section search
+fn peak(f: &mut impl FnMut(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut x1, mut x2) = (b - ratio * (b - a), a + ratio * (b - a));
+    let (mut y1, mut y2) = (f(x1).abs(), f(x2).abs());
+
+    for _ in 0..PEAK_STEPS {
+        if y1 > y2 {
+            (b, x2, y2) = (x2, x1, y1);
+            x1 = b - ratio * (b - a);
+            y1 = f(x1).abs();
+        } else {
+            (a, x1, y1) = (x1, x2, y2);
+            x2 = a + ratio * (b - a);
+            y2 = f(x2).abs();
+        }
+    }
+
+    if y1 > y2 { x1 } else { x2 }
+}
+
+/// Finds the singularities of `f` from its values at `xs` (in increasing order): the points where
+/// it isn't finite, along with poles that fall between them. A pole is found where `|f|` peaks
+/// between neighboring points, and is much larger at its peak than at the points on either side.
+pub fn find_singularities(mut f: impl FnMut(f64) -> f64, xs: &[f64]) -> Vec<f64> {
+    let magnitudes: Vec<f64> = xs.iter().map(|&x| f(x).abs()).collect();
+    let mut singularities = Vec::new();
+
+    for i in 0..xs.len() {
+        if !magnitudes[i].is_finite() {
+            singularities.push(xs[i]);
+            continue;
+        }
+
+        // Points on either side of a sample that is larger than its neighbors
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(xs.len() - 1));
+        if (left == right)
+            | (magnitudes[left] > magnitudes[i])
+            | (magnitudes[right] > magnitudes[i])
+        {
+            continue;
+        }
+
+        let x = peak(&mut f, xs[left], xs[right]);
+        if f(x).abs() > POLE_GROWTH * magnitudes[left].max(magnitudes[right]) {
+            singularities.push(x);
+        }
+    }
+    singularities
+}
+
+/// Integrates `f` from `a` to `b`, either of which may be infinite. The integral is split around
+/// `singularities` (points where `f` is undefined) so that each becomes a singularity at a bound.
+/// Returns infinity if the integral diverges and `NaN` if `f` is undefined over part of the bounds.
+pub fn improper_integral(
+    mut f: impl FnMut(f64) -> f64,
+    a: f64,
+    b: f64,
+    singularities: &[f64],
+) -> f64 {
+    if a > b {
+        return -improper_integral(f, b, a, singularities);
+    }
+
+    let mut splits: Vec<f64> = singularities
+        .iter()
+        .copied()
+        .filter(|x| (a < *x) && (*x < b))
+        .collect();
+    if splits.len() > MAX_SINGULARITIES {
+        return f64::NAN;
+    }
+    splits.sort_by(f64::total_cmp);
+    splits.dedup();
+
+    let bounds: Vec<f64> = std::iter::once(a)
+        .chain(splits)
+        .chain(std::iter::once(b))
+        .collect();
+
+    let mut total = 0.0;
+    for (start, end) in bounds.iter().zip(bounds.iter().skip(1)) {
+        if start == end {
+            continue;
+        }
+
+        match integrate_piece(&mut f, *start, *end) {
+            Some(area) => total += area,
+            None => return f64::INFINITY,
+        }
+    }
+    total
+}
+
+/// Formats the area of an integral for display, describing it if the integral doesn't converge
+pub fn format_area(area: f64) -> String {
+    if area.is_nan() {
+        "undefined".to_owned()
+    } else if area.is_infinite() {
+        "diverges".to_owned()
+    } else {
+        emath::round_to_decimals(area, 8).to_string()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::exact::{format_exact, format_term};
+use egui_plot::{Line, PlotPoint, PlotUi, Points, Text};
+use emath::Align2;
+use epaint::Color32;
+
+/// Maximum distance (in pixels) from a tangent's point that the pointer can grab it from
+pub const GRAB_DISTANCE: f32 = 12.0;
+
+/// Equation of the line passing through `point` with a slope of `slope`
+pub fn line_equation(point: PlotPoint, slope: f64) -> String {
+    if !slope.is_finite() {
+        return format!("x = {}", format_exact(point.x));
+    }
+
+    let intercept = point.y - (slope * point.x);
+    if slope == 0.0 {
+        return format!("y = {}", format_exact(intercept));
+    }
+
+    let term = format_term(slope, "x");
+    if intercept == 0.0 {
+        format!("y = {}", term)
+    } else if intercept < 0.0 {
+        format!("y = {} - {}", term, format_exact(-intercept))
+    } else {
+        format!("y = {} + {}", term, format_exact(intercept))
+    }
+}
+
+/// Line tangent to a function at `point`
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct TangentLine {
+    pub point: PlotPoint,
+    pub slope: f64,
+}
+
+impl TangentLine {
+    /// Creates the tangent at `(x, y)` where the derivative is `slope`, `None` if the function is
+    /// undefined at `x`
+    pub fn new(x: f64, y: f64, slope: f64) -> Option<Self> {
+        if !x.is_finite() | !y.is_finite() | slope.is_nan() {
+            return None;
+        }
+
+        Some(Self {
+            point: PlotPoint::new(x, y),
+            slope,
+        })
+    }
+
+    /// Slope of the line perpendicular to the tangent
+    pub fn normal_slope(&self) -> f64 {
+        if self.slope == 0.0 {
+            f64::INFINITY
+        } else {
+            -1.0 / self.slope
+        }
+    }
+
+    /// Equation of the tangent line
+    pub fn equation(&self) -> String {
+        line_equation(self.point, self.slope)
+    }
+
+    /// Equation of the normal line
+    pub fn normal_equation(&self) -> String {
+        line_equation(self.point, self.normal_slope())
+    }
+
+    /// Draws the line passing through the tangent's point with a slope of `slope` across the plot
+    fn line(&self, plot_ui: &mut PlotUi, slope: f64, color: Color32) {
+        let bounds = plot_ui.plot_bounds();
+        let points = if slope.is_finite() {
+            let y = |x: f64| self.point.y + slope * (x - self.point.x);
+            vec![
+                [bounds.min()[0], y(bounds.min()[0])],
+                [bounds.max()[0], y(bounds.max()[0])],
+            ]
+        } else {
+            vec![
+                [self.point.x, bounds.min()[1]],
+                [self.point.x, bounds.max()[1]],
+            ]
+        };
+
+        plot_ui.line(Line::new("", points).color(color).width(2.0));
+    }
+
+    /// Draws the tangent line (and the normal line if `normal` is set) along with their equations.
+    /// `grabbed` enlarges the point to show it is being hovered or dragged.
+    pub fn display(&self, plot_ui: &mut PlotUi, color: Color32, normal: bool, grabbed: bool) {
+        self.line(plot_ui, self.slope, color);
+
+        let mut label = format!("Tangent: {}", self.equation());
+        if normal {
+            self.line(plot_ui, self.normal_slope(), color.gamma_multiply(0.6));
+            label.push_str(&format!("\nNormal: {}", self.normal_equation()));
+        }
+
+        plot_ui.points(
+            Points::new("", vec![[self.point.x, self.point.y]])
+                .color(color)
+                .radius(if grabbed { 8.0 } else { 6.0 }),
+        );
+
+        plot_ui.text(
+            Text::new("", self.point, label)
+                .anchor(Align2::LEFT_TOP)
+                .color(color),
+        );
+    }
+}
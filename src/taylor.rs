@@ -0,0 +1,97 @@
+use crate::exact::{format_exact, format_term};
+use std::fmt;
+
+/// Taylor polynomial of a function about `center`
+#[derive(PartialEq, Debug, Clone)]
+pub struct TaylorPolynomial {
+    pub center: f64,
+
+    /// Coefficient of each `(x - center)^k` term, starting with `k = 0`
+    coefficients: Vec<f64>,
+}
+
+impl TaylorPolynomial {
+    /// Creates the polynomial from `derivatives`, where `derivatives[k]` is the `k`th derivative of
+    /// the function at `center`. Returns `None` if any of them are undefined.
+    pub fn new(center: f64, derivatives: &[f64]) -> Option<Self> {
+        if !center.is_finite() | derivatives.is_empty() | !derivatives.iter().all(|d| d.is_finite())
+        {
+            return None;
+        }
+
+        let mut factorial = 1.0;
+        let coefficients = derivatives
+            .iter()
+            .enumerate()
+            .map(|(k, derivative)| {
+                if k > 1 {
+                    factorial *= k as f64;
+                }
+                derivative / factorial
+            })
+            .collect();
+
+        Some(Self {
+            center,
+            coefficients,
+        })
+    }
+
+    /// Evaluates the polynomial at `x` using Horner's method
+    pub fn eval(&self, x: f64) -> f64 {
+        let offset = x - self.center;
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, coefficient| (acc * offset) + coefficient)
+    }
+}
+
+impl fmt::Display for TaylorPolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let base = if self.center == 0.0 {
+            "x".to_owned()
+        } else if self.center < 0.0 {
+            format!("(x + {})", format_exact(-self.center))
+        } else {
+            format!("(x - {})", format_exact(self.center))
+        };
+
+        write!(f, "P(x) = ")?;
+
+        let mut first = true;
+        for (k, &coefficient) in self.coefficients.iter().enumerate() {
+            if coefficient == 0.0 {
+                continue;
+            }
+
+            if !first {
+                write!(f, "{}", if coefficient < 0.0 { " - " } else { " + " })?;
+            }
+
+            // Later terms write their sign separately
+            let coefficient = if first {
+                coefficient
+            } else {
+                coefficient.abs()
+            };
+            match k {
+                0 => write!(f, "{}", format_exact(coefficient))?,
+                1 => write!(f, "{}", format_term(coefficient, &base))?,
+                _ => write!(
+                    f,
+                    "{}",
+                    format_term(coefficient, &format!("{}^{}", base, k))
+                )?,
+            }
+
+            first = false;
+        }
+
+        if first {
+            write!(f, "0")?;
+        }
+
+        Ok(())
+    }
+}
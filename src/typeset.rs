@@ -0,0 +1,385 @@
+use egui::{Color32, FontId, Galley, Response, Sense, Stroke, Vec2, vec2};
+use parsing::{BinaryOp, Constant, Expr};
+use std::sync::Arc;
+
+/// Size of exponents and limits relative to the text around them
+const SCRIPT_SCALE: f32 = 0.7;
+
+/// Size of `∫` and `Σ` relative to the text around them
+const BIG_OPERATOR_SCALE: f32 = 1.6;
+
+/// Width of the lines drawn for fraction bars, radicals, and absolute values
+const LINE_WIDTH: f32 = 1.0;
+
+/// Something drawn as part of a typeset expression
+enum Item {
+    /// Text whose top left corner is at the offset
+    Text(Vec2, Arc<Galley>),
+    Line(Vec2, Vec2),
+}
+
+/// A typeset piece of an expression. Items are positioned relative to the left end of the block's
+/// axis, the height that fraction bars are drawn at and that blocks are lined up on when placed in a
+/// row.
+struct Block {
+    items: Vec<Item>,
+    width: f32,
+
+    /// Height of the block above its axis
+    ascent: f32,
+
+    /// Height of the block below its axis
+    descent: f32,
+}
+
+impl Block {
+    const fn empty() -> Self {
+        Self {
+            items: Vec::new(),
+            width: 0.0,
+            ascent: 0.0,
+            descent: 0.0,
+        }
+    }
+
+    fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    /// The block's items moved by `offset`
+    fn translated(self, offset: Vec2) -> impl Iterator<Item = Item> {
+        self.items.into_iter().map(move |item| match item {
+            Item::Text(position, galley) => Item::Text(position + offset, galley),
+            Item::Line(from, to) => Item::Line(from + offset, to + offset),
+        })
+    }
+}
+
+/// Lays out expressions into [`Block`]s
+struct Typesetter<'a> {
+    ui: &'a egui::Ui,
+    color: Color32,
+}
+
+impl Typesetter<'_> {
+    fn text(&self, text: &str, size: f32) -> Block {
+        let galley = self.ui.painter().layout_no_wrap(
+            text.to_owned(),
+            FontId::proportional(size),
+            self.color,
+        );
+        let height = galley.size().y;
+        Block {
+            width: galley.size().x,
+            ascent: height / 2.0,
+            descent: height / 2.0,
+            items: vec![Item::Text(vec2(0.0, -height / 2.0), galley)],
+        }
+    }
+
+    /// Empty space `width` wide
+    fn space(&self, width: f32) -> Block {
+        Block {
+            width,
+            ..Block::empty()
+        }
+    }
+
+    /// Places `blocks` next to each other, lined up on their axes
+    fn row(&self, blocks: impl IntoIterator<Item = Block>) -> Block {
+        let mut result = Block::empty();
+        for block in blocks {
+            let x = result.width;
+            result.width += block.width;
+            result.ascent = result.ascent.max(block.ascent);
+            result.descent = result.descent.max(block.descent);
+            result.items.extend(block.translated(vec2(x, 0.0)));
+        }
+        result
+    }
+
+    /// Wraps `inner` in parentheses as tall as it is
+    fn parens(&self, inner: Block, size: f32) -> Block {
+        let paren_size = size.max(inner.height() * 0.85);
+        self.row([
+            self.text("(", paren_size),
+            inner,
+            self.text(")", paren_size),
+        ])
+    }
+
+    /// Lays out `expr`, wrapped in parentheses if `parens` is true
+    fn grouped(&self, expr: &Expr, parens: bool, size: f32) -> Block {
+        let block = self.expr(expr, size);
+        match parens {
+            true => self.parens(block, size),
+            false => block,
+        }
+    }
+
+    /// Stacks `numerator` over `denominator` with a bar between them
+    fn fraction(&self, numerator: Block, denominator: Block, size: f32) -> Block {
+        let gap = size * 0.15;
+        let width = numerator.width.max(denominator.width) + (gap * 2.0);
+        let numerator_offset = vec2((width - numerator.width) / 2.0, -gap - numerator.descent);
+        let denominator_offset = vec2((width - denominator.width) / 2.0, gap + denominator.ascent);
+
+        let mut items = vec![Item::Line(Vec2::ZERO, vec2(width, 0.0))];
+        let (ascent, descent) = (gap + numerator.height(), gap + denominator.height());
+        items.extend(numerator.translated(numerator_offset));
+        items.extend(denominator.translated(denominator_offset));
+        Block {
+            items,
+            width,
+            ascent,
+            descent,
+        }
+    }
+
+    /// Places `script` raised (or lowered, if `raise` is negative) `raise` above the right of `base`
+    fn script(&self, base: Block, script: Block, raise: f32) -> Block {
+        let x = base.width;
+        let mut result = Block {
+            width: base.width + script.width,
+            ascent: base.ascent.max(raise + script.ascent),
+            descent: base.descent.max(script.descent - raise),
+            items: Vec::new(),
+        };
+        result.items.extend(base.translated(Vec2::ZERO));
+        result.items.extend(script.translated(vec2(x, -raise)));
+        result
+    }
+
+    /// Raises `exponent` to the upper right of `base`
+    fn superscript(&self, base: Block, exponent: Block) -> Block {
+        let raise = base.ascent * 0.8;
+        self.script(base, exponent, raise)
+    }
+
+    /// Draws a radical sign over `inner`, with `index` (such as the 3 of a cube root) in its crook
+    fn radical(&self, inner: Block, index: Option<Block>, size: f32) -> Block {
+        let pad = size * 0.15;
+        let hook = size * 0.5;
+        let top = -(inner.ascent + pad);
+        let bottom = inner.descent;
+
+        // Indices are placed above the radical's tick, moving everything else to make room
+        let index_width = index.as_ref().map_or(0.0, |index| index.width);
+        let x = (index_width - (hook * 0.4)).max(0.0);
+
+        let mut items = vec![
+            Item::Line(vec2(x, 0.0), vec2(x + (hook * 0.4), bottom)),
+            Item::Line(vec2(x + (hook * 0.4), bottom), vec2(x + hook, top)),
+            Item::Line(vec2(x + hook, top), vec2(x + hook + inner.width + pad, top)),
+        ];
+
+        let mut ascent = -top + LINE_WIDTH;
+        if let Some(index) = index {
+            let index_offset = vec2(0.0, -index.descent - pad);
+            ascent = ascent.max(pad + index.height());
+            items.extend(index.translated(index_offset));
+        }
+
+        let (width, descent) = (x + hook + inner.width + pad, inner.descent);
+        items.extend(inner.translated(vec2(x + hook + (pad / 2.0), 0.0)));
+        Block {
+            items,
+            width,
+            ascent,
+            descent,
+        }
+    }
+
+    /// Draws vertical bars on either side of `inner`, as in absolute values
+    fn bars(&self, inner: Block, size: f32) -> Block {
+        let pad = size * 0.15;
+        let (top, bottom) = (-inner.ascent, inner.descent);
+        let right = inner.width + (pad * 3.0);
+
+        let mut items = vec![
+            Item::Line(vec2(pad, top), vec2(pad, bottom)),
+            Item::Line(vec2(right - pad, top), vec2(right - pad, bottom)),
+        ];
+        let (ascent, descent) = (inner.ascent, inner.descent);
+        items.extend(inner.translated(vec2(pad * 1.5, 0.0)));
+        Block {
+            items,
+            width: right,
+            ascent,
+            descent,
+        }
+    }
+
+    /// Draws a large operator `symbol` (such as `∫`) with `upper` and `lower` limits
+    fn big_operator(&self, symbol: &str, lower: Block, upper: Block, size: f32) -> Block {
+        let symbol = self.text(symbol, size * BIG_OPERATOR_SCALE);
+        let width = symbol.width.max(lower.width).max(upper.width);
+
+        let symbol_offset = vec2((width - symbol.width) / 2.0, 0.0);
+        let upper_offset = vec2((width - upper.width) / 2.0, -symbol.ascent - upper.descent);
+        let lower_offset = vec2((width - lower.width) / 2.0, symbol.descent + lower.ascent);
+
+        let ascent = symbol.ascent + upper.height();
+        let descent = symbol.descent + lower.height();
+        let mut items = Vec::new();
+        items.extend(symbol.translated(symbol_offset));
+        items.extend(upper.translated(upper_offset));
+        items.extend(lower.translated(lower_offset));
+        Block {
+            items,
+            width,
+            ascent,
+            descent,
+        }
+    }
+
+    /// Lays out a call to `name` with `args`
+    fn call(&self, name: &str, args: &[Expr], size: f32) -> Block {
+        let script_size = size * SCRIPT_SCALE;
+        match (name, args) {
+            ("sqrt", [arg]) => self.radical(self.expr(arg, size), None, size),
+            ("cbrt", [arg]) => self.radical(
+                self.expr(arg, size),
+                Some(self.text("3", script_size)),
+                size,
+            ),
+            ("abs", [arg]) => self.bars(self.expr(arg, size), size),
+            ("factorial", [arg]) => self.row([
+                self.grouped(arg, !arg.is_atom(), size),
+                self.text("!", size),
+            ]),
+            ("integral", [f, t, a, b]) => self.row([
+                self.big_operator(
+                    "∫",
+                    self.expr(a, script_size),
+                    self.expr(b, script_size),
+                    size,
+                ),
+                self.grouped(f, f.is_sum(), size),
+                self.space(size * 0.2),
+                self.text("d", size),
+                self.expr(t, size),
+            ]),
+            ("sum", [f, n, a, b]) => self.row([
+                self.big_operator(
+                    "Σ",
+                    self.row([
+                        self.expr(n, script_size),
+                        self.text("=", script_size),
+                        self.expr(a, script_size),
+                    ]),
+                    self.expr(b, script_size),
+                    size,
+                ),
+                self.grouped(f, f.is_sum(), size),
+            ]),
+            ("deriv", [f, t, rest @ ..]) => {
+                let operator = self.fraction(
+                    self.text("d", size),
+                    self.row([self.text("d", size), self.expr(t, size)]),
+                    size,
+                );
+                let derivative = self.row([operator, self.grouped(f, !f.is_atom(), size)]);
+                match rest {
+                    // Evaluated at a point, such as `deriv(f, t, 2)`
+                    [a] => {
+                        let at = self.row([
+                            self.expr(t, script_size),
+                            self.text("=", script_size),
+                            self.expr(a, script_size),
+                        ]);
+                        let lowered = -(derivative.descent * 0.6);
+                        let bar = self.bars(derivative, size);
+                        self.script(bar, at, lowered)
+                    }
+                    _ => derivative,
+                }
+            }
+            _ => {
+                let mut inner = Vec::new();
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        inner.push(self.text(", ", size));
+                    }
+                    inner.push(self.expr(arg, size));
+                }
+                let args = self.parens(self.row(inner), size);
+                self.row([self.text(name, size), args])
+            }
+        }
+    }
+
+    /// Lays out `expr` with text of `size`
+    fn expr(&self, expr: &Expr, size: f32) -> Block {
+        let is_negated = |expr: &Expr| matches!(expr, Expr::Negate(_));
+        match expr {
+            Expr::Number(n) => self.text(&n.to_string(), size),
+            Expr::Constant(Constant::Pi) => self.text("π", size),
+            Expr::Constant(Constant::E) => self.text("e", size),
+            Expr::Variable(name) => self.text(name, size),
+            Expr::Negate(inner) => self.row([
+                self.text("−", size),
+                self.grouped(inner, inner.is_sum() | is_negated(inner), size),
+            ]),
+            Expr::Binary { op, lhs, rhs } => match op {
+                BinaryOp::Add => self.row([
+                    self.expr(lhs, size),
+                    self.text(" + ", size),
+                    self.grouped(rhs, is_negated(rhs), size),
+                ]),
+                BinaryOp::Sub => self.row([
+                    self.expr(lhs, size),
+                    self.text(" − ", size),
+                    self.grouped(rhs, rhs.is_sum() | is_negated(rhs), size),
+                ]),
+                BinaryOp::Mul => {
+                    let rhs_parens = rhs.is_sum() | is_negated(rhs);
+                    // Numbers need a multiplication sign to not run into what they're multiplied by
+                    let separator = match !rhs_parens && rhs.starts_with_number() {
+                        true => self.text(" · ", size),
+                        false => self.space(size * 0.1),
+                    };
+                    self.row([
+                        self.grouped(lhs, lhs.is_sum(), size),
+                        separator,
+                        self.grouped(rhs, rhs_parens, size),
+                    ])
+                }
+                BinaryOp::Div => self.fraction(self.expr(lhs, size), self.expr(rhs, size), size),
+                BinaryOp::Pow => self.superscript(
+                    self.grouped(lhs, !lhs.is_atom(), size),
+                    self.expr(rhs, size * SCRIPT_SCALE),
+                ),
+            },
+            Expr::Call { name, args } => self.call(name, args, size),
+        }
+    }
+}
+
+/// Displays `expr` typeset (with fractions stacked, exponents raised, and roots drawn) after `label`
+/// (such as `f(x)`)
+pub fn typeset(ui: &mut egui::Ui, label: Option<&str>, expr: &Expr) -> Response {
+    let color = ui.visuals().text_color();
+    let size = crate::consts::FONT_SIZE;
+
+    let block = {
+        let typesetter = Typesetter { ui, color };
+        let label = label.map(|label| typesetter.text(&format!("{} = ", label), size));
+        typesetter.row(label.into_iter().chain([typesetter.expr(expr, size)]))
+    };
+
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(block.width, block.height()), Sense::hover());
+    let origin = rect.left_top() + vec2(0.0, block.ascent);
+    let painter = ui.painter();
+    for item in block.items {
+        match item {
+            Item::Text(offset, galley) => painter.galley(origin + offset, galley, color),
+            Item::Line(from, to) => {
+                painter.line_segment([origin + from, origin + to], Stroke::new(LINE_WIDTH, color));
+            }
+        }
+    }
+
+    response
+}
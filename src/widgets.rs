@@ -1,5 +1,10 @@
 use crate::misc::Offset;
-use egui::{Id, InnerResponse};
+use egui::{
+    Color32, Galley, Id, InnerResponse, RichText, Stroke, TextEdit, TextFormat, TextStyle,
+    text::LayoutJob,
+};
+use parsing::{ParseError, SymbolTable};
+use std::{ops::Range, sync::Arc};
 
 /// Creates an area ontop of a widget with an y offset
 pub fn widgets_ontop<R>(
@@ -15,3 +20,156 @@ pub fn widgets_ontop<R>(
 
     area.show(ui.ctx(), |ui| add_contents(ui))
 }
+
+/// Displays a `⚠` indicator followed by `error`
+pub fn error_indicator(ui: &mut egui::Ui, error: &str) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("⚠").color(Color32::YELLOW))
+            .on_hover_text(error);
+        ui.label(RichText::new(error).color(Color32::LIGHT_RED).small());
+    });
+}
+
+/// Lays out `text` for a text field, underlining the bytes in `span` (such as the ones causing an error)
+pub fn underlined_text(
+    ui: &egui::Ui,
+    text: &str,
+    span: Option<Range<usize>>,
+    wrap_width: f32,
+) -> Arc<Galley> {
+    let format = TextFormat::simple(
+        TextStyle::Body.resolve(ui.style()),
+        ui.visuals().text_color(),
+    );
+
+    let mut job = LayoutJob::default();
+    // The span may no longer fit `text` if it was edited since the span was found
+    match span.filter(|span| !span.is_empty() && text.get(span.clone()).is_some()) {
+        Some(span) => {
+            let underlined = TextFormat {
+                underline: Stroke::new(1.5, Color32::LIGHT_RED),
+                ..format.clone()
+            };
+            job.append(&text[..span.start], 0.0, format.clone());
+            job.append(&text[span.clone()], 0.0, underlined);
+            job.append(&text[span.end..], 0.0, format);
+        }
+        None => job.append(text, 0.0, format),
+    }
+    job.wrap.max_width = wrap_width;
+
+    ui.painter().layout_job(job)
+}
+
+/// Text field for entering a value as an expression of numbers and constants (such as `pi/2` or `2e`)
+pub struct ExpressionInput {
+    /// Text entered by the user
+    text: String,
+
+    /// Last valid value of `text`
+    value: f64,
+
+    /// Error from evaluating `text`
+    error: Option<String>,
+
+    /// If `inf` and `-inf` are accepted
+    allow_infinite: bool,
+
+    /// Text and constants the input was last evaluated with along with the result, so it's only
+    /// evaluated again once either changes
+    evaluated: Option<(String, SymbolTable, Result<f64, ParseError>)>,
+}
+
+/// Parses `text` as positive or negative infinity (such as `inf`, `-inf`, or `∞`)
+fn parse_infinity(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    matches!(
+        rest.trim().to_lowercase().as_str(),
+        "inf" | "infinity" | "∞"
+    )
+    .then_some(sign * f64::INFINITY)
+}
+
+impl ExpressionInput {
+    pub fn new(value: f64) -> Self {
+        Self {
+            text: value.to_string(),
+            value,
+            error: None,
+            allow_infinite: false,
+            evaluated: None,
+        }
+    }
+
+    /// Accepts `inf` and `-inf` as values
+    pub const fn allow_infinite(mut self) -> Self {
+        self.allow_infinite = true;
+        self
+    }
+
+    /// Last valid value entered
+    pub const fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Error from evaluating the entered text
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Marks the entered value as invalid with `error`
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Displays the text field and evaluates it using the constants in `symbols`.
+    /// Returns the newly entered value if it changed.
+    pub fn show(&mut self, ui: &mut egui::Ui, symbols: &SymbolTable) -> Option<f64> {
+        ui.add(
+            TextEdit::singleline(&mut self.text)
+                .desired_width(crate::consts::FONT_SIZE * 4.0)
+                .hint_text("value"),
+        );
+
+        let result = match &self.evaluated {
+            Some((text, evaluated_symbols, result))
+                if (*text == self.text) && (evaluated_symbols == symbols) =>
+            {
+                result.clone()
+            }
+            _ => {
+                let result = match parse_infinity(&self.text) {
+                    Some(infinity) if self.allow_infinite => Ok(infinity),
+                    _ => symbols.eval_constant(&self.text),
+                };
+                self.evaluated = Some((self.text.clone(), symbols.clone(), result.clone()));
+                result
+            }
+        };
+
+        match result {
+            Ok(value) if value.is_nan() | (!self.allow_infinite && value.is_infinite()) => {
+                self.error = Some("Error: value is not finite".to_owned());
+                None
+            }
+            Ok(value) => {
+                self.error = None;
+                (value != self.value).then_some(value)
+            }
+            Err(error) => {
+                self.error = Some(error.to_string());
+                None
+            }
+        }
+    }
+
+    /// Accepts `value` as the input's current value
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+    }
+}
@@ -1,7 +1,8 @@
-use parsing::{AutoComplete, Hint, Movement};
+use parsing::{AutoComplete, CompletionContext, Hint, Movement};
 
 enum Action<'a> {
     AssertIndex(usize),
+    AssertCursor(usize),
     AssertString(&'a str),
     AssertHint(&'a str),
     SetString(&'a str),
@@ -21,6 +22,14 @@ fn ac_tester(actions: &[Action]) {
                     )
                 }
             }
+            AssertCursor(target_cursor) => {
+                if &ac.cursor != target_cursor {
+                    panic!(
+                        "AssertCursor failed: Current: '{}' Expected: '{}'",
+                        ac.cursor, target_cursor
+                    )
+                }
+            }
             AssertString(target_string) => {
                 if &ac.string != target_string {
                     panic!(
@@ -90,26 +99,31 @@ fn single() {
 fn multi() {
     ac_tester(&[
         SetString("s"),
-        AssertHint("in("),
+        AssertHint("um("),
         Move(Movement::Up),
-        AssertIndex(3),
+        AssertIndex(6),
         AssertString("s"),
-        AssertHint("ignum("),
+        AssertHint("igmoid("),
         Move(Movement::Down),
         AssertIndex(0),
         AssertString("s"),
-        AssertHint("in("),
+        AssertHint("um("),
         Move(Movement::Down),
         AssertIndex(1),
         AssertString("s"),
-        AssertHint("qrt("),
+        AssertHint("in("),
+        Move(Movement::Down),
+        AssertIndex(2),
+        AssertString("s"),
+        AssertHint("ec("),
         Move(Movement::Up),
-        AssertIndex(0),
+        AssertIndex(1),
         AssertString("s"),
         AssertHint("in("),
         Move(Movement::Complete),
-        AssertString("sin("),
-        AssertHint(")"),
+        AssertString("sin()"),
+        AssertCursor(4),
+        AssertHint(""),
         AssertIndex(0),
     ]);
 }
@@ -157,3 +171,110 @@ fn parens() {
         AssertIndex(0),
     ]);
 }
+
+#[test]
+fn user_names() {
+    let mut context = CompletionContext::default();
+    for raw in ["speed(t) = 2t", "velocity = 3", "y = x^2"] {
+        context.define(&parsing::parse_definition(raw).unwrap());
+    }
+
+    let mut ac = AutoComplete::default();
+    ac.set_context(&context);
+    ac.update_string("spe");
+    assert_eq!(ac.hint_text(), "ed(");
+    assert_eq!(ac.completions[0].signature, "speed(t)");
+    assert_eq!(ac.completions[0].description, "speed(t) = 2t");
+    ac.register_movement(&Movement::Complete);
+    assert_eq!(ac.string, "speed()");
+
+    // Constants complete without a parenthesis
+    ac.update_string("2vel");
+    assert_eq!(ac.hint_text(), "ocity");
+
+    // Names that don't complete any built-in function are still completed
+    let mut context = CompletionContext::default();
+    context.define(&parsing::parse_definition("wave(x) = sin(x)").unwrap());
+    ac.set_context(&context);
+    ac.update_string("wa");
+    assert_eq!(parsing::generate_hint("wa"), &Hint::None);
+    assert!(ac.has_hint());
+    assert_eq!(ac.hint_text(), "ve(");
+}
+
+#[test]
+fn documentation() {
+    let completions = CompletionContext::default().completions("atan");
+    let atan2 = completions
+        .iter()
+        .find(|completion| completion.name == "atan2")
+        .unwrap();
+    assert_eq!(atan2.hint, "2(");
+    assert_eq!(atan2.signature, "atan2(y, x)");
+    assert!(!atan2.description.is_empty());
+
+    assert!(CompletionContext::default().completions("2+").is_empty());
+}
+
+#[test]
+fn usage_ranking() {
+    let hints = |context: &CompletionContext| -> Vec<String> {
+        context
+            .completions("s")
+            .into_iter()
+            .map(|completion| completion.hint)
+            .collect()
+    };
+
+    // Without usage, completions are ordered like the built-in hints
+    let mut context = CompletionContext::default();
+    assert_eq!(&hints(&context)[..3], ["um(", "in(", "ec("]);
+
+    context.record_usage("sqrt");
+    context.record_usage("sigmoid");
+    context.record_usage("sigmoid");
+    assert_eq!(context.usage("sigmoid"), 2);
+    assert_eq!(&hints(&context)[..3], ["igmoid(", "qrt(", "um("]);
+
+    let mut ac = AutoComplete::default();
+    ac.set_context(&context);
+    ac.update_string("s");
+    ac.register_movement(&Movement::Complete);
+    assert_eq!(ac.string, "sigmoid()");
+}
+
+#[test]
+fn mid_string() {
+    let mut ac = AutoComplete::default();
+
+    // Completions are inserted at the caret, closing the call they open
+    ac.update("2*sq+1", 4);
+    assert_eq!(ac.hint_text(), "rt(");
+    assert!(!ac.at_end());
+    ac.register_movement(&Movement::Complete);
+    assert_eq!(ac.string, "2*sqrt()+1");
+    assert_eq!(ac.cursor, 7);
+
+    // A parenthesis already after the caret is used instead
+    ac.update("sq(x)", 2);
+    ac.register_movement(&Movement::Complete);
+    assert_eq!(ac.string, "sqrt(x)");
+    assert_eq!(ac.cursor, 5);
+
+    // Parentheses closed later on aren't hinted
+    ac.update("sin(x)", 5);
+    assert!(!ac.has_hint());
+
+    // Nor is anything at the start of, or in the middle of a name in, an existing expression
+    ac.update("sin(x)", 0);
+    assert!(!ac.has_hint());
+    ac.update("sin(x)", 2);
+    assert!(!ac.has_hint());
+
+    // Unclosed parentheses are still hinted
+    ac.update("(sin(x+1", 6);
+    assert_eq!(ac.hint_text(), ")");
+    ac.register_movement(&Movement::Complete);
+    assert_eq!(ac.string, "(sin(x)+1");
+    assert_eq!(ac.cursor, 7);
+}
@@ -21,6 +21,7 @@ fn app_settings_constructor(
         do_roots: false,
         do_intersections: false,
         plot_width: pixel_width,
+        complex: false,
     }
 }
 
@@ -272,3 +273,240 @@ fn middle_function() {
 fn right_function() {
     do_test(Riemann::Right, 0.8800000000000001);
 }
+
+#[test]
+fn improper_integrals() {
+    let mut function = FunctionEntry::default();
+    function.update_string("1/(x^2+1)");
+    function.integral = true;
+
+    let settings = app_settings_constructor(
+        Riemann::Middle,
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        10,
+        10,
+        -1.0,
+        1.0,
+    );
+    function.calculate(true, true, false, settings);
+    let (rectangles, area) = function.integral_data.clone().unwrap();
+    assert!((area - std::f64::consts::PI).abs() < 1e-8);
+
+    // Only the visible part of the integral is drawn
+    assert_eq!(rectangles.len(), settings.integral_num);
+
+    let mut function = FunctionEntry::default();
+    function.update_string("1/x");
+    function.integral = true;
+
+    let settings = app_settings_constructor(Riemann::Middle, 0.0, 1.0, 10, 10, -1.0, 1.0);
+    function.calculate(true, true, false, settings);
+    assert!(function.integral_data.clone().unwrap().1.is_infinite());
+
+    // The pole falls between the points the function is sampled at
+    let mut function = FunctionEntry::default();
+    function.update_string("1/(x-0.33)");
+    function.integral = true;
+
+    function.calculate(true, true, false, settings);
+    assert!(function.integral_data.clone().unwrap().1.is_infinite());
+}
+
+#[test]
+fn named_functions() {
+    use parsing::SymbolTable;
+
+    let mut f = FunctionEntry::default();
+    f.update_string("f(x) = x^2");
+    assert!(f.get_test_result().is_none());
+    assert_eq!(f.name(), Some("f"));
+
+    let mut g = FunctionEntry::default();
+    g.update_string("g(x) = f(x+1) - 3");
+    assert_eq!(g.name(), Some("g"));
+    assert_eq!(g.references(), ["f", "x"]);
+
+    let mut symbols = SymbolTable::default();
+    symbols.insert_function("f", "x", f.expanded_func_str().unwrap());
+    g.resolve(&symbols);
+    assert!(g.get_test_result().is_none());
+    assert_eq!(g.expanded_func_str(), Some("((x+1)^2) - 3"));
+
+    // Changing `f` changes what `g` expands to
+    f.update_string("f(x) = 2x");
+    let mut symbols = SymbolTable::default();
+    symbols.insert_function("f", "x", f.expanded_func_str().unwrap());
+    g.resolve(&symbols);
+    assert_eq!(g.expanded_func_str(), Some("(2*(x+1)) - 3"));
+}
+
+#[test]
+fn complex_functions() {
+    use parsing::Complex;
+
+    // Expressions are functions of `z`, which isn't a variable of real functions
+    let mut function = FunctionEntry::default();
+    function.update_string("z^2 + 1");
+    assert!(function.error(false).is_some());
+    assert!(function.error(true).is_none());
+    let complex = function.complex_function().unwrap();
+    assert_eq!(complex.eval(Complex::I), Complex::ZERO);
+
+    // Functions of their own parameter work either way
+    function.update_string("f(z) = sqrt(z)");
+    assert!(function.error(true).is_none());
+    let complex = function.complex_function().unwrap();
+    assert_eq!(complex.eval(Complex::real(-4.0)), Complex::new(0.0, 2.0));
+
+    function.update_string("floor(z)");
+    assert!(function.complex_function().is_none());
+    assert_eq!(
+        function.error(true).unwrap().to_string(),
+        "Error: floor isn't supported for complex numbers"
+    );
+}
+
+#[test]
+fn saved_colors() {
+    use ytbn_graphing_software::{FunctionManager, palette_color};
+
+    let mut functions = FunctionManager::default();
+    functions.push_empty();
+    functions.push_empty();
+    functions.get_entries_mut()[0].1.color = palette_color(7);
+    let colors: Vec<_> = functions
+        .get_entries()
+        .iter()
+        .map(|(_, function)| function.color)
+        .collect();
+
+    let saved = bincode::serialize(&functions).unwrap();
+    let loaded: FunctionManager = bincode::deserialize(&saved).unwrap();
+    let loaded_colors: Vec<_> = loaded
+        .get_entries()
+        .iter()
+        .map(|(_, function)| function.color)
+        .collect();
+    assert_eq!(loaded_colors, colors);
+}
+
+#[test]
+fn dedupe() {
+    use ytbn_graphing_software::FunctionManager;
+
+    // `2 * x` and `2*x` are the same as `2x`, but only duplicates when drawn the same way
+    let saved = r#"[
+        [1, {"raw_func_str": "2x", "integral": false, "derivative": false, "color": [255, 0, 0, 255]}],
+        [2, {"raw_func_str": "2 * x", "integral": true, "derivative": false, "color": [255, 0, 0, 255]}],
+        [3, {"raw_func_str": "2*x", "integral": false, "derivative": false, "color": [0, 0, 255, 255]}],
+        [4, {"raw_func_str": "2*x", "integral": false, "derivative": false, "color": [255, 0, 0, 255]}]
+    ]"#;
+    let mut functions: FunctionManager = serde_json::from_str(saved).unwrap();
+    assert!(functions.has_duplicates());
+    assert_eq!(functions.dedupe(), 1);
+    assert!(!functions.has_duplicates());
+
+    let remaining: Vec<&str> = functions
+        .get_entries()
+        .iter()
+        .map(|(_, function)| function.raw_func_str.as_str())
+        .collect();
+    assert_eq!(remaining, ["2x", "2 * x", "2*x"]);
+    assert_eq!(
+        functions.get_entries()[2].1.color.to_array(),
+        [0, 0, 255, 255]
+    );
+}
+
+#[test]
+fn saved_derivative_orders() {
+    use parsing::MAX_DERIVATIVE;
+
+    // Orders are limited to ones that can be calculated in reasonable time
+    let saved = r#"{
+        "raw_func_str": "sin(x)", "integral": false, "derivative": false, "color": [255, 0, 0, 255],
+        "nth_derivatives": [[3, [255, 0, 0, 255]], [1000000, [0, 255, 0, 255]]]
+    }"#;
+    let function: FunctionEntry = serde_json::from_str(saved).unwrap();
+    let orders: Vec<_> = function
+        .nth_derivatives
+        .iter()
+        .map(|nth| nth.order)
+        .collect();
+    assert_eq!(orders, [3, MAX_DERIVATIVE]);
+}
+
+#[test]
+fn rewrites_keep_the_function() {
+    use parsing::{BackingFunction, Rewrite, SymbolTable};
+
+    let mut f = FunctionEntry::default();
+    f.update_string("f(x) = x^2 - 1");
+    let mut symbols = SymbolTable::default();
+    symbols.insert_function("f", "x", f.expanded_func_str().unwrap());
+
+    for raw in ["4-x^2", "-x^2 + 2x", "(x+1)^2 - x^2", "g(x) = f(x)·(x+1)"] {
+        for rewrite in Rewrite::ALL {
+            let mut function = FunctionEntry::default();
+            function.update_string(raw);
+            function.resolve(&symbols);
+            let mut original = BackingFunction::new(function.expanded_func_str().unwrap()).unwrap();
+
+            function.rewrite(rewrite);
+            function.resolve(&symbols);
+            assert!(
+                function.get_test_result().is_none(),
+                "{:?} of {}",
+                rewrite,
+                raw
+            );
+
+            // Whatever is written back is read by exmex as the same function
+            let mut rewritten =
+                BackingFunction::new(function.expanded_func_str().unwrap()).unwrap();
+            for x in [-2.5, -1.0, 0.0, 0.5, 3.0] {
+                assert!(
+                    (original.get(0, x) - rewritten.get(0, x)).abs() < 1e-9,
+                    "{:?} of {} gave {}",
+                    rewrite,
+                    raw,
+                    function.raw_func_str
+                );
+            }
+        }
+    }
+
+    let mut function = FunctionEntry::default();
+    function.update_string("(x+1)^2 - x^2");
+    function.rewrite(Rewrite::Expand);
+    assert_eq!(function.raw_func_str, "2·x + 1");
+}
+
+#[test]
+fn polynomial_roots_are_roots() {
+    use parsing::BackingFunction;
+
+    for (raw, real_roots) in [
+        ("4-x^2", 2),
+        ("-x^2+4", 0),
+        ("-(x^2)+4", 2),
+        ("x^3 - 2x + 1", 3),
+        ("(x-1)^2·(x+3)", 2),
+    ] {
+        let mut function = FunctionEntry::default();
+        function.update_string(raw);
+        let roots = function.polynomial_roots().unwrap();
+        let mut backing = BackingFunction::new(function.expanded_func_str().unwrap()).unwrap();
+
+        let real: Vec<f64> = roots
+            .iter()
+            .filter(|root| root.is_real())
+            .map(|root| root.re)
+            .collect();
+        assert_eq!(real.len(), real_roots, "{}: {:?}", raw, roots);
+        for x in real {
+            assert!(backing.get(0, x).abs() < 1e-9, "{} at {}", raw, x);
+        }
+    }
+}
@@ -170,6 +170,26 @@ fn newtons_method() {
     assert_eq!(data, Some(std::f64::consts::PI));
 }
 
+#[test]
+fn palette_color() {
+    use ytbn_graphing_software::palette_color;
+
+    let colors: Vec<_> = (0..64).map(palette_color).collect();
+
+    // Every generated color should be distinct from the others
+    for (i, color) in colors.iter().enumerate() {
+        assert!(
+            !colors[(i + 1)..].contains(color),
+            "color {} ({:?}) is repeated",
+            i,
+            color
+        );
+    }
+
+    // Generated colors should be fully opaque
+    assert!(colors.iter().all(|color| color.a() == 255));
+}
+
 #[test]
 fn to_unicode_hash() {
     use ytbn_graphing_software::to_unicode_hash;
@@ -184,3 +204,374 @@ fn to_chars_array() {
         r"['\u{1f31e}', '\u{2d12c}']"
     );
 }
+
+#[test]
+fn almost_variable() {
+    use std::f64::consts::{E, FRAC_PI_2, PI, SQRT_2};
+    use ytbn_graphing_software::almost_variable;
+
+    let values = [
+        (PI, Some("π")),
+        (-PI, Some("-π")),
+        (FRAC_PI_2, Some("π/2")),
+        (3.0 * PI / 4.0, Some("3π/4")),
+        (2.0 * E, Some("2e")),
+        (E / 3.0, Some("e/3")),
+        (SQRT_2, Some("√2")),
+        (-(3.0_f64.sqrt()), Some("-√3")),
+        (2.0, None),
+        (0.0, None),
+        (1.2345, None),
+        (f64::NAN, None),
+    ];
+
+    for (value, expected) in values {
+        assert_eq!(almost_variable(value).as_deref(), expected, "{}", value);
+    }
+}
+
+#[test]
+fn rational_approximation() {
+    use std::f64::consts::PI;
+    use ytbn_graphing_software::rational_approximation;
+
+    assert_eq!(rational_approximation(0.5, 1000), Some((1, 2)));
+    assert_eq!(rational_approximation(1.0 / 3.0, 1000), Some((1, 3)));
+    assert_eq!(rational_approximation(-0.75, 1000), Some((-3, 4)));
+    assert_eq!(rational_approximation(4.0, 1000), Some((4, 1)));
+    assert_eq!(rational_approximation(PI, 10), Some((22, 7)));
+    assert_eq!(rational_approximation(PI, 1000), Some((355, 113)));
+    assert_eq!(rational_approximation(f64::INFINITY, 1000), None);
+}
+
+#[test]
+fn recognize() {
+    use std::f64::consts::{E, PI};
+    use ytbn_graphing_software::{ExactValue, recognize};
+
+    let values = [
+        (1.0 / 3.0, Some("1/3")),
+        (-22.0 / 7.0, Some("-22/7")),
+        (PI / 6.0, Some("π/6")),
+        (5.0 * PI / 12.0, Some("5π/12")),
+        (E / 4.0, Some("e/4")),
+        (3.0_f64.sqrt() / 2.0, Some("√3/2")),
+        (1.0 / 2.0_f64.sqrt(), Some("√2/2")),
+        (3.0 * 5.0_f64.sqrt(), Some("3√5")),
+        (8.0_f64.sqrt(), Some("2√2")),
+        (-1.0, None),
+        (0.0, None),
+        (0.123456789, None),
+    ];
+
+    for (value, expected) in values {
+        let exact = recognize(value);
+        assert_eq!(
+            exact.map(|exact| exact.to_string()).as_deref(),
+            expected,
+            "{}",
+            value
+        );
+    }
+
+    assert_eq!(
+        recognize(-0.25),
+        Some(ExactValue::Rational {
+            negative: true,
+            numerator: 1,
+            denominator: 4,
+        })
+    );
+}
+
+#[test]
+fn format_exact() {
+    use std::f64::consts::{FRAC_PI_2, SQRT_2};
+    use ytbn_graphing_software::format_exact;
+
+    assert_eq!(format_exact(0.5), "0.5");
+    assert_eq!(format_exact(-0.0), "0");
+    assert_eq!(format_exact(2.0), "2");
+    assert_eq!(format_exact(1.0 / 3.0), "1/3");
+    assert_eq!(format_exact(FRAC_PI_2), "π/2");
+    assert_eq!(format_exact(SQRT_2), "√2");
+    assert_eq!(format_exact(0.123456789), "0.123457");
+    assert_eq!(format_exact(f64::NAN), "undefined");
+}
+
+#[test]
+fn tangent_line() {
+    use egui_plot::PlotPoint;
+    use ytbn_graphing_software::{TangentLine, line_equation};
+
+    // Tangent of x^2 at x = 1
+    let tangent = TangentLine::new(1.0, 1.0, 2.0).unwrap();
+    assert_eq!(tangent.equation(), "y = 2x - 1");
+    assert_eq!(tangent.normal_slope(), -0.5);
+    assert_eq!(tangent.normal_equation(), "y = -0.5x + 1.5");
+
+    // Tangent of x^2 at its minimum
+    let tangent = TangentLine::new(0.0, 0.0, 0.0).unwrap();
+    assert_eq!(tangent.equation(), "y = 0");
+    assert_eq!(tangent.normal_equation(), "x = 0");
+
+    assert_eq!(line_equation(PlotPoint::new(2.0, 3.0), 1.0), "y = x + 1");
+    assert_eq!(line_equation(PlotPoint::new(1.0, 1.0), -1.0), "y = -x + 2");
+    assert_eq!(line_equation(PlotPoint::new(0.0, 0.0), 3.0), "y = 3x");
+    assert_eq!(
+        line_equation(PlotPoint::new(0.0, 0.0), 1.0 / 3.0),
+        "y = (1/3)x"
+    );
+
+    assert_eq!(TangentLine::new(0.0, f64::NAN, 1.0), None);
+}
+
+#[test]
+fn taylor_polynomial() {
+    use std::f64::consts::PI;
+    use ytbn_graphing_software::TaylorPolynomial;
+
+    // e^x about 0
+    let polynomial = TaylorPolynomial::new(0.0, &[1.0, 1.0, 1.0, 1.0]).unwrap();
+    assert_eq!(polynomial.to_string(), "P(x) = 1 + x + 0.5x^2 + (1/6)x^3");
+    assert_eq!(polynomial.eval(0.0), 1.0);
+    assert!((polynomial.eval(1.0) - (8.0 / 3.0)).abs() < 1e-12);
+
+    // sin(x) about π
+    let polynomial = TaylorPolynomial::new(PI, &[0.0, -1.0, 0.0, 1.0]).unwrap();
+    assert_eq!(polynomial.to_string(), "P(x) = -(x - π) + (1/6)(x - π)^3");
+    assert_eq!(polynomial.eval(PI), 0.0);
+
+    // x^2 about -1
+    let polynomial = TaylorPolynomial::new(-1.0, &[1.0, -2.0, 2.0]).unwrap();
+    assert_eq!(polynomial.to_string(), "P(x) = 1 - 2(x + 1) + (x + 1)^2");
+    assert_eq!(polynomial.eval(3.0), 9.0);
+
+    let polynomial = TaylorPolynomial::new(2.0, &[0.0, 0.0]).unwrap();
+    assert_eq!(polynomial.to_string(), "P(x) = 0");
+
+    assert_eq!(TaylorPolynomial::new(0.0, &[1.0, f64::NAN]), None);
+    assert_eq!(TaylorPolynomial::new(0.0, &[]), None);
+}
+
+#[test]
+fn trapezoidal_integral() {
+    use ytbn_graphing_software::trapezoidal_integral;
+
+    // Exact for linear functions
+    assert_eq!(trapezoidal_integral(|x| 2.0 * x, 0.0, 3.0, 1), 9.0);
+    assert_eq!(trapezoidal_integral(|x| 2.0 * x, 3.0, 0.0, 3), -9.0);
+
+    let area = trapezoidal_integral(|x| x * x, 0.0, 1.0, 1000);
+    assert!((area - (1.0 / 3.0)).abs() < 1e-6);
+
+    assert_eq!(trapezoidal_integral(|_| 1.0, 2.0, 2.0, 0), 0.0);
+}
+
+#[test]
+fn accumulate() {
+    use egui_plot::PlotPoint;
+    use ytbn_graphing_software::accumulate;
+
+    let data: Vec<PlotPoint> = [
+        (0.0, 0.0),
+        (1.0, 2.0),
+        (2.0, 4.0),
+        (3.0, f64::NAN),
+        (4.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y)| PlotPoint::new(x, y))
+    .collect();
+
+    let accumulated = accumulate(&data, 1.0);
+    assert_eq!(accumulated.len(), data.len());
+    assert_eq!(accumulated[0], PlotPoint::new(0.0, 1.0));
+    assert_eq!(accumulated[1], PlotPoint::new(1.0, 2.0));
+    assert_eq!(accumulated[2], PlotPoint::new(2.0, 5.0));
+
+    // Undefined past where the function is undefined
+    assert!(accumulated[3].y.is_nan());
+    assert!(accumulated[4].y.is_nan());
+
+    assert!(accumulate(&[], 0.0).is_empty());
+}
+
+#[test]
+fn interpolate() {
+    use egui_plot::PlotPoint;
+    use ytbn_graphing_software::interpolate;
+
+    let data: Vec<PlotPoint> = [(0.0, 0.0), (1.0, 2.0), (2.0, f64::NAN)]
+        .iter()
+        .map(|&(x, y)| PlotPoint::new(x, y))
+        .collect();
+
+    assert_eq!(interpolate(&data, 0.0), Some(0.0));
+    assert_eq!(interpolate(&data, 0.25), Some(0.5));
+    assert_eq!(interpolate(&data, 1.0), Some(2.0));
+    assert_eq!(interpolate(&data, 1.5), None);
+    assert_eq!(interpolate(&data, -1.0), None);
+    assert_eq!(interpolate(&data, 3.0), None);
+}
+
+#[test]
+fn improper_integral() {
+    use std::f64::consts::PI;
+    use ytbn_graphing_software::improper_integral;
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-8;
+
+    // Singularities at the bounds
+    assert!(close(
+        improper_integral(|x| 1.0 / x.sqrt(), 0.0, 1.0, &[0.0]),
+        2.0
+    ));
+    assert!(close(improper_integral(|x| x.ln(), 0.0, 1.0, &[]), -1.0));
+
+    // Infinite bounds
+    assert!(close(
+        improper_integral(|x| (-x).exp(), 0.0, f64::INFINITY, &[]),
+        1.0
+    ));
+    assert!(close(
+        improper_integral(|x| 1.0 / (x * x), f64::NEG_INFINITY, -1.0, &[]),
+        1.0
+    ));
+    assert!(close(
+        improper_integral(|x| x.powf(-1.01), 1.0, f64::INFINITY, &[]),
+        100.0
+    ));
+    assert!(close(
+        improper_integral(
+            |x| 1.0 / (1.0 + x * x),
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            &[]
+        ),
+        PI
+    ));
+
+    // Split around a singularity between the bounds
+    assert!(close(
+        improper_integral(|x| 1.0 / x.abs().sqrt(), -1.0, 1.0, &[0.0]),
+        4.0
+    ));
+
+    // Reversed bounds
+    assert!(close(
+        improper_integral(|x| x * x, 1.0, -1.0, &[]),
+        -2.0 / 3.0
+    ));
+
+    // Divergent
+    assert!(improper_integral(|x| 1.0 / x, 0.0, 1.0, &[0.0]).is_infinite());
+    assert!(improper_integral(|x| 1.0 / x, 1.0, f64::INFINITY, &[]).is_infinite());
+    assert!(improper_integral(|x| 1.0 / (x * x), -1.0, 1.0, &[0.0]).is_infinite());
+    assert!(improper_integral(|_| 1.0, 0.0, f64::INFINITY, &[]).is_infinite());
+
+    // Undefined over part of the bounds
+    assert!(improper_integral(|x| x.sqrt(), -1.0, 1.0, &[]).is_nan());
+}
+
+#[test]
+fn find_singularities() {
+    use ytbn_graphing_software::find_singularities;
+
+    let xs: Vec<f64> = (0..=8).map(|i| i as f64 / 8.0).collect();
+    let close = |a: &[f64], b: &[f64]| {
+        (a.len() == b.len()) && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-12)
+    };
+
+    // Undefined at a point
+    assert!(close(&find_singularities(|x| 1.0 / x, &xs), &[0.0]));
+
+    // Poles between points, whether or not they change sign
+    assert!(close(&find_singularities(|x| 1.0 / (x - 0.3), &xs), &[0.3]));
+    assert!(close(
+        &find_singularities(|x| 1.0 / ((x - 0.7) * (x - 0.7)), &xs),
+        &[0.7]
+    ));
+
+    // Roots and peaks aren't poles
+    assert!(find_singularities(|x| x - 0.3, &xs).is_empty());
+    assert!(find_singularities(|x| (10.0 * x).sin(), &xs).is_empty());
+}
+
+#[test]
+fn format_area() {
+    use ytbn_graphing_software::format_area;
+
+    assert_eq!(format_area(2.0), "2");
+    assert_eq!(format_area(0.333333333333), "0.33333333");
+    assert_eq!(format_area(f64::INFINITY), "diverges");
+    assert_eq!(format_area(f64::NAN), "undefined");
+}
+
+#[test]
+fn to_csv() {
+    use ytbn_graphing_software::to_csv;
+
+    let table = |rows: &[&[&str]]| -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    };
+
+    assert_eq!(
+        to_csv(&table(&[&["x", "x^2"], &["1", "1"], &["2", "4"]])),
+        "x,x^2\r\n1,1\r\n2,4"
+    );
+
+    // Labels with commas or quotes are quoted so the columns stay aligned
+    assert_eq!(
+        to_csv(&table(&[
+            &["x", "atan2(x, 1)", "say \"hi\""],
+            &["1", "0.785", "2"]
+        ])),
+        "x,\"atan2(x, 1)\",\"say \"\"hi\"\"\"\r\n1,0.785,2"
+    );
+}
+
+#[test]
+fn expression_input() {
+    use egui::{CentralPanel, Context, Event, Key, Modifiers, RawInput};
+    use parsing::SymbolTable;
+    use ytbn_graphing_software::ExpressionInput;
+
+    let ctx = Context::default();
+    let mut input = ExpressionInput::new(0.0);
+    let symbols = SymbolTable::default();
+
+    let key = |key: Key| Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: Modifiers::NONE,
+    };
+
+    // Focuses the text field, clears the `0` in it, then types a new value
+    for events in [
+        vec![],
+        vec![key(Key::Tab)],
+        vec![key(Key::End), key(Key::Backspace)],
+        vec![Event::Text("2pi".to_owned())],
+        vec![],
+    ] {
+        let raw_input = RawInput {
+            events,
+            ..RawInput::default()
+        };
+        let _ = ctx.run(raw_input, |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                if let Some(value) = input.show(ui, &symbols) {
+                    input.set_value(value);
+                }
+            });
+        });
+    }
+
+    assert!(input.error().is_none());
+    assert_eq!(input.value(), 2.0 * std::f64::consts::PI);
+}
@@ -106,6 +106,12 @@ fn func_process_test() {
         ("e^sin(x)", "e^sin(x)"),
         ("x**2", "x^2"),
         ("(x+1)(x-3)", "(x+1)*(x-3)"),
+        ("2exp(x)", "2*exp(x)"),
+        ("xerfc(x)", "x*erfc(x)"),
+        ("2×x", "2*x"),
+        ("6÷2", "6/2"),
+        ("−x", "-x"),
+        ("2 x", "2* x"),
     ]);
 
     for (key, value) in values {
@@ -123,8 +129,8 @@ fn func_process_test() {
 fn hints() {
     let values = HashMap::from([
         ("", Hint::Single("x^2")),
-        ("si", Hint::Many(&["n(", "nh(", "gnum("])),
-        ("log", Hint::Many(&["2(", "10("])),
+        ("si", Hint::Many(&["n(", "nh(", "gnum(", "gmoid("])),
+        ("log", Hint::Many(&["(", "2(", "10("])),
         ("cos", Hint::Many(&["(", "h("])),
         ("sin(", Hint::Single(")")),
         ("sqrt", Hint::Single("(")),
@@ -292,3 +298,1163 @@ fn hint_accessor() {
     assert_eq!(Hint::None.single(), None);
     assert_eq!(Hint::None.many(), None);
 }
+
+#[test]
+fn parse_definition() {
+    use parsing::{Definition, ParseErrorKind};
+
+    assert_eq!(
+        parsing::parse_definition("x^2"),
+        Ok(Definition::Expression("x^2".to_owned()))
+    );
+    assert_eq!(
+        parsing::parse_definition("y = x^2"),
+        Ok(Definition::Expression("x^2".to_owned()))
+    );
+    assert_eq!(
+        parsing::parse_definition("f(x) = x^2"),
+        Ok(Definition::Function {
+            name: "f".to_owned(),
+            param: "x".to_owned(),
+            body: "x^2".to_owned()
+        })
+    );
+    assert_eq!(
+        parsing::parse_definition("area2 (t)=t^2"),
+        Ok(Definition::Function {
+            name: "area2".to_owned(),
+            param: "t".to_owned(),
+            body: "t^2".to_owned()
+        })
+    );
+
+    for invalid in [
+        "sin(x) = x",
+        "2f(x) = x",
+        "f(2) = x",
+        "f(x) = x = 2",
+        "(x) = 2",
+    ] {
+        assert!(
+            parsing::parse_definition(invalid).is_err(),
+            "{} should be invalid",
+            invalid
+        );
+    }
+
+    // `x` isn't the parameter of a function of `t`, so it would be mixed up with `t`
+    let error = parsing::parse_definition("f(t) = t*x").unwrap_err();
+    assert_eq!(error.kind, ParseErrorKind::XNotParameter("t".to_owned()));
+    assert_eq!(error.span, Some(9..10));
+    assert!(parsing::parse_definition("g(t) = 2x + t").is_err());
+    assert!(parsing::parse_definition("h(t) = exp(t)").is_ok());
+}
+
+#[test]
+fn process_definition() {
+    let values = HashMap::from([
+        ("2x", "2*x"),
+        ("f(x) = 2x", "2*x"),
+        ("f(t) = 2t + sin(t)", "2*x + sin(x)"),
+    ]);
+
+    for (key, value) in values {
+        let definition = parsing::parse_definition(key).unwrap();
+        assert_eq!(parsing::process_definition(&definition), value);
+    }
+}
+
+#[test]
+fn symbol_table_expand() {
+    let mut symbols = parsing::SymbolTable::default();
+    symbols.insert_function("f", "x", "x^2");
+    symbols.insert_function("g", "x", "(((x+1))^2)-3");
+
+    let values = HashMap::from([
+        ("x", "x"),
+        ("f(x)", "((x)^2)"),
+        ("2*f(x+1)", "2*((x+1)^2)"),
+        ("f(f(x))", "((((x)^2))^2)"),
+        ("exp(x)*f(2)", "exp(x)*((2)^2)"),
+        ("g(x)-f(x)", "(((((x)+1))^2)-3)-((x)^2)"),
+        ("fx", "fx"),
+    ]);
+
+    for (key, value) in values {
+        assert_eq!(symbols.expand(key), Ok(value.to_owned()), "{}", key);
+    }
+
+    for invalid in ["f", "f(x", "f()", "f(x, 2)"] {
+        assert!(
+            symbols.expand(invalid).is_err(),
+            "{} should be invalid",
+            invalid
+        );
+    }
+}
+
+/// Calling a function only replaces the variables its operators don't bind
+#[test]
+fn symbol_table_expand_operators() {
+    let mut symbols = parsing::SymbolTable::default();
+    symbols.insert_function("f", "x", "deriv(sin(x), x)");
+    symbols.insert_function("g", "x", "integral(x*t, t, 0, x)");
+    symbols.insert_function("h", "x", "sum(x^x, x, 1, 3)");
+
+    assert_eq!(
+        symbols.expand("f(2*x)"),
+        Ok("(deriv(sin(x), x,(2*x)))".to_owned())
+    );
+
+    let x: f64 = 0.3;
+    let values = [
+        ("f(2*x)", (2.0 * x).cos()),
+        ("g(2*x)", 4.0 * x.powi(3)),
+        ("h(2*x)", 1.0 + 4.0 + 27.0),
+    ];
+    for (input, expected) in values {
+        let expanded = symbols.expand(input).unwrap();
+        let mut function = parsing::BackingFunction::new(&expanded).unwrap();
+        assert!(
+            (function.get(0, x) - expected).abs() < 1e-6,
+            "{} = {}",
+            input,
+            expanded
+        );
+    }
+}
+
+#[test]
+fn referenced_names() {
+    assert_eq!(
+        parsing::referenced_names("2*f(x)+sin(g(x))*x+2e5"),
+        vec!["f", "g", "sin", "x"]
+    );
+    assert!(parsing::referenced_names("").is_empty());
+}
+
+#[test]
+fn constant_definitions() {
+    use parsing::Definition;
+
+    assert_eq!(
+        parsing::parse_definition("k = 9.81"),
+        Ok(Definition::Constant {
+            name: "k".to_owned(),
+            body: "9.81".to_owned()
+        })
+    );
+    assert!(parsing::parse_definition("pi = 3").is_err());
+    assert!(parsing::parse_definition("e = 3").is_err());
+
+    let mut symbols = parsing::SymbolTable::default();
+    symbols.insert_constant("k", 9.81);
+    symbols.insert_constant("G", -2.5);
+    assert_eq!(symbols.get_constant("k"), Some(9.81));
+    assert_eq!(symbols.get_constant("x"), None);
+
+    let values = HashMap::from([
+        ("2*k", "2*(9.81)"),
+        ("k*x+G", "(9.81)*x+(-2.5)"),
+        ("k(x+1)", "(9.81)*(x+1)"),
+        ("sin(k)", "sin((9.81))"),
+        ("kx", "kx"),
+    ]);
+
+    for (key, value) in values {
+        assert_eq!(symbols.expand(key), Ok(value.to_owned()), "{}", key);
+    }
+
+    assert_eq!(symbols.eval_constant("2k"), Ok(19.62));
+    assert!(symbols.eval_constant("2kx").is_err());
+    assert!(symbols.eval_constant("").is_err());
+}
+
+#[test]
+fn scientific_notation() {
+    let values = HashMap::from([
+        ("6.674e-11", "6.674*10^(-11)"),
+        ("1.5E+3", "1.5*10^(+3)"),
+        ("2e5x", "2*10^(5)*x"),
+        ("2e", "2*e"),
+        ("2e^x", "2*e^x"),
+    ]);
+
+    for (key, value) in values {
+        assert_eq!(parsing::process_func_str(key), value, "{}", key);
+    }
+}
+
+#[test]
+fn eval_constant() {
+    let symbols = parsing::SymbolTable::default();
+
+    let values = HashMap::from([
+        ("2", 2.0),
+        ("-3.5", -3.5),
+        ("pi/2", std::f64::consts::FRAC_PI_2),
+        ("2e", 2.0 * std::f64::consts::E),
+        ("sqrt(4)", 2.0),
+    ]);
+
+    for (key, value) in values {
+        assert_eq!(symbols.eval_constant(key), Ok(value), "{}", key);
+    }
+
+    for invalid in ["", "x", "2x", "a", "sin(", "0/0"] {
+        assert!(
+            symbols.eval_constant(invalid).is_err(),
+            "{} should be invalid",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn numeric_derivative() {
+    use parsing::numeric_derivative;
+
+    assert_eq!(numeric_derivative(f64::sin, 0, 1.0), 1.0_f64.sin());
+    assert!(numeric_derivative(f64::ln, 1, -1.0).is_nan());
+
+    // Function, order, point, expected derivative, and tolerance
+    type Case = (fn(f64) -> f64, usize, f64, f64, f64);
+    let values: [Case; 4] = [
+        (f64::sin, 1, 1.0, 1.0_f64.cos(), 1e-9),
+        (f64::sin, 2, 1.0, -1.0_f64.sin(), 1e-6),
+        (f64::exp, 3, 0.5, 0.5_f64.exp(), 1e-4),
+        (|x| x.powi(4), 4, 10.0, 24.0, 1e-2),
+    ];
+
+    for (f, order, x, expected, tolerance) in values {
+        let derivative = numeric_derivative(f, order, x);
+        assert!(
+            (derivative - expected).abs() <= tolerance,
+            "order {} at {}: {} != {}",
+            order,
+            x,
+            derivative,
+            expected
+        );
+    }
+}
+
+#[test]
+fn operators() {
+    let backing = |func_str: &str| {
+        parsing::BackingFunction::new(&parsing::process_func_str(func_str))
+            .unwrap_or_else(|e| panic!("{}: {}", func_str, e))
+    };
+
+    let values: [(&str, f64, f64); 6] = [
+        ("integral(t^2, t, 0, x)", 3.0, 9.0),
+        ("deriv(sin(x), x)", 1.0, 1.0_f64.cos()),
+        ("deriv(t^3, t, 2)", 0.0, 12.0),
+        ("sum(1/n^2, n, 1, 100)", 0.0, 1.6349839001848923),
+        ("2x + sum(integral(t, t, 0, n), n, 1, 3)", 1.0, 9.0),
+        ("sum(n, n, 1, 0)", 0.0, 0.0),
+    ];
+
+    for (func_str, x, expected) in values {
+        let value = backing(func_str).get(0, x);
+        assert!(
+            (value - expected).abs() <= 1e-6,
+            "{} at {}: {} != {}",
+            func_str,
+            x,
+            value,
+            expected
+        );
+    }
+
+    assert!(backing("integral(t, t, 0, 2)").is_constant());
+    assert!(!backing("deriv(t^2, t)").is_constant());
+
+    // Functions using operators are differentiated numerically
+    let mut function = backing("integral(t^2, t, 0, x)");
+    function.generate_derivative(1);
+    assert!(function.is_numeric(1));
+    assert!((function.get(1, 2.0) - 4.0).abs() <= 1e-4);
+
+    let errors = HashMap::from([
+        (
+            "integral(t^2, t, 0)",
+            "Error: wrong number of arguments to integral, expected integral(f, t, a, b)",
+        ),
+        (
+            "sum(n, n, , 10)",
+            "Error: empty argument to sum, expected sum(f, n, a, b)",
+        ),
+        (
+            "integral(t^2, 2, 0, x)",
+            "Error: invalid variable for integral: 2",
+        ),
+        ("integral(t^2, t, 0, t)", "Error: invalid variable: t"),
+        ("sum(n, n, 1, 10) + n", "Error: invalid variable: n"),
+        (
+            "deriv",
+            "Error: deriv must be called like deriv(f, t) or deriv(f, t, a)",
+        ),
+        ("sum(n, n, 1, 10", "Error: unclosed call to sum"),
+    ]);
+
+    for (func_str, error) in errors {
+        assert_eq!(
+            parsing::BackingFunction::new(&parsing::process_func_str(func_str))
+                .err()
+                .map(|error| error.to_string()),
+            Some(error.to_owned()),
+            "{}",
+            func_str
+        );
+    }
+
+    // The operator's variable hides constants of the same name
+    let mut symbols = parsing::SymbolTable::default();
+    symbols.insert_constant("n", 2.0);
+    assert_eq!(
+        symbols.expand("n*sum(n, n, 1, n)"),
+        Ok("(2)*sum(n, n, 1, (2))".to_owned())
+    );
+}
+
+#[test]
+fn builtin_functions() {
+    use std::f64::consts::PI;
+
+    // Expression, expected value, and relative tolerance
+    let values: [(&str, f64, f64); 38] = [
+        ("gamma(0.5)", PI.sqrt(), 1e-14),
+        ("gamma(5)", 24.0, 1e-14),
+        ("gamma(-1.5)", 2.363271801207355, 1e-14),
+        ("gamma(10.3)", 716430.6890623764, 1e-13),
+        ("factorial(5)", 120.0, 0.0),
+        ("factorial(20)", 2432902008176640000.0, 0.0),
+        ("factorial(0.5)", 0.886226925452758, 1e-14),
+        ("erf(0.5)", 0.5204998778130465, 1e-14),
+        ("erf(2.9)", 0.9999589021219005, 1e-14),
+        ("erf(-3.5)", -0.9999992569016276, 1e-14),
+        ("erfc(2)", 0.004677734981047266, 1e-12),
+        ("erfc(4)", 1.541725790028002e-8, 1e-12),
+        ("erfc(-3.2)", 1.9999939742388482, 1e-14),
+        ("beta(2, 3)", 1.0 / 12.0, 1e-13),
+        ("beta(2.5, 1.5)", 0.19634954084936207, 1e-13),
+        ("besselj0(1)", 0.7651976865579666, 1e-12),
+        ("besselj0(11.9)", 0.025049441699589645, 1e-9),
+        ("besselj0(15)", -0.014224472826780772, 1e-9),
+        ("besselj1(2.5)", 0.49709410246427405, 1e-12),
+        ("besselj1(-3)", -0.3390589585259365, 1e-12),
+        ("besselj1(12.1)", -0.2157489733769248, 1e-10),
+        ("besselj1(20)", 0.06683312417585005, 1e-10),
+        ("sec(0)", 1.0, 1e-15),
+        ("csc(pi/6)", 2.0, 1e-15),
+        ("cot(pi/4)", 1.0, 1e-15),
+        ("asec(2)", PI / 3.0, 1e-15),
+        ("acsc(2)", PI / 6.0, 1e-15),
+        ("acot(1)", PI / 4.0, 1e-15),
+        ("atan2(1, -1)", 3.0 * PI / 4.0, 1e-15),
+        ("min(3, 1, 2)", 1.0, 0.0),
+        ("max(3, 1, 2)", 3.0, 0.0),
+        ("mod(-7, 3)", 2.0, 0.0),
+        ("mod(7, -3)", -2.0, 0.0),
+        ("nCr(5, 2)", 10.0, 0.0),
+        ("nCr(52, 5)", 2598960.0, 0.0),
+        ("nPr(5, 2)", 20.0, 0.0),
+        ("log(2, 8)", 3.0, 1e-15),
+        ("sigmoid(0)", 0.5, 0.0),
+    ];
+
+    let symbols = parsing::SymbolTable::default();
+    for (func_str, expected, tolerance) in values {
+        let value = symbols.eval_constant(func_str).unwrap();
+        assert!(
+            (value - expected).abs() <= tolerance * expected.abs(),
+            "{}: {} != {}",
+            func_str,
+            value,
+            expected
+        );
+    }
+
+    // Undefined values
+    for func_str in [
+        "gamma(0)",
+        "gamma(-2)",
+        "nCr(2.5, 1)",
+        "nPr(-1, 1)",
+        "mod(1, 0)",
+    ] {
+        assert!(symbols.eval_constant(func_str).is_err(), "{}", func_str);
+    }
+
+    let mut function =
+        parsing::BackingFunction::new(&parsing::process_func_str("2erf(x)")).unwrap();
+    assert!((function.get(0, 0.5) - 2.0 * 0.5204998778130465).abs() <= 1e-14);
+
+    let errors = HashMap::from([
+        (
+            "gamma(1, 2)",
+            "Error: wrong number of arguments to gamma, expected gamma(x)",
+        ),
+        (
+            "min(1)",
+            "Error: wrong number of arguments to min, expected min(a, b, ...)",
+        ),
+        (
+            "atan2(1, )",
+            "Error: empty argument to atan2, expected atan2(y, x)",
+        ),
+        ("nCr", "Error: nCr must be called like nCr(n, r)"),
+    ]);
+
+    for (func_str, error) in errors {
+        assert_eq!(
+            parsing::BackingFunction::new(&parsing::process_func_str(func_str))
+                .err()
+                .map(|error| error.to_string()),
+            Some(error.to_owned()),
+            "{}",
+            func_str
+        );
+    }
+}
+
+#[test]
+fn distributions() {
+    // Expression, expected value, and relative tolerance
+    let values: [(&str, f64, f64); 18] = [
+        ("normalpdf(0.5)", 0.3520653267642995, 1e-14),
+        ("normalcdf(1.96)", 0.9750021048517796, 1e-14),
+        ("normalpdf(3, 1, 2)", 0.12098536225957167, 1e-14),
+        ("normalcdf(-1, 1, 2)", 0.15865525393145705, 1e-13),
+        ("tpdf(1.5, 4)", 0.12288, 1e-13),
+        ("tcdf(2, 5)", 0.9490302605850708, 1e-13),
+        ("tcdf(-1, 3)", 0.19550110947788532, 1e-13),
+        ("tcdf(0.7, 30)", 0.7553397782501642, 1e-13),
+        ("chisqpdf(3, 4)", 0.16734762011132237, 1e-13),
+        ("chisqpdf(0.5, 1)", 0.4393912894677224, 1e-13),
+        ("chisqcdf(3.84, 1)", 0.9499564787512949, 1e-13),
+        ("chisqcdf(10, 6)", 0.8753479805169189, 1e-13),
+        ("binompdf(3, 10, 0.4)", 0.214990848, 1e-13),
+        ("binomcdf(3, 10, 0.4)", 0.3822806016, 1e-13),
+        ("binomcdf(40, 100, 0.5)", 0.028443966820490396, 1e-12),
+        ("poissonpdf(2, 3)", 0.22404180765538774, 1e-13),
+        ("poissoncdf(2, 3)", 0.4231900811268435, 1e-13),
+        ("poissoncdf(10, 7.5)", 0.862237983428388, 1e-13),
+    ];
+
+    let symbols = parsing::SymbolTable::default();
+    for (func_str, expected, tolerance) in values {
+        let value = symbols.eval_constant(func_str).unwrap();
+        assert!(
+            (value - expected).abs() <= tolerance * expected.abs(),
+            "{}: {} != {}",
+            func_str,
+            value,
+            expected
+        );
+    }
+
+    // The probability mass functions are step functions that are zero outside of their support
+    for (func_str, expected) in [
+        ("binompdf(3.7, 10, 0.4)", 0.214990848),
+        ("binompdf(-1, 10, 0.4)", 0.0),
+        ("binompdf(11, 10, 0.4)", 0.0),
+        ("poissonpdf(-0.5, 3)", 0.0),
+        ("chisqpdf(-1, 2)", 0.0),
+    ] {
+        let value = symbols.eval_constant(func_str).unwrap();
+        assert!((value - expected).abs() <= 1e-13, "{}: {}", func_str, value);
+    }
+
+    // Undefined values
+    for func_str in [
+        "normalpdf(0, 0, -1)",
+        "tcdf(1, 0)",
+        "chisqcdf(1, -2)",
+        "binompdf(1, 2.5, 0.5)",
+        "binomcdf(1, 2, 1.5)",
+        "poissoncdf(1, -1)",
+    ] {
+        assert!(symbols.eval_constant(func_str).is_err(), "{}", func_str);
+    }
+
+    assert_eq!(
+        parsing::BackingFunction::new(&parsing::process_func_str("normalpdf(x, 1)"))
+            .err()
+            .map(|error| error.to_string()),
+        Some(
+            "Error: wrong number of arguments to normalpdf, expected normalpdf(x) or normalpdf(x, m, s)"
+                .to_owned()
+        )
+    );
+
+    // Shading the probability of being within one standard deviation
+    let value = symbols
+        .eval_constant("integral(normalpdf(t), t, -1, 1)")
+        .unwrap();
+    assert!((value - 0.6826894921370859).abs() <= 1e-9, "{}", value);
+}
+
+#[test]
+fn function_docs() {
+    let docs = parsing::function_docs();
+    let names: Vec<&str> = docs.iter().map(|doc| doc.name).collect();
+    assert_eq!(names, SUPPORTED_FUNCTIONS.to_vec());
+
+    for doc in docs {
+        assert!(doc.usage.starts_with(doc.name), "{}", doc.usage);
+        assert!(!doc.description.is_empty(), "{}", doc.name);
+    }
+}
+
+#[test]
+fn parse_error_spans() {
+    use parsing::ParseErrorKind;
+
+    // Parses `raw` like a function entry does, with the error's spans into `raw`
+    let entry_error = |raw: &str| {
+        let definition = match parsing::parse_definition(raw) {
+            Ok(definition) => definition,
+            Err(error) => return error,
+        };
+        let processed = parsing::process_definition(&definition);
+        let Err(error) = parsing::BackingFunction::new(&processed) else {
+            panic!("{} parsed", raw);
+        };
+        error.in_definition(&processed, raw)
+    };
+
+    let invalid_variable = |name: &str| ParseErrorKind::InvalidVariables(vec![name.to_owned()]);
+
+    // Raw string, kind of error, span of the error, and the raw string after applying the fix
+    let values: [(&str, ParseErrorKind, std::ops::Range<usize>, Option<&str>); 13] = [
+        (
+            "sinn(x)",
+            ParseErrorKind::UnknownFunction("sinn".to_owned()),
+            0..4,
+            Some("sin(x)"),
+        ),
+        (
+            "2x + foo(x)",
+            ParseErrorKind::UnknownFunction("foo".to_owned()),
+            5..8,
+            None,
+        ),
+        (
+            "(x + 1",
+            ParseErrorKind::UnbalancedParens,
+            0..1,
+            Some("(x + 1)"),
+        ),
+        (
+            "x + 1)",
+            ParseErrorKind::UnbalancedParens,
+            5..6,
+            Some("x + 1"),
+        ),
+        ("2x + y", invalid_variable("y"), 5..6, None),
+        ("sum(n, n, 1, 10) + n", invalid_variable("n"), 19..20, None),
+        ("6.674e-11x + z", invalid_variable("z"), 13..14, None),
+        (
+            "integral(t^2 + q, t, 0, x)",
+            invalid_variable("q"),
+            15..16,
+            None,
+        ),
+        ("f(t) = 3t + y", invalid_variable("y"), 12..13, None),
+        (
+            "integral(t^2, 2, 0, x)",
+            ParseErrorKind::InvalidOperatorVariable {
+                operator: "integral".to_owned(),
+                variable: "2".to_owned(),
+            },
+            14..15,
+            None,
+        ),
+        (
+            "2gamma",
+            ParseErrorKind::NotCalled {
+                function: "gamma".to_owned(),
+                usage: "gamma(x)".to_owned(),
+            },
+            1..6,
+            Some("2gamma(x)"),
+        ),
+        (
+            "sin(x)erf(x",
+            ParseErrorKind::UnclosedCall("erf".to_owned()),
+            6..9,
+            Some("sin(x)erf(x)"),
+        ),
+        ("k = 2 = 3", ParseErrorKind::MultipleEquals, 6..7, None),
+    ];
+
+    for (raw, kind, span, fixed) in values {
+        let error = entry_error(raw);
+        assert_eq!(error.kind, kind, "{}", raw);
+        assert_eq!(error.span, Some(span), "{}", raw);
+        assert_eq!(
+            error.fix.and_then(|fix| fix.apply(raw)).as_deref(),
+            fixed,
+            "{}",
+            raw
+        );
+    }
+
+    // Errors from expanding user definitions
+    let mut symbols = parsing::SymbolTable::default();
+    symbols.insert_function("f", "x", "x^2");
+    let raw = "3 + f";
+    let processed = parsing::process_func_str(raw);
+    let error = symbols
+        .expand(&processed)
+        .unwrap_err()
+        .in_source(&processed, raw);
+    assert_eq!(error.to_string(), "Error: f must be called like f(x)");
+    assert_eq!(error.span, Some(4..5));
+    assert_eq!(
+        error.fix.and_then(|fix| fix.apply(raw)).as_deref(),
+        Some("3 + f(x)")
+    );
+
+    // Constant values are located in the raw string they were entered as
+    let error = parsing::SymbolTable::default()
+        .eval_constant("2pi + w")
+        .unwrap_err();
+    assert_eq!(error.span, Some(6..7));
+}
+
+#[test]
+fn expression_ast() {
+    use parsing::{BinaryOp, Constant, Expr};
+
+    let num = |n: f64| Expr::Number(n);
+    let x = || Expr::Variable("x".to_owned());
+    let binary = |op: BinaryOp, lhs: Expr, rhs: Expr| Expr::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    };
+    let call = |name: &str, args: Vec<Expr>| Expr::Call {
+        name: name.to_owned(),
+        args,
+    };
+
+    let values = [
+        (
+            "2sin(x)x",
+            binary(
+                BinaryOp::Mul,
+                binary(BinaryOp::Mul, num(2.0), call("sin", vec![x()])),
+                x(),
+            ),
+        ),
+        ("x**2", binary(BinaryOp::Pow, x(), num(2.0))),
+        (
+            "2×3÷4",
+            binary(
+                BinaryOp::Div,
+                binary(BinaryOp::Mul, num(2.0), num(3.0)),
+                num(4.0),
+            ),
+        ),
+        (
+            "−x^2",
+            binary(BinaryOp::Pow, Expr::Negate(Box::new(x())), num(2.0)),
+        ),
+        (
+            "-(x^2)",
+            Expr::Negate(Box::new(binary(BinaryOp::Pow, x(), num(2.0)))),
+        ),
+        (
+            "pi(2x+1)",
+            binary(
+                BinaryOp::Mul,
+                Expr::Constant(Constant::Pi),
+                binary(
+                    BinaryOp::Add,
+                    binary(BinaryOp::Mul, num(2.0), x()),
+                    num(1.0),
+                ),
+            ),
+        ),
+        (
+            "e^sin(x)",
+            binary(
+                BinaryOp::Pow,
+                Expr::Constant(Constant::E),
+                call("sin", vec![x()]),
+            ),
+        ),
+        (
+            "2^3^x",
+            binary(
+                BinaryOp::Pow,
+                binary(BinaryOp::Pow, num(2.0), num(3.0)),
+                x(),
+            ),
+        ),
+        ("6.674e-11x", binary(BinaryOp::Mul, num(6.674e-11), x())),
+        ("atan2(1, x)", call("atan2", vec![num(1.0), x()])),
+    ];
+
+    for (input, expected) in values {
+        assert_eq!(parsing::parse_expression(input), Ok(expected), "{}", input);
+    }
+}
+
+#[test]
+fn expression_ast_errors() {
+    use parsing::ParseErrorKind;
+
+    let values = [
+        ("2 + ", ParseErrorKind::ExpectedExpression, 4..4),
+        (
+            "2 $ 3",
+            ParseErrorKind::UnexpectedToken("$".to_owned()),
+            2..3,
+        ),
+        (
+            "2+*3",
+            ParseErrorKind::UnexpectedToken("*".to_owned()),
+            2..3,
+        ),
+        ("(x + 1", ParseErrorKind::UnbalancedParens, 0..1),
+        ("x)", ParseErrorKind::UnbalancedParens, 1..2),
+        (
+            "sin(x",
+            ParseErrorKind::UnclosedCall("sin".to_owned()),
+            0..3,
+        ),
+        (
+            "1.2.3",
+            ParseErrorKind::InvalidNumber("1.2.3".to_owned()),
+            0..5,
+        ),
+    ];
+
+    for (input, kind, span) in values {
+        let error = parsing::parse_expression(input).unwrap_err();
+        assert_eq!(error.kind, kind, "{}", input);
+        assert_eq!(error.span, Some(span), "{}", input);
+    }
+}
+
+#[test]
+fn latex() {
+    let values = HashMap::from([
+        ("x^2/2", "\\frac{x^{2}}{2}"),
+        ("2sin(x)x", "2 \\sin\\left(x\\right) x"),
+        ("sqrt(x+1)", "\\sqrt{x + 1}"),
+        ("cbrt(x)", "\\sqrt[3]{x}"),
+        ("(x+1)(x-1)", "\\left(x + 1\\right) \\left(x - 1\\right)"),
+        ("(x+1)^2", "\\left(x + 1\\right)^{2}"),
+        ("-x^2", "\\left(-x\\right)^{2}"),
+        ("-(x^2)", "-\\left(x^{2}\\right)"),
+        ("x - (x + 1)", "x - \\left(x + 1\\right)"),
+        ("2*3", "2 \\cdot 3"),
+        ("x*-2", "x \\left(-2\\right)"),
+        ("e^(2x)", "e^{2 x}"),
+        ("pi*tau", "\\pi \\tau"),
+        ("abs(x)", "\\left|x\\right|"),
+        ("asin(x)", "\\arcsin\\left(x\\right)"),
+        ("besselj0(x)", "\\operatorname{besselj0}\\left(x\\right)"),
+        ("f(x+1)", "f\\left(x + 1\\right)"),
+        ("integral(t^2, t, 0, x)", "\\int_{0}^{x} t^{2} \\, dt"),
+        ("sum(1/n^2, n, 1, 10)", "\\sum_{n=1}^{10} \\frac{1}{n^{2}}"),
+        (
+            "deriv(deriv(sin(x), x), x)",
+            "\\frac{d^{2}}{dx^{2}} \\sin\\left(x\\right)",
+        ),
+    ]);
+
+    for (key, value) in values {
+        let expr = parsing::parse_expression(key).unwrap();
+        assert_eq!(expr.to_latex(), value, "{}", key);
+    }
+
+    let function = parsing::BackingFunction::new("x^2").unwrap();
+    assert_eq!(
+        function.derivative_expression(0),
+        parsing::parse_expression("x^2").ok()
+    );
+}
+
+#[test]
+fn latex_input() {
+    let values = HashMap::from([
+        ("\\frac{1}{2}x^{2}", "((1)/(2))*x^(2)"),
+        ("\\frac12", "((1)/(2))"),
+        ("\\sqrt{x}", "sqrt(x)"),
+        ("\\sqrt[3]{x}", "cbrt(x)"),
+        ("\\sin x", "sin(x)"),
+        ("\\sin\\left(x\\right)", "sin(x)"),
+        ("\\arcsin{x}", "asin(x)"),
+        ("2\\cdot\\pi", "2*π"),
+        ("\\left(x+1\\right)^{2}", "(x+1)^(2)"),
+        ("\\left|x\\right|", "abs(x)"),
+        ("e^{2x}", "e^(2*x)"),
+        ("\\log_{2}x", "log2(x)"),
+        ("\\ln x", "ln(x)"),
+    ]);
+
+    for (key, value) in values {
+        test_process_helper(&parsing::latex_to_plain(key), value);
+    }
+
+    // LaTeX written by `to_latex` reads back as the same expression
+    for input in [
+        "x^2/2",
+        "(x+1)(x-1)",
+        "sqrt(x+1)",
+        "2*3",
+        "e^(2x)",
+        "asin(x)",
+    ] {
+        let expr = parsing::parse_expression(input).unwrap();
+        let plain = parsing::process_func_str(&parsing::latex_to_plain(&expr.to_latex()));
+        assert_eq!(parsing::parse_expression(&plain), Ok(expr), "{}", input);
+    }
+
+    assert!(parsing::is_latex("\\frac{1}{2}"));
+    assert!(!parsing::is_latex("sin(x)/2"));
+    assert!(func_is_valid(&parsing::latex_to_plain(
+        "\\frac{x^{2}}{2} + \\sqrt{x}"
+    )));
+    assert!(!func_is_valid(&parsing::latex_to_plain("\\unknown{x}")));
+}
+
+#[test]
+fn asciimath_input() {
+    let values = HashMap::from([
+        ("frac(1)(2) x^2", "((1)/(2)) x^2"),
+        ("sqrt x xx 2", "sqrt(x) * 2"),
+        ("6 -: 3", "6 / 3"),
+        ("root(3)(x)", "((x)^(1/(3)))"),
+        ("arcsin x", "asin(x)"),
+        ("log_2 x", "log2(x)"),
+        ("log x", "log10(x)"),
+        ("log10(x)", "log10(x)"),
+        ("[x+1]^2", "(x+1)^2"),
+        ("sin(cos(x))", "sin(cos(x))"),
+    ]);
+
+    for (key, value) in values {
+        assert_eq!(parsing::asciimath_to_plain(key), value, "{}", key);
+    }
+}
+
+#[test]
+fn canonical_format() {
+    let values = HashMap::from([
+        ("2sin(x)x", "2·sin(x)·x"),
+        ("x**2+1", "x^2 + 1"),
+        ("  x  -  ( x + 1 )", "x - (x + 1)"),
+        ("2×x÷3", "2·x/3"),
+        ("pi e", "π·e"),
+        ("((x))", "x"),
+        ("(x+1)(x-1)", "(x + 1)·(x - 1)"),
+        ("x*-2", "x·(-2)"),
+        ("-(-x)", "-(-x)"),
+        ("-x^2", "(-x)^2"),
+        ("-(x^2)", "-(x^2)"),
+        ("(-x)^2", "(-x)^2"),
+        ("2^(3^x)", "2^(3^x)"),
+        ("(2^3)^x", "(2^3)^x"),
+        ("2^3^x", "(2^3)^x"),
+        ("e^(-x)", "e^(-x)"),
+        ("1/(2x)", "1/(2·x)"),
+        ("1/x^2", "1/x^2"),
+        ("atan2( 1 ,x )", "atan2(1, x)"),
+        ("2.50x", "2.5·x"),
+        ("1e3", "1000"),
+    ]);
+
+    for (key, value) in values {
+        let formatted = parsing::format_expression(key).unwrap();
+        assert_eq!(formatted, value, "{}", key);
+
+        // Formatting is idempotent
+        assert_eq!(parsing::format_expression(&formatted).unwrap(), formatted);
+
+        // and doesn't change the value of the function
+        let mut before = parsing::BackingFunction::new(&parsing::process_func_str(key)).unwrap();
+        let mut after =
+            parsing::BackingFunction::new(&parsing::process_func_str(&formatted)).unwrap();
+        for x in [-1.5, 0.5, 2.0] {
+            let (a, b) = (before.get(0, x), after.get(0, x));
+            assert!(
+                (a == b) | (a.is_nan() && b.is_nan()),
+                "{}: {} != {}",
+                key,
+                a,
+                b
+            );
+        }
+    }
+
+    assert!(parsing::format_expression("sin(x").is_err());
+
+    let definition = parsing::parse_definition("f( t )=2t").unwrap();
+    assert_eq!(definition.canonical(), Ok("f(t) = 2·t".to_owned()));
+    let definition = parsing::parse_definition("y = x**2").unwrap();
+    assert_eq!(definition.canonical(), Ok("x^2".to_owned()));
+}
+
+#[test]
+fn rewrites() {
+    use parsing::Rewrite;
+
+    let values = [
+        (Rewrite::Simplify, "2x+3x", "5·x"),
+        (Rewrite::Simplify, "x*x*2", "2·x^2"),
+        (Rewrite::Simplify, "x/x + 0*x", "1"),
+        (Rewrite::Simplify, "sin(x)^2+cos(x)^2", "1"),
+        (Rewrite::Simplify, "3sin(x)^2 + x + 3cos(x)^2", "x + 3"),
+        (Rewrite::Simplify, "sin(x)/cos(x)", "tan(x)"),
+        (Rewrite::Simplify, "4sin(x)cos(x)", "2·sin(2·x)"),
+        (Rewrite::Simplify, "cos(-x) + sin(-x)", "cos(x) - sin(x)"),
+        (Rewrite::Simplify, "ln(exp(2x))", "2·x"),
+        (Rewrite::Simplify, "sqrt(x)^2", "x"),
+        (Rewrite::Simplify, "(x+1)(x+1)", "(x + 1)^2"),
+        (Rewrite::Simplify, "2(x+1) - 2", "2·x"),
+        (Rewrite::Simplify, "x/3 + x/6", "x/2"),
+        (Rewrite::Simplify, "2^3 + cos(0)", "9"),
+        (Rewrite::Expand, "(x+1)^2", "x^2 + 2·x + 1"),
+        (Rewrite::Expand, "(x-1)(x+1)", "x^2 - 1"),
+        (Rewrite::Expand, "2x(x+sin(x))", "2·x^2 + 2·x·sin(x)"),
+        (Rewrite::Factor, "x^2-1", "(x + 1)·(x - 1)"),
+        (Rewrite::Factor, "x^2+2x+1", "(x + 1)^2"),
+        (Rewrite::Factor, "2x^3+4x^2", "2·x^2·(x + 2)"),
+        (Rewrite::Factor, "2x^2-x-1", "(2·x + 1)·(x - 1)"),
+        (Rewrite::Factor, "-x^3+x", "-x·(x + 1)·(x - 1)"),
+        (Rewrite::Factor, "x^2+1", "x^2 + 1"),
+        (Rewrite::Factor, "(x^2-1)/(x-1)", "x + 1"),
+        (Rewrite::Factor, "1/x + 2/x^2", "(x + 2)/x^2"),
+        (Rewrite::Factor, "sin(x)^2+sin(x)", "sin(x)·(sin(x) + 1)"),
+    ];
+
+    for (rewrite, key, value) in values {
+        let expr = parsing::parse_expression(key).unwrap();
+        let rewritten = rewrite.apply(&expr, "x").to_string();
+        assert_eq!(rewritten, value, "{:?} {}", rewrite, key);
+
+        // Rewriting doesn't change the value of the function
+        let mut before = parsing::BackingFunction::new(&parsing::process_func_str(key)).unwrap();
+        let mut after =
+            parsing::BackingFunction::new(&parsing::process_func_str(&rewritten)).unwrap();
+        for x in [0.5, 1.5, 2.0] {
+            let (a, b) = (before.get(0, x), after.get(0, x));
+            assert!((a - b).abs() < 1e-9, "{}: {} != {}", key, a, b);
+        }
+    }
+
+    let definition = parsing::parse_definition("f(t) = (t+1)^2").unwrap();
+    assert_eq!(
+        definition.rewritten(Rewrite::Expand),
+        Ok("f(t) = t^2 + 2·t + 1".to_owned())
+    );
+    let definition = parsing::parse_definition("f(t) = t^2 - 1").unwrap();
+    assert_eq!(
+        definition.rewritten(Rewrite::Factor),
+        Ok("f(t) = (t + 1)·(t - 1)".to_owned())
+    );
+}
+
+#[test]
+fn polynomial_roots() {
+    let roots = |input: &str| {
+        parsing::parse_expression(input)
+            .unwrap()
+            .polynomial_roots("x")
+            .map(|roots| {
+                roots
+                    .into_iter()
+                    .map(|root| (root.re, root.im, root.multiplicity))
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    // Rational roots are exact
+    assert_eq!(roots("x^2 - 1"), Some(vec![(-1.0, 0.0, 1), (1.0, 0.0, 1)]));
+    assert_eq!(
+        roots("(x-1)^2(x+2)"),
+        Some(vec![(-2.0, 0.0, 1), (1.0, 0.0, 2)])
+    );
+    assert_eq!(
+        roots("x^3 + x^2"),
+        Some(vec![(-1.0, 0.0, 1), (0.0, 0.0, 2)])
+    );
+    assert_eq!(
+        roots("0.5x^2 - 0.125"),
+        Some(vec![(-0.5, 0.0, 1), (0.5, 0.0, 1)])
+    );
+    assert_eq!(roots("2x + 3"), Some(vec![(-1.5, 0.0, 1)]));
+
+    // Roots of quadratics are found with the quadratic formula
+    let sqrt_2 = std::f64::consts::SQRT_2;
+    let found = roots("x^2 - 2").unwrap();
+    assert_eq!(found.len(), 2);
+    for (found, expected) in found.iter().zip([-sqrt_2, sqrt_2]) {
+        assert!((found.0 - expected).abs() < 1e-15, "{:?}", found);
+        assert_eq!((found.1, found.2), (0.0, 1));
+    }
+    assert_eq!(roots("x^2 + 1"), Some(vec![(0.0, -1.0, 1), (0.0, 1.0, 1)]));
+
+    // and the rest numerically
+    let polynomial =
+        parsing::Polynomial::from_expr(&parsing::parse_expression("x^5 - x - 1").unwrap(), "x")
+            .unwrap();
+    assert_eq!(polynomial.degree(), 5);
+    let found = polynomial.roots();
+    assert_eq!(found.len(), 5);
+    assert!(found[0].is_real() && found[1..].iter().all(|root| !root.is_real()));
+    assert!((found[0].re - 1.1673039782614187).abs() < 1e-15);
+    assert!(polynomial.eval(found[0].re).abs() < 1e-14);
+
+    let found = roots("(x^2 - 2)^2 (x^2 + x + 1)").unwrap();
+    assert_eq!(found.len(), 4);
+    for (found, expected) in found.iter().zip([-sqrt_2, sqrt_2]) {
+        assert!((found.0 - expected).abs() < 1e-6, "{:?}", found);
+        assert_eq!((found.1, found.2), (0.0, 2));
+    }
+    assert!((found[2].0 + 0.5).abs() < 1e-12);
+    assert!((found[2].1 + 0.75f64.sqrt()).abs() < 1e-12);
+
+    // Constants are multiplied into coefficients
+    let found = roots("x - pi").unwrap();
+    assert_eq!(found, vec![(std::f64::consts::PI, 0.0, 1)]);
+
+    assert_eq!(roots("sin(x)"), None);
+    assert_eq!(roots("1/x"), None);
+    assert_eq!(roots("5"), None);
+}
+
+#[test]
+fn complex_functions() {
+    use parsing::{Complex, ComplexFunction};
+
+    let eval = |input: &str, z: Complex| ComplexFunction::new(input, "z").unwrap().eval(z);
+    let close = |a: Complex, b: Complex| (a - b).abs() < 1e-12;
+
+    // Complex arithmetic doesn't leave the domain of real functions undefined
+    assert_eq!(eval("sqrt(-1)", Complex::ZERO), Complex::I);
+    assert_eq!(eval("i^2", Complex::ZERO), Complex::real(-1.0));
+    assert!(close(eval("exp(i pi)", Complex::ZERO), Complex::real(-1.0)));
+    assert!(close(
+        eval("ln(-1)", Complex::ZERO),
+        Complex::new(0.0, std::f64::consts::PI)
+    ));
+    assert_eq!(eval("z^2 + 1", Complex::I), Complex::ZERO);
+    assert_eq!(eval("1/z", Complex::I), Complex::new(0.0, -1.0));
+    assert!(close(
+        eval("sin(z)", Complex::new(1.0, 2.0)),
+        Complex::new(1f64.sin() * 2f64.cosh(), 1f64.cos() * 2f64.sinh())
+    ));
+    assert!(close(
+        eval("sin(asin(z))", Complex::new(2.0, 0.5)),
+        Complex::new(2.0, 0.5)
+    ));
+    assert!(close(
+        eval("tan(atan(z))", Complex::new(0.5, -1.5)),
+        Complex::new(0.5, -1.5)
+    ));
+
+    // Functions and names that complex numbers can't use
+    assert_eq!(
+        ComplexFunction::new("floor(z)", "z")
+            .unwrap_err()
+            .to_string(),
+        "Error: floor isn't supported for complex numbers"
+    );
+    assert_eq!(
+        ComplexFunction::new("z + y", "z").unwrap_err().to_string(),
+        "Error: invalid variable: y"
+    );
+
+    // Zeros and poles with their orders
+    let zeros_and_poles = |input: &str| {
+        ComplexFunction::new(input, "z")
+            .unwrap()
+            .zeros_and_poles(Complex::new(-3.1, -3.1), Complex::new(3.3, 2.9), [32, 30])
+            .into_iter()
+            .map(|found| {
+                let rounded = |x: f64| (x * 1e6).round() / 1e6 + 0.0;
+                ((rounded(found.z.re), rounded(found.z.im)), found.order)
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        zeros_and_poles("(z^2 + 1)/(z - 2)"),
+        vec![((0.0, -1.0), 1), ((2.0, 0.0), -1), ((0.0, 1.0), 1)]
+    );
+    assert_eq!(zeros_and_poles("z^2"), vec![((0.0, 0.0), 2)]);
+    assert_eq!(zeros_and_poles("1/(z - 1)^2"), vec![((1.0, 0.0), -2)]);
+    assert!(zeros_and_poles("exp(z)").is_empty());
+}
+
+/// Unary minus only applies to what directly follows it, the same in the AST as in exmex
+#[test]
+fn negation() {
+    use parsing::{BackingFunction, Complex, ComplexFunction};
+
+    let values = [("-x^2", "(-x)^2"), ("-2^x", "(-2)^x"), ("4-x^2", "4 - x^2")];
+    for (input, canonical) in values {
+        assert_eq!(parsing::format_expression(input).unwrap(), canonical);
+
+        let mut function = BackingFunction::new(&parsing::process_func_str(input)).unwrap();
+        let ast = ComplexFunction::new(input, "x").unwrap();
+        for x in [-1.0, 2.0, 3.0] {
+            let (expected, value) = (function.get(0, x), ast.eval(Complex::real(x)));
+            assert!(
+                ((value.re - expected).abs() < 1e-9) && (value.im.abs() < 1e-9),
+                "{} at {}: {} != {}",
+                input,
+                x,
+                value.re,
+                expected
+            );
+        }
+    }
+}
+
+/// Complex mode gives the same values as real mode on the real axis
+#[test]
+fn complex_matches_real() {
+    use parsing::{BackingFunction, Complex, ComplexFunction};
+
+    let inputs = [
+        "-z^2",
+        "4-z^2",
+        "-z^3 + 2z",
+        "2--z^2",
+        "2^-z^2",
+        "2^3^z",
+        "-z^-2",
+        "-sin(z)^2",
+        "exp(-z)/2",
+    ];
+    for input in inputs {
+        let complex = ComplexFunction::new(input, "z").unwrap();
+        let mut real =
+            BackingFunction::new(&parsing::process_func_str(&input.replace('z', "x"))).unwrap();
+        for x in [-1.5, 0.5, 2.0] {
+            let (expected, value) = (real.get(0, x), complex.eval(Complex::real(x)));
+            assert!(
+                ((value.re - expected).abs() < 1e-9) && (value.im.abs() < 1e-9),
+                "{} at {}: {} != {}",
+                input,
+                x,
+                value.re,
+                expected
+            );
+        }
+    }
+    assert_eq!(
+        ComplexFunction::new("-z^2", "z")
+            .unwrap()
+            .eval(Complex::real(2.0)),
+        Complex::real(4.0)
+    );
+}